@@ -149,7 +149,12 @@ pub fn braced_expr(items: &str) -> String {
 }
 
 pub fn struct_expr(name: &str, fields: &[(String, String)]) -> String {
-    let mut xs = format!("(struct {name}){{");
+    return compound_expr(&format!("struct {name}"), fields);
+}
+
+// Compound literal of a struct or union type, like `(union name){.x = 1,}`
+pub fn compound_expr(typ: &str, fields: &[(String, String)]) -> String {
+    let mut xs = format!("({typ}){{");
     for (field, value) in fields {
         xs.push_str(&format!(".{field} = {value},"));
     }
@@ -166,6 +171,25 @@ pub fn struct_expr_ordered(name: &str, fields: &[String]) -> String {
     xs
 }
 
+// Initializer of a static variable, like {.x = 1,.y = 2,}
+pub fn initializer_expr(fields: &[(String, String)]) -> String {
+    let mut xs = String::from("{");
+    for (field, value) in fields {
+        xs.push_str(&format!(".{field} = {value},"));
+    }
+    xs.push('}');
+    xs
+}
+
+pub fn initializer_expr_ordered(fields: &[String]) -> String {
+    let mut xs = String::from("{");
+    for value in fields {
+        xs.push_str(&format!("{value},"));
+    }
+    xs.push('}');
+    xs
+}
+
 pub fn function_call_expr(name: &str, arguments: &str) -> String {
     return format!("{name}({arguments})");
 }
@@ -178,6 +202,10 @@ pub fn cast_expr(expr: &str, typ: &str) -> String {
     return format!("({typ})({expr})")
 }
 
+pub fn ternary_expr(condition: &str, r#true: &str, r#false: &str) -> String {
+    return format!("(({condition}) ? ({true}) : ({false}))")
+}
+
 pub fn statement_list(statements: &[String]) -> String {
     let mut s = String::new();
     for x in statements {
@@ -233,6 +261,14 @@ pub fn while_stmt(condition: &str, block: &str) -> String {
     return format!("while({condition})\n{{{block}}};\n");
 }
 
+pub fn break_stmt() -> String {
+    return String::from("break;\n");
+}
+
+pub fn variable_decl_stmt(r#type: &str, name: &str) -> String {
+    return format!("{type} {name};\n");
+}
+
 pub fn variable_stmt(r#type: &str, name: &str, value: &str) -> String {
     return format!("{type} {name} = {value};\n");
 }
@@ -256,6 +292,11 @@ pub fn struct_stmt(name: &str, body: &str) -> String {
     return format!("struct {name} {{ {body} }};\n");
 }
 
+// Struct or union with attributes, like `struct __elo_packed name { ... };`
+pub fn aggregate_stmt(keyword: &str, attributes: &str, name: &str, body: &str) -> String {
+    return format!("{keyword} {attributes}{name} {{ {body} }};\n");
+}
+
 pub fn typedef_stmt(body: &str) -> String {
     return format!("typedef {body};");
 }
@@ -1,6 +1,7 @@
 use crate::c;
 use elo_ir::cir::{self, Program};
 use elo_lexer::span::Span;
+use std::collections::HashSet;
 
 pub struct Generator {
     pub input: Program,
@@ -9,6 +10,94 @@ pub struct Generator {
     tuples: Vec<Vec<cir::Typing>>,
     fn_types: Vec<(cir::Typing, Vec<cir::Typing>)>,
     arrays: Vec<(cir::Typing, usize)>,
+    // Number of struct update helpers generated so far (see StructInit)
+    // Number of closures generated so far
+    closures: usize,
+    // Number of temporary variables generated so far (see generate_branch)
+    temporaries: usize,
+    // Temporaries holding the values of the enclosing matches on enums with payloads, innermost last
+    matched: Vec<String>,
+    // Deferred code of every scope of the current function, innermost scope last
+    defers: Vec<Vec<Deferred>>,
+    // Body of the init function, which the runtime calls before main to initialize the globals
+    init: String,
+    // C return type of the current function, used to hold the returned value while the defers run
+    return_type: String,
+    // Functions whose returned values may hold their temporaries, which the scope of the caller releases
+    transferring: HashSet<String>,
+    // Statements computing the values of the expressions that need them (see has_statements),
+    // emitted before the statement being generated
+    prelude: String,
+}
+
+// Code that runs when a scope is exited
+#[derive(Clone)]
+enum Deferred {
+    Block(cir::Block),
+    // Drop the runtime temporaries created since the mark in this variable was taken
+    Release(String),
+}
+
+// Whether the expression can be the initializer of a C static variable
+fn is_constant(expr: &cir::Expression) -> bool {
+    match &expr.data {
+        cir::ExpressionData::Integer { .. }
+        | cir::ExpressionData::Float { .. }
+        | cir::ExpressionData::Bool { .. }
+        | cir::ExpressionData::Null => true,
+        cir::ExpressionData::EnumVariant { origin, .. } => !origin.is_tagged(),
+        cir::ExpressionData::Cast { expr, .. } => is_constant(expr),
+        cir::ExpressionData::UnaryOperation { operand, .. } => is_constant(operand),
+        cir::ExpressionData::BinaryOperation { left, right, .. } => is_constant(left) && is_constant(right),
+        _ => false,
+    }
+}
+
+// Whether the expression is generated as statements followed by the temporary holding its value,
+// like the if and match expressions with statements in their branches
+fn has_statements(expr: &cir::Expression) -> bool {
+    let any = |exprs: &[&cir::Expression]| exprs.iter().any(|e| has_statements(e));
+    match &expr.data {
+        cir::ExpressionData::Block { .. } => true,
+        // Only a variable can be compared with each pattern without a temporary
+        cir::ExpressionData::Match { value, arms, .. } => {
+            !matches!(value.data, cir::ExpressionData::Identifier { .. }) || arms.iter().any(|(_, branch)| has_statements(branch))
+        }
+        cir::ExpressionData::If { condition, r#true, r#false, .. } => any(&[condition, r#true, r#false]),
+        cir::ExpressionData::BinaryOperation { left, right, .. } => any(&[left, right]),
+        cir::ExpressionData::ArraySubscript { origin, index }
+        | cir::ExpressionData::SliceSubscript { origin, index, .. }
+        | cir::ExpressionData::StrSubscript { origin, index }
+        | cir::ExpressionData::PointerSubscript { origin, index } => any(&[origin, index]),
+        cir::ExpressionData::UnaryOperation { operand: expr, .. }
+        | cir::ExpressionData::FieldAccess { origin: expr, .. }
+        | cir::ExpressionData::TupleAccess { origin: expr, .. }
+        | cir::ExpressionData::EnumName { expr, .. }
+        | cir::ExpressionData::EnumFromInt { expr, .. }
+        | cir::ExpressionData::Cast { expr, .. }
+        | cir::ExpressionData::StrToCStr { expr }
+        | cir::ExpressionData::CStrToStr { expr } => has_statements(expr),
+        cir::ExpressionData::ArrayLiteral { exprs, .. }
+        | cir::ExpressionData::SlicePack { exprs, .. }
+        | cir::ExpressionData::Tuple { exprs, .. }
+        | cir::ExpressionData::FunctionCall { arguments: exprs, .. }
+        | cir::ExpressionData::IntrinsicCall { arguments: exprs, .. } => exprs.iter().any(has_statements),
+        cir::ExpressionData::IndirectCall { function, arguments, .. } => has_statements(function) || arguments.iter().any(has_statements),
+        cir::ExpressionData::EnumInit { fields, .. } => fields.iter().any(|(_, e)| has_statements(e)),
+        cir::ExpressionData::StructInit { fields, base, .. } => fields.iter().any(|(_, e)| has_statements(e)) || base.as_deref().is_some_and(has_statements),
+        _ => false,
+    }
+}
+
+// Whether the constant has strs in it, which can't be part of a static initializer
+fn contains_str(expr: &cir::Expression) -> bool {
+    match &expr.data {
+        cir::ExpressionData::StrLiteral { .. } => true,
+        cir::ExpressionData::ArrayLiteral { exprs, .. } | cir::ExpressionData::Tuple { exprs, .. } => exprs.iter().any(contains_str),
+        cir::ExpressionData::StructInit { fields, .. } => fields.iter().any(|(_, e)| contains_str(e)),
+        cir::ExpressionData::Cast { expr, .. } => contains_str(expr),
+        _ => false,
+    }
 }
 
 impl Generator {
@@ -20,6 +109,14 @@ impl Generator {
             tuples: Vec::new(),
             fn_types: Vec::new(),
             arrays: Vec::new(),
+            closures: 0,
+            temporaries: 0,
+            matched: Vec::new(),
+            defers: Vec::new(),
+            init: String::new(),
+            return_type: String::from("void"),
+            transferring: HashSet::new(),
+            prelude: String::new(),
         }
     }
 }
@@ -28,7 +125,12 @@ const HEAD: &'static str = "// Generated by Elo compiler
 // Copyright(c) 2026 Marcio Dantas, Igor Ferreira
 #include <prelude.h>\n";
 
+// Globals that the C library defines as macros, like the thread-local errno,
+// so they are declared by including their header instead
+const C_MACROS: &[(&str, &str)] = &[("errno", "errno.h")];
+
 const MAIN: &'static str = "void __elo_main(GlobalContext* ctx)";
+const INIT: &'static str = "void __elo_init(GlobalContext* ctx)";
 
 fn fnv_hash(text: &str) -> u32 {
     const FNV_OFFSET_BASIS_32: u32 = 2166136261;
@@ -49,18 +151,44 @@ fn mangle_function(name: &str) -> String {
     return format!("_fn_{name}_{}", fnv_hash(name));
 }
 
-fn mangle_enum(name: &str) -> String {
+pub(crate) fn mangle_enum(name: &str) -> String {
     return format!("_enum_{name}_{}", fnv_hash(name));
 }
 
+// Member of a payload struct. The fields of tuple payloads are named like those of tuples.
+fn mangle_payload_field(field: &str) -> String {
+    if field.starts_with(|c: char| c.is_ascii_digit()) {
+        return format!("t{field}");
+    }
+    field.to_string()
+}
+
+fn mangle_enum_names(name: &str) -> String {
+    return format!("_enum_{name}_names_{}", fnv_hash(name));
+}
+
+fn mangle_enum_name(name: &str) -> String {
+    return format!("_enum_{name}_name_{}", fnv_hash(name));
+}
+
+fn mangle_enum_check(name: &str, signed: bool) -> String {
+    let kind = if signed { "signed" } else { "unsigned" };
+    return format!("_enum_{name}_from_{kind}_{}", fnv_hash(name));
+}
+
 fn mangle_enum_variant(enum_name: &str, variant: &str) -> String {
     return format!("_enum_{enum_name}_{variant}_{}", fnv_hash(variant));
 }
 
-fn mangle_struct(name: &str) -> String {
+pub(crate) fn mangle_struct(name: &str) -> String {
     return format!("_struct_{name}_{}", fnv_hash(name));
 }
 
+// C keyword that declares the struct
+pub(crate) fn aggregate_keyword(layout: &cir::Layout) -> &'static str {
+    if layout.union { "union" } else { "struct" }
+}
+
 fn mangle_tuple_type(no: usize) -> String {
     return format!("_tuple{no}_{}", fnv_hash(&no.to_string()));
 }
@@ -69,8 +197,20 @@ fn mangle_array_type(no: usize) -> String {
     return format!("_array{no}_{}", fnv_hash(&no.to_string()));
 }
 
-fn mangle_fn_type(no: usize) -> String {
-    return format!("_fn{no}_{}", fnv_hash(&no.to_string()));
+fn mangle_fn_call(no: usize) -> String {
+    return format!("_call_fn{no}_{}", fnv_hash(&no.to_string()));
+}
+
+fn mangle_closure(no: usize) -> String {
+    return format!("_closure{no}_{}", fnv_hash(&no.to_string()));
+}
+
+fn mangle_closure_env(no: usize) -> String {
+    return format!("_env{no}_{}", fnv_hash(&no.to_string()));
+}
+
+fn mangle_temporary(no: usize) -> String {
+    return format!("_tmp{no}_{}", fnv_hash(&no.to_string()));
 }
 
 impl Generator {
@@ -78,7 +218,9 @@ impl Generator {
         format!("(Pos){{\"{}\", {}, {}}}", self.input.filename, span.line, span.start)
     }
 
-    pub fn get_fn_type(&mut self, ret: &cir::Typing, args: &Vec<cir::Typing>) -> String {
+    // Function values are all _ELO_FN_T, so they are called through a helper for their type,
+    // which passes the environment only to the functions that take it.
+    pub fn get_fn_call(&mut self, ret: &cir::Typing, args: &Vec<cir::Typing>) -> String {
         let mut fn_type_index = None;
         for (index, fn_type) in self.fn_types.iter().enumerate() {
             if (ret, args) == (&fn_type.0, &fn_type.1) {
@@ -86,16 +228,33 @@ impl Generator {
             }
         }
         if let Some(i) = fn_type_index {
-            return mangle_fn_type(i);
+            return mangle_fn_call(i);
         } else {
             let i = self.fn_types.len();
             self.fn_types.push((ret.clone(), args.clone()));
-            let type_name = mangle_fn_type(i);
-            let ret = self.choose_type(ret);
-            let args = self.generate_fn_args(args, false);
-            let fn_type = &format!("{ret}(*{type_name})({args})");
-            self.head.push_str(&(c::typedef_stmt(fn_type) + "\n"));
-            return type_name;
+            let helper = mangle_fn_call(i);
+            let r#return = self.choose_type(ret);
+            let types = args.iter().map(|t| self.choose_type(t)).collect::<Vec<String>>();
+            let names = (0..args.len()).map(|i| format!("a{i}")).collect::<Vec<String>>();
+
+            let mut parameters = vec![c::field("GlobalContext*", "ctx"), c::field("_ELO_FN_T", "f")];
+            parameters.extend(types.iter().zip(&names).map(|(t, n)| c::field(t, n)));
+            let mut with_env = vec![String::from("ctx"), String::from("f.env")];
+            with_env.extend(names.iter().cloned());
+            let mut without_env = vec![String::from("ctx")];
+            without_env.extend(names.iter().cloned());
+            let with_env_type = format!("{}(*)({})", r#return, c::list(&[vec![String::from("GlobalContext*"), String::from("void*")], types.clone()].concat()));
+            let without_env_type = format!("{}(*)({})", r#return, c::list(&[vec![String::from("GlobalContext*")], types].concat()));
+            let with_env = c::function_ptr_call_expr(&c::cast_expr("f.fn", &with_env_type), &c::list(&with_env));
+            let without_env = c::function_ptr_call_expr(&c::cast_expr("f.fn", &without_env_type), &c::list(&without_env));
+
+            let body = if let cir::Typing::Void = ret {
+                c::if_stmt("f.env", &(c::expr_stmt(&with_env) + &c::return_stmt(None)), None) + &c::expr_stmt(&without_env)
+            } else {
+                c::if_stmt("f.env", &c::return_stmt(Some(with_env)), None) + &c::return_stmt(Some(without_env))
+            };
+            self.head.push_str(&format!("static inline {}", c::function_stmt(&r#return, &helper, &c::list(&parameters), false, &body)));
+            return helper;
         }
     }
 
@@ -156,20 +315,24 @@ impl Generator {
             cir::Typing::Primitive(cir::Primitive::F64) => "_ELO_F64_T".to_string(),
             cir::Typing::Primitive(cir::Primitive::Int) => "_ELO_INT_T".to_string(),
             cir::Typing::Primitive(cir::Primitive::UInt) => "_ELO_UINT_T".to_string(),
+            cir::Typing::Primitive(cir::Primitive::ISize) => "_ELO_ISIZE_T".to_string(),
+            cir::Typing::Primitive(cir::Primitive::USize) => "_ELO_USIZE_T".to_string(),
             cir::Typing::Primitive(cir::Primitive::Float) => "_ELO_FLOAT_T".to_string(),
             cir::Typing::Primitive(cir::Primitive::Str) => "_ELO_STR_T".to_string(),
             cir::Typing::Primitive(cir::Primitive::Char) => "_ELO_CHAR_T".to_string(),
             cir::Typing::Primitive(cir::Primitive::CStr) => "char*".to_string(),
+            cir::Typing::Primitive(cir::Primitive::RawPtr) => "void*".to_string(),
             cir::Typing::Pointer { typ, mutable: _ } => self.choose_type(typ) + "*",
             cir::Typing::Array { typ, amount } => format!("struct {}", self.get_array_type(typ, *amount)),
-            cir::Typing::Struct(cir::Struct { name, .. }) => format!("struct {}", mangle_struct(name)),
-            cir::Typing::Enum(cir::Enum { name, .. }) => format!("enum {}", mangle_enum(name)),
+            cir::Typing::Struct(cir::Struct { name, layout, .. }) => format!("{} {}", aggregate_keyword(layout), mangle_struct(name)),
+            cir::Typing::Enum(e) if e.is_tagged() => format!("struct {}", mangle_enum(&e.name)),
+            cir::Typing::Enum(cir::Enum { name, backing: None, .. }) => format!("enum {}", mangle_enum(name)),
+            // Enums with a backing type are stored as the integer
+            cir::Typing::Enum(e) => self.choose_type(&e.backing_type()),
             cir::Typing::Void => "void".to_string(),
             cir::Typing::Tuple { types } => format!("struct {}", self.get_tuple_type(types)),
             cir::Typing::Slice { typ: _ } => "_ELO_SLICE_T".to_string(),
-            cir::Typing::Function { ret, arguments, variadic: _, extrn: _ } => {
-                self.get_fn_type(ret, arguments)
-            }
+            cir::Typing::Function { .. } => "_ELO_FN_T".to_string(),
             _ => {
                 todo!()
             }
@@ -193,6 +356,18 @@ impl Generator {
             cir::ExpressionData::Bool { value } => {
                 return if *value { "1" } else { "0" }.to_string();
             }
+            cir::ExpressionData::Null => "NULL".to_string(),
+            cir::ExpressionData::StrToCStr { expr } => {
+                self.scope_temporaries();
+                let expr = self.generate_expression(expr);
+                return c::function_call_expr("__elo_str_to_cstr", &self.generate_passed_args(vec![expr], false));
+            }
+            cir::ExpressionData::ExportedFunction { symbol, .. } => c::cast_expr(symbol, "void*"),
+            cir::ExpressionData::CStrToStr { expr: inner } => {
+                let inner = self.generate_expression(inner);
+                let pos = self.get_position_struct(expr.span);
+                return c::function_call_expr("__elo_str_from_cstr", &self.generate_passed_args(vec![pos, inner], false));
+            }
             cir::ExpressionData::Cast { expr, typ } => {
                 let typ = self.choose_type(typ);
                 let expr = self.generate_expression(expr);
@@ -223,6 +398,22 @@ impl Generator {
                 ..
             } => {
                 let lhs = &self.generate_expression(left);
+                if matches!(operator, cir::BinaryOperation::And | cir::BinaryOperation::Or) && has_statements(right) {
+                    // The statements of the right operand only run when it is evaluated
+                    let no = self.temporaries;
+                    self.temporaries += 1;
+                    let result = mangle_temporary(no);
+                    let condition = match operator {
+                        cir::BinaryOperation::And => result.clone(),
+                        _ => c::unop_expr(&result, c::Unop::Not),
+                    };
+                    let outer = std::mem::take(&mut self.prelude);
+                    let rhs = self.generate_expression(right);
+                    let body = std::mem::replace(&mut self.prelude, outer) + &c::expr_stmt(&c::binop_expr(&result, &rhs, c::Binop::Assign));
+                    self.prelude.push_str(&c::variable_stmt("_ELO_BOOL_T", &result, lhs));
+                    self.prelude.push_str(&c::if_stmt(&condition, &body, None));
+                    return result;
+                }
                 let rhs = &self.generate_expression(right);
                 match *operator {
                     cir::BinaryOperation::Add => c::binop_expr(lhs, rhs, c::Binop::Add),
@@ -285,35 +476,169 @@ impl Generator {
                 function,
                 arguments,
             } => {
-                let identity = function.identity;
-                let function = self.generate_expression(function);
-                if let cir::ExpressionIdentity::Function(extrn) = identity {
-                    let arguments = self.generate_passed_fn_args(arguments, extrn);
-                    return c::function_call_expr(&function, &arguments);
-                } else {
-                    let arguments = self.generate_passed_fn_args(arguments, false);
-                    return c::function_ptr_call_expr(&function, &arguments)
+                let (cir::ExpressionData::Identifier { name }, cir::ExpressionIdentity::Function(extrn)) = (&function.data, function.identity) else {
+                    unreachable!("direct call to something that is not a function")
+                };
+                if !extrn && self.transferring.contains(name) {
+                    self.scope_temporaries();
                 }
+                let function = if extrn { name.clone() } else { mangle_function(name) };
+                let arguments = self.generate_passed_fn_args(arguments, extrn);
+                return c::function_call_expr(&function, &arguments);
             },
-            cir::ExpressionData::Identifier { name } => {
-                if let cir::ExpressionIdentity::Function(extrn) = expr.identity {
-                    if extrn {
-                        name.to_string()
-                    } else {
-                        mangle_function(name)
+            cir::ExpressionData::IndirectCall { function, arguments, typ } => {
+                let cir::Typing::Function { ret, arguments: types, .. } = typ else { unreachable!() };
+                if ret.holds_temporaries() {
+                    self.scope_temporaries();
+                }
+                let call = self.get_fn_call(ret, types);
+                let mut values = vec![self.generate_expression(function)];
+                values.extend(arguments.iter().map(|a| self.generate_expression(a)));
+                let values = self.generate_passed_args(values, false);
+                return c::function_call_expr(&call, &values);
+            }
+            cir::ExpressionData::Closure { arguments, ret, captures, block } => {
+                let no = self.closures;
+                self.closures += 1;
+                let name = mangle_closure(no);
+                let r#return = self.choose_type(ret);
+                let mut parameters = vec![c::field("GlobalContext*", "ctx")];
+                let mut body = String::new();
+                let mut env = String::from("NULL");
+                if !captures.is_empty() {
+                    // The captured variables are copied into the environment when the closure is created,
+                    // then into locals of the same name when it is called. The environment is released
+                    // with the temporaries of the scope that creates the closure.
+                    self.scope_temporaries();
+                    let env_name = mangle_closure_env(no);
+                    let mut fields = Vec::new();
+                    let mut values = Vec::new();
+                    for (capture, typ) in captures {
+                        let typ = self.choose_type(typ);
+                        let capture = mangle_name(capture);
+                        fields.push(c::struct_field(&typ, &capture));
+                        body.push_str(&c::variable_stmt(&typ, &capture, &format!("((struct {env_name}*)env)->{capture}")));
+                        values.push((capture.clone(), capture));
+                    }
+                    self.head.push_str(&c::struct_stmt(&env_name, &c::statement_list(&fields)));
+                    parameters.push(c::field("void*", "env"));
+                    let value = format!("&{}", c::struct_expr(&env_name, &values));
+                    let arguments = self.generate_passed_args(vec![value, format!("sizeof(struct {env_name})")], false);
+                    env = c::function_call_expr("__elo_env_new", &arguments);
+                }
+                for (argument, typ) in arguments {
+                    let typ = self.choose_type(typ);
+                    parameters.push(c::field(&typ, &mangle_name(argument)));
+                }
+                let defers = std::mem::take(&mut self.defers);
+                let return_type = std::mem::replace(&mut self.return_type, r#return.clone());
+                body.push_str(&self.generate_block(&mut block.clone()));
+                self.defers = defers;
+                self.return_type = return_type;
+                self.head.push_str(&format!("static {}", c::function_stmt(&r#return, &name, &c::list(&parameters), false, &body)));
+                return format!("(_ELO_FN_T){{.fn = (void*){name}, .env = {env}}}");
+            }
+            cir::ExpressionData::Block { .. } => unreachable!("block expression outside of a branch"),
+            cir::ExpressionData::If { condition, r#true, r#false, typ } => {
+                let condition = self.generate_expression(condition);
+                if !has_statements(r#true) && !has_statements(r#false) {
+                    let r#true = self.generate_expression(r#true);
+                    let r#false = self.generate_expression(r#false);
+                    return c::ternary_expr(&condition, &r#true, &r#false);
+                }
+                // The branches have statements, so the value is stored in a temporary
+                let (mut statements, target) = self.generate_temporary(typ);
+                let r#true = self.generate_branch(r#true, target.as_deref());
+                let r#false = self.generate_branch(r#false, target.as_deref());
+                statements.push_str(&c::if_stmt(&condition, &r#true, Some(r#false)));
+                self.prelude.push_str(&statements);
+                return target.unwrap_or_default();
+            }
+            cir::ExpressionData::Match { value, value_typ, arms, typ } => {
+                let value_typ = self.choose_type(value_typ);
+                if !has_statements(expr) {
+                    let value = self.generate_expression(value);
+                    // Comparing a variable has no side effects, so it doesn't need a temporary
+                    let mut chain = String::new();
+                    for (pattern, branch) in arms.iter().rev() {
+                        let branch = self.generate_expression(branch);
+                        chain = match pattern {
+                            Some(pattern) if !chain.is_empty() => {
+                                let condition = self.generate_match_condition(&value, pattern);
+                                c::ternary_expr(&condition, &branch, &chain)
+                            }
+                            // The last arm is the only one left when the others don't match
+                            _ => branch,
+                        };
                     }
+                    return chain;
+                }
+                let value = self.generate_expression(value);
+                let no = self.temporaries;
+                self.temporaries += 1;
+                let matched = mangle_temporary(no);
+                let (mut statements, target) = self.generate_temporary(typ);
+                // Compare the value with the patterns from the last arm, building the else-if chain backwards
+                let mut chain: Option<String> = None;
+                // The arms read the payloads they bind from the temporary
+                self.matched.push(matched.clone());
+                for (pattern, branch) in arms.iter().rev() {
+                    let branch = self.generate_branch(branch, target.as_deref());
+                    chain = Some(match pattern {
+                        Some(pattern) => {
+                            let condition = self.generate_match_condition(&matched, pattern);
+                            c::if_stmt(&condition, &branch, chain)
+                        }
+                        None => branch,
+                    });
+                }
+                self.matched.pop();
+                statements.insert_str(0, &c::variable_stmt(&value_typ, &matched, &value));
+                statements.push_str(&chain.unwrap_or_default());
+                self.prelude.push_str(&statements);
+                return target.unwrap_or_default();
+            }
+            cir::ExpressionData::Identifier { name } => {
+                if let cir::ExpressionIdentity::Function(_) = expr.identity {
+                    // A function used as a value, which has no environment
+                    format!("(_ELO_FN_T){{.fn = (void*){}, .env = NULL}}", mangle_function(name))
                 } else {
                     mangle_name(name)
                 }
             },
-            cir::ExpressionData::StructInit { origin, fields } => {
-                let name = origin.name.clone();
+            cir::ExpressionData::ExternVariable { name } => name.clone(),
+            cir::ExpressionData::StructInit { origin, fields, base } => {
+                if let Some(base) = base {
+                    // The fields and then the base are evaluated in the order they are written, into
+                    // temporaries sequenced by the comma operator, and the fields overwrite the base
+                    let mut steps = Vec::new();
+                    let mut updates = Vec::new();
+                    for (field, e) in fields {
+                        let (_, field_type) = origin.fields.iter().find(|(f, _)| f == field).unwrap();
+                        let value = self.generate_expression(e);
+                        let (declaration, temporary) = self.generate_temporary(field_type);
+                        let temporary = temporary.unwrap();
+                        self.prelude.push_str(&declaration);
+                        steps.push(c::binop_expr(&temporary, &value, c::Binop::Assign));
+                        updates.push((field, temporary));
+                    }
+                    let value = self.generate_expression(base);
+                    let (declaration, result) = self.generate_temporary(&cir::Typing::Struct(origin.clone()));
+                    let result = result.unwrap();
+                    self.prelude.push_str(&declaration);
+                    steps.push(c::binop_expr(&result, &value, c::Binop::Assign));
+                    for (field, temporary) in updates {
+                        steps.push(c::binop_expr(&c::member_expr(&result, field), &temporary, c::Binop::Assign));
+                    }
+                    steps.push(result);
+                    return format!("({})", c::list(&steps));
+                }
                 let fields = fields
                     .iter()
                     .map(|(f, e)| (f.clone(), self.generate_expression(e)))
                     .collect::<Vec<(String, String)>>();
-                let name = &mangle_struct(&name);
-                return c::struct_expr(name, &fields);
+                let typ = self.choose_type(&cir::Typing::Struct(origin.clone()));
+                return c::compound_expr(&typ, &fields);
             }
             cir::ExpressionData::TupleAccess { origin, field } => {
                 let field = &format!("t{field}");
@@ -324,13 +649,33 @@ impl Generator {
                 let index = self.generate_expression(index);
                 return c::subscript_expr(&origin, &index);
             }
+            cir::ExpressionData::PointerSubscript { origin, index } => {
+                let origin = self.generate_expression(origin);
+                let index = self.generate_expression(index);
+                return c::subscript_expr(&origin, &index);
+            }
             cir::ExpressionData::SliceSubscript { typ, origin, index } => {
-                let _typ = self.choose_type(typ);
+                let typ = self.choose_type(typ);
                 let origin = self.generate_expression(origin);
                 let index = self.generate_expression(index);
                 let pos = self.get_position_struct(expr.span);
-                let args = self.generate_passed_args(vec![pos, origin, index], false);
-                return c::function_call_expr("__elo_slice_get", &args);
+                let args = self.generate_passed_args(vec![pos, origin, index, format!("sizeof({typ})")], false);
+                let item = c::function_call_expr("__elo_slice_get", &args);
+                return c::unop_expr(&c::cast_expr(&item, &format!("{typ}*")), c::Unop::Deref);
+            }
+            cir::ExpressionData::SlicePack { typ, exprs } => {
+                // The items are copied into a temporary, dropped when the scope is exited
+                self.scope_temporaries();
+                let typ = self.choose_type(typ);
+                let items = if exprs.is_empty() {
+                    String::from("NULL")
+                } else {
+                    let exprs = exprs.iter().map(|e| self.generate_expression(e)).collect::<Vec<String>>();
+                    // Temporary array holding the items until they are copied
+                    format!("({typ}[]){}", c::braced_expr(&c::list(&exprs)))
+                };
+                let args = self.generate_passed_args(vec![items, exprs.len().to_string(), format!("sizeof({typ})")], false);
+                return c::function_call_expr("__elo_slice_pack", &args);
             }
             cir::ExpressionData::StrSubscript { origin, index } => {
                 let origin = self.generate_expression(origin);
@@ -344,8 +689,40 @@ impl Generator {
                 let rhs = field.clone();
                 return c::member_expr(&lhs, &rhs);
             }
+            cir::ExpressionData::EnumVariant { origin, variant } if origin.is_tagged() => {
+                let typ = self.choose_type(&cir::Typing::Enum(origin.clone()));
+                return c::compound_expr(&typ, &[("tag".to_string(), mangle_enum_variant(&origin.name, variant))]);
+            }
             cir::ExpressionData::EnumVariant { origin, variant } => {
-                return mangle_enum_variant(origin, variant);
+                return mangle_enum_variant(&origin.name, variant);
+            }
+            cir::ExpressionData::EnumInit { origin, variant, fields } => {
+                let typ = self.choose_type(&cir::Typing::Enum(origin.clone()));
+                let mut members = vec![("tag".to_string(), mangle_enum_variant(&origin.name, variant))];
+                for (field, value) in fields {
+                    let member = format!("data.{variant}.{}", mangle_payload_field(field));
+                    members.push((member, self.generate_expression(value)));
+                }
+                return c::compound_expr(&typ, &members);
+            }
+            cir::ExpressionData::EnumName { expr: inner, origin } => {
+                let mut value = self.generate_expression(inner);
+                if origin.is_tagged() {
+                    value = c::member_expr(&value, "tag");
+                }
+                let name = c::function_call_expr(&mangle_enum_name(&origin.name), &value);
+                return c::function_call_expr("__elo_str_new", &c::list(&["ctx".to_string(), name]));
+            }
+            cir::ExpressionData::Payload { variant, field } => {
+                let matched = self.matched.last().expect("payload outside of a match");
+                let data = c::member_expr(&c::member_expr(matched, "data"), variant);
+                return c::member_expr(&data, &mangle_payload_field(field));
+            }
+            cir::ExpressionData::EnumFromInt { expr: inner, origin, signed } => {
+                let value = self.generate_expression(inner);
+                let integer = if *signed { "_ELO_I64_T" } else { "_ELO_U64_T" };
+                let args = vec![self.get_position_struct(expr.span), c::cast_expr(&value, integer)];
+                return c::function_call_expr(&mangle_enum_check(&origin.name, *signed), &c::list(&args));
             }
         }
     }
@@ -397,7 +774,14 @@ impl Generator {
         return c::list(&result);
     }
 
+    // Generate the statement, preceded by the prelude of its expressions
     pub fn generate_statement(&mut self, stmt: &mut cir::Statement) -> String {
+        let outer = std::mem::take(&mut self.prelude);
+        let output = self.generate_statement_kind(stmt);
+        std::mem::replace(&mut self.prelude, outer) + &output
+    }
+
+    fn generate_statement_kind(&mut self, stmt: &mut cir::Statement) -> String {
         let mut output = String::new();
         match &mut stmt.kind {
             cir::StatementKind::Constant {
@@ -405,25 +789,28 @@ impl Generator {
                 binding,
                 typing,
             } => {
-                self.head.push_str("static const ");
                 let x = self.choose_type(typing);
+                if contains_str(&value) {
+                    // strs are created by the runtime, so they are initialized before main like globals
+                    let expr = self.generate_expression(&value);
+                    self.head.push_str(&format!("static {}", c::variable_decl_stmt(&x, &mangle_name(binding))));
+                    self.init.push_str(&std::mem::take(&mut self.prelude));
+                    self.init.push_str(&c::expr_stmt(&c::binop_expr(&mangle_name(binding), &expr, c::Binop::Assign)));
+                    return output;
+                }
+                let expr = self.generate_initializer(&value);
+                self.head.push_str("static const ");
                 self.head.push_str(&x);
                 self.head.push(' ');
                 self.head.push_str(&mangle_name(binding));
                 self.head.push('=');
-                let expr = self.generate_expression(&value);
                 self.head.push_str(&expr);
                 self.head.push_str(";\n");
             }
             cir::StatementKind::FnStatement(stmt) => {
-                let body = stmt
-                    .block
-                    .iter_mut()
-                    .map(|x| self.generate_statement(x))
-                    .collect::<Vec<String>>();
-                let body = c::statement_list(body.as_slice());
-
                 if stmt.head.name == "main" {
+                    self.return_type = String::from("void");
+                    let body = self.generate_block(&mut stmt.block);
                     output.push_str(&c::function_sign(
                         MAIN,
                         &body,
@@ -431,18 +818,54 @@ impl Generator {
                     return output;
                 }
 
+                // Declare the function before generating the body, since the closures in it
+                // are emitted before the function and may call it
                 let r#return = self.choose_type(&stmt.head.ret);
                 let name = stmt.head.name.clone();
                 let arguments = self.generate_named_fn_args(&stmt.head.arguments, stmt.head.extrn);
+                if stmt.head.ret.holds_temporaries() {
+                    self.transferring.insert(name.clone());
+                }
                 let name = &mangle_function(&name);
                 self.head.push_str(&c::function_decl_stmt(&r#return, name, &arguments, false));
+
+                self.return_type = r#return.clone();
+                let body = self.generate_block(&mut stmt.block);
                 output.push_str(&c::function_stmt(
                     &r#return,
                     name,
                     &arguments,
-                    stmt.head.variadic,
+                    false, // The variadic tail of Elo functions is a slice, not C variadic arguments
                     &body,
                 ));
+
+                if let Some(symbol) = &stmt.export {
+                    // C callers have no context to pass, so the entry point takes the one of the runtime
+                    let parameters = self.generate_named_fn_args(&stmt.head.arguments, true);
+                    let mut values = vec![String::from("__elo_context_get()")];
+                    values.extend(stmt.head.arguments.iter().map(|(name, _)| mangle_name(name)));
+                    let call = c::function_call_expr(name, &c::list(&values));
+                    let body = if stmt.head.ret == cir::Typing::Void {
+                        c::expr_stmt(&call)
+                    } else if stmt.head.ret.holds_temporaries() {
+                        // C can't release the temporaries the value refers to, so they are kept
+                        // until the next call of the entry point, which drops them
+                        let mut body = String::from("static SlotTable kept = {0};\n");
+                        body.push_str(&c::variable_stmt("GlobalContext*", "ctx", "__elo_context_get()"));
+                        body.push_str(&c::variable_stmt("size_t", "mark", &c::function_call_expr("__elo_temporary_mark", "ctx")));
+                        values[0] = String::from("ctx");
+                        let call = c::function_call_expr(name, &c::list(&values));
+                        body.push_str(&c::variable_stmt(&r#return, "__elo_ret", &call));
+                        let keep = c::function_call_expr("__elo_temporary_keep", "ctx, mark, &kept");
+                        body.push_str(&c::expr_stmt(&keep));
+                        body.push_str(&c::return_stmt(Some(String::from("__elo_ret"))));
+                        body
+                    } else {
+                        c::return_stmt(Some(call))
+                    };
+                    self.head.push_str(&c::function_decl_stmt(&r#return, symbol, &parameters, false));
+                    output.push_str(&c::function_stmt(&r#return, symbol, &parameters, false, &body));
+                }
             }
             cir::StatementKind::ExternFnStatement(stmt) => {
                 let r#return = self.choose_type(&stmt.ret);
@@ -456,18 +879,134 @@ impl Generator {
                 ));
             }
             cir::StatementKind::StructStatement(stmt) => {
+                let layout = &stmt.layout;
                 let fields = stmt
                     .fields
                     .iter()
-                    .map(|(k, v)| c::struct_field(&self.choose_type(v), k));
+                    .enumerate()
+                    .map(|(i, (k, v))| {
+                        let field = c::struct_field(&self.choose_type(v), k);
+                        let mut attributes = String::new();
+                        if layout.field_packed.get(i).copied().unwrap_or(false) {
+                            attributes.push_str("__elo_packed ");
+                        }
+                        if let Some(align) = layout.field_aligns.get(i).copied().flatten() {
+                            attributes.push_str(&format!("__elo_align({align}) "));
+                        }
+                        format!("{attributes}{field}")
+                    });
                 let fields = fields.collect::<Vec<String>>();
                 let body = c::statement_list(&fields);
-                self.head.push_str(&c::struct_stmt(&mangle_struct(&stmt.name), &body));
+                let mut attributes = String::new();
+                if layout.packed {
+                    attributes.push_str("__elo_packed ");
+                }
+                if let Some(align) = layout.align {
+                    attributes.push_str(&format!("__elo_align({align}) "));
+                }
+                let declaration = c::aggregate_stmt(aggregate_keyword(layout), &attributes, &mangle_struct(&stmt.name), &body);
+                if layout.packed {
+                    self.head.push_str(&format!("__elo_packed_begin\n{declaration}__elo_packed_end\n"));
+                } else {
+                    self.head.push_str(&declaration);
+                }
             }
             cir::StatementKind::EnumStatement(stmt) => {
-                let vars: Vec<String> = stmt.variants.iter().map(|x| mangle_enum_variant(&stmt.name, x)).collect();
-                let doby = c::list(&vars);
-                self.head.push_str(&c::enum_stmt(&mangle_enum(&stmt.name), &doby));
+                if stmt.is_tagged() {
+                    // A struct with the tag, stored as the backing type, and a union of the payloads
+                    let tag = self.choose_type(&stmt.backing_type());
+                    let mut payloads = String::new();
+                    for ((variant, value), payload) in stmt.variants.iter().zip(&stmt.payloads) {
+                        let value = c::cast_expr(&value.to_string(), &tag);
+                        self.head.push_str(&format!("#define {} ({value})\n", mangle_enum_variant(&stmt.name, variant)));
+                        if let Some(payload) = payload {
+                            let fields = payload.fields()
+                                .iter()
+                                .map(|(field, typ)| c::struct_field(&self.choose_type(typ), &mangle_payload_field(field)))
+                                .collect::<Vec<String>>();
+                            payloads.push_str(&format!("struct {{ {}}} {variant};\n", c::statement_list(&fields)));
+                        }
+                    }
+                    let body = format!("{}union {{ {payloads}}} data;\n", c::struct_field(&tag, "tag"));
+                    self.head.push_str(&c::aggregate_stmt("struct", "", &mangle_enum(&stmt.name), &body));
+                } else {
+                    let typ = self.choose_type(&cir::Typing::Enum(stmt.clone()));
+                    if stmt.backing.is_none() {
+                        let vars: Vec<String> = stmt.variants
+                            .iter()
+                            .map(|(x, value)| format!("{} = {value}", mangle_enum_variant(&stmt.name, x)))
+                            .collect();
+                        let doby = c::list(&vars);
+                        self.head.push_str(&c::enum_stmt(&mangle_enum(&stmt.name), &doby));
+                    } else {
+                        // The values may not fit in an int, which C enums are limited to
+                        for (variant, value) in &stmt.variants {
+                            let value = c::cast_expr(&value.to_string(), &typ);
+                            self.head.push_str(&format!("#define {} ({value})\n", mangle_enum_variant(&stmt.name, variant)));
+                        }
+                    }
+                    // Checked conversions from signed and unsigned integers (see EnumFromInt)
+                    for signed in [true, false] {
+                        let (integer, suffix, format) = if signed { ("_ELO_I64_T", "LL", "%lld") } else { ("_ELO_U64_T", "ULL", "%llu") };
+                        let (min, max) = if signed { (i64::MIN as i128, i64::MAX as i128) } else { (0, u64::MAX as i128) };
+                        let conditions = stmt.variants
+                            .iter()
+                            .filter(|(_, value)| (min..=max).contains(value))
+                            .map(|(_, value)| format!("value == {value}{suffix}"))
+                            .collect::<Vec<String>>();
+                        let condition = if conditions.is_empty() { String::from("false") } else { conditions.join(" || ") };
+                        let message = format!("\"{format} is not the value of a variant of enumeration {}\"", stmt.name);
+                        let cast = if signed { "(long long)value" } else { "(unsigned long long)value" };
+                        let mut body = c::if_stmt(&condition, &c::return_stmt(Some(c::cast_expr("value", &typ))), None);
+                        body.push_str(&c::expr_stmt(&c::function_call_expr("__elo_panic", &c::list(&["pos".to_string(), message, cast.to_string()]))));
+                        body.push_str(&c::return_stmt(Some(c::cast_expr("value", &typ))));
+                        let arguments = c::list(&["Pos pos".to_string(), c::field(integer, "value")]);
+                        let function = c::function_stmt(&typ, &mangle_enum_check(&stmt.name, signed), &arguments, false, &body);
+                        self.head.push_str(&format!("static inline {function}"));
+                    }
+                }
+                // Names of the variants, found by their value or the tag of enums with payloads (see EnumName)
+                let tag = if stmt.is_tagged() { stmt.backing_type() } else { cir::Typing::Enum(stmt.clone()) };
+                let tag = self.choose_type(&tag);
+                let names = stmt.variants.iter().map(|(variant, _)| c::string_expr(variant)).collect::<Vec<String>>();
+                let mut body = String::new();
+                if !names.is_empty() {
+                    let table = mangle_enum_names(&stmt.name);
+                    self.head.push_str(&format!("static const char* const {table}[] = {{{}}};\n", c::list(&names)));
+                    for (i, (variant, _)) in stmt.variants.iter().enumerate() {
+                        let condition = c::binop_expr("value", &mangle_enum_variant(&stmt.name, variant), c::Binop::Eq);
+                        body.push_str(&c::if_stmt(&condition, &c::return_stmt(Some(c::subscript_expr(&table, &i.to_string()))), None));
+                    }
+                }
+                body.push_str(&c::return_stmt(Some(c::string_expr("?"))));
+                let function = c::function_stmt("const char*", &mangle_enum_name(&stmt.name), &c::field(&tag, "value"), false, &body);
+                self.head.push_str(&format!("static inline {function}"));
+            }
+            cir::StatementKind::TypeAlias { .. } => {} // Aliases are already resolved
+            cir::StatementKind::StaticAssert { .. } => {} // Already checked by the compiler
+            cir::StatementKind::ExternVarStatement { binding, typing, .. } => {
+                if let Some((_, header)) = C_MACROS.iter().find(|(name, _)| name == binding) {
+                    self.head.push_str(&format!("#include <{header}>\n"));
+                    return output;
+                }
+                let typ = self.choose_type(typing);
+                self.head.push_str(&format!("extern {}", c::variable_decl_stmt(&typ, binding)));
+            }
+            cir::StatementKind::Global {
+                binding,
+                assignment,
+                typing,
+            } => {
+                let typ = self.choose_type(typing);
+                let name = mangle_name(binding);
+                let expr = self.generate_expression(&assignment);
+                if is_constant(assignment) {
+                    self.head.push_str(&format!("static {}", c::variable_stmt(&typ, &name, &expr)));
+                } else {
+                    self.head.push_str(&format!("static {}", c::variable_decl_stmt(&typ, &name)));
+                    self.init.push_str(&std::mem::take(&mut self.prelude));
+                    self.init.push_str(&c::expr_stmt(&c::binop_expr(&name, &expr, c::Binop::Assign)));
+                }
             }
             // BODY STATEMENTS //
             cir::StatementKind::Variable {
@@ -484,9 +1023,27 @@ impl Generator {
                 let e = self.generate_expression(&expr);
                 output.push_str(&c::expr_stmt(&e));
             }
-            cir::StatementKind::ReturnStatement { value, .. } => {
+            cir::StatementKind::ReturnStatement { value, typing } => {
                 let e = value.as_ref().map(|x| self.generate_expression(x));
-                output.push_str(&c::return_stmt(e));
+                // Every scope being exited runs its deferred blocks, from the innermost one
+                let mut defers = self.defers.iter().rev().flat_map(|scope| scope.iter().rev().cloned()).collect::<Vec<Deferred>>();
+                if typing.holds_temporaries() {
+                    // The value may refer to the temporaries, so the scope of the caller releases them
+                    defers.retain(|deferred| !matches!(deferred, Deferred::Release(_)));
+                }
+                if defers.is_empty() {
+                    output.push_str(&c::return_stmt(e));
+                    return output;
+                }
+                let mut body = String::new();
+                let e = e.map(|e| {
+                    // The value is computed before the deferred code runs
+                    body.push_str(&c::variable_stmt(&self.return_type, "__elo_ret", &e));
+                    String::from("__elo_ret")
+                });
+                body.push_str(&self.generate_deferred(defers));
+                body.push_str(&c::return_stmt(e));
+                output.push_str(&format!("{{\n{body}}}\n"));
             }
             cir::StatementKind::IfStatement {
                 condition,
@@ -495,39 +1052,180 @@ impl Generator {
                 ..
             } => {
                 let comparison = self.generate_expression(&condition);
-                let r#true = block_true
-                    .iter_mut()
-                    .map(|x| self.generate_statement(x))
-                    .collect::<Vec<String>>();
-                let r#true = c::statement_list(r#true.as_slice());
+                let r#true = self.generate_block(block_true);
 
                 let mut r#false = None;
                 if !block_false.is_empty() {
-                    let f = block_false
-                        .iter_mut()
-                        .map(|x| self.generate_statement(x))
-                        .collect::<Vec<String>>();
-                    r#false = Some(c::statement_list(f.as_slice()));
+                    r#false = Some(self.generate_block(block_false));
                 }
                 output.push_str(&c::if_stmt(&comparison, &r#true, r#false));
             }
             cir::StatementKind::WhileStatement { condition, block } => {
                 let comparison = self.generate_expression(&condition);
-                let block = block
-                    .iter_mut()
-                    .map(|x| self.generate_statement(x))
-                    .collect::<Vec<String>>();
-                let block = c::statement_list(block.as_slice());
-                output.push_str(&c::while_stmt(&comparison, &block));
+                let prelude = std::mem::take(&mut self.prelude);
+                let block = self.generate_block(block);
+                if prelude.is_empty() {
+                    output.push_str(&c::while_stmt(&comparison, &block));
+                } else {
+                    // The statements of the condition run before every check
+                    let exit = c::if_stmt(&c::unop_expr(&comparison, c::Unop::Not), &c::break_stmt(), None);
+                    output.push_str(&c::while_stmt("1", &format!("{prelude}{exit}{{\n{block}}}\n")));
+                }
+            }
+            cir::StatementKind::ForStatement { binding, typing, amount, iterable, block } => {
+                // The array is copied, so the block can't change the items that are left
+                let array = self.choose_type(&cir::Typing::Array { typ: Box::new(typing.clone()), amount: *amount });
+                let iterable = self.generate_expression(&iterable);
+                let items = mangle_temporary(self.temporaries);
+                let index = mangle_temporary(self.temporaries + 1);
+                self.temporaries += 2;
+                output.push_str(&c::variable_stmt(&array, &items, &iterable));
+                output.push_str(&c::variable_stmt("size_t", &index, "0"));
+                let item = c::subscript_expr(&c::member_expr(&items, "items"), &index);
+                let mut body = c::variable_stmt(&self.choose_type(typing), &mangle_name(binding), &item);
+                body.push_str(&c::expr_stmt(&c::binop_expr(&index, "1", c::Binop::AssignAdd)));
+                body.push_str(&format!("{{\n{}}}\n", self.generate_block(block)));
+                let comparison = c::binop_expr(&index, &amount.to_string(), c::Binop::Lt);
+                output.push_str(&c::while_stmt(&comparison, &body));
+            }
+            cir::StatementKind::DeferStatement { block } => {
+                // Generated when the scope is exited (see generate_block)
+                self.defers.last_mut().unwrap().push(Deferred::Block(std::mem::take(block)));
             }
         };
         return output;
     }
 
+    // Generate the statements of a new scope, followed by its deferred blocks
+    // when the end of the scope can be reached
+    fn generate_block(&mut self, block: &mut cir::Block) -> String {
+        self.generate_scope(block, String::new())
+    }
+
+    // Same as generate_block, with `tail` emitted at the end of the scope, before the deferred blocks
+    fn generate_scope(&mut self, block: &mut cir::Block, tail: String) -> String {
+        self.defers.push(Vec::new());
+        let mut body = block
+            .iter_mut()
+            .map(|x| self.generate_statement(x))
+            .collect::<Vec<String>>();
+        body.push(tail);
+        let defers = self.defers.pop().unwrap();
+        // The mark is taken when the scope is entered, so its temporaries are all released together
+        if let Some(Deferred::Release(mark)) = defers.first() {
+            let mark = c::variable_stmt("size_t", mark, &c::function_call_expr("__elo_temporary_mark", "ctx"));
+            body.insert(0, mark);
+        }
+        let returns = matches!(block.last(), Some(cir::Statement { kind: cir::StatementKind::ReturnStatement { .. }, .. }));
+        if !returns {
+            body.push(self.generate_deferred(defers.into_iter().rev().collect()));
+        }
+        return c::statement_list(body.as_slice());
+    }
+
+    // Initializer of a static constant. The values computed at compile time are literals,
+    // so the arrays, structs and tuples in them are written as brace initializers.
+    fn generate_initializer(&mut self, expr: &cir::Expression) -> String {
+        match &expr.data {
+            cir::ExpressionData::ArrayLiteral { exprs, .. } => {
+                let items = exprs.iter().map(|e| self.generate_initializer(e)).collect::<Vec<String>>();
+                return c::initializer_expr(&[("items".to_string(), c::braced_expr(&c::list(&items)))]);
+            }
+            cir::ExpressionData::StructInit { fields, base: None, .. } => {
+                let fields = fields
+                    .iter()
+                    .map(|(f, e)| (f.clone(), self.generate_initializer(e)))
+                    .collect::<Vec<(String, String)>>();
+                return c::initializer_expr(&fields);
+            }
+            cir::ExpressionData::Tuple { exprs, .. } => {
+                let fields = exprs.iter().map(|e| self.generate_initializer(e)).collect::<Vec<String>>();
+                return c::initializer_expr_ordered(&fields);
+            }
+            _ => self.generate_expression(expr),
+        }
+    }
+
+    // Declaration of the temporary that holds the value of an if or match expression
+    // whose branches have statements, or a value of a struct update. Expressions without value don't need it.
+    fn generate_temporary(&mut self, typ: &cir::Typing) -> (String, Option<String>) {
+        if let cir::Typing::Void = typ {
+            return (String::new(), None);
+        }
+        let no = self.temporaries;
+        self.temporaries += 1;
+        let name = mangle_temporary(no);
+        let typ = self.choose_type(typ);
+        return (c::variable_decl_stmt(&typ, &name), Some(name));
+    }
+
+    // Condition of a match arm, which compares the matched value with the pattern
+    fn generate_match_condition(&mut self, value: &str, pattern: &cir::Expression) -> String {
+        if let cir::ExpressionData::EnumVariant { origin, variant } = &pattern.data && origin.is_tagged() {
+            // Enums with payloads are compared by their tag
+            let tag = c::member_expr(value, "tag");
+            return c::binop_expr(&tag, &mangle_enum_variant(&origin.name, variant), c::Binop::Eq);
+        }
+        let pattern = self.generate_expression(pattern);
+        c::binop_expr(value, &pattern, c::Binop::Eq)
+    }
+
+    // Statements of a branch of an if or match expression, storing its value in `target`
+    fn generate_branch(&mut self, branch: &cir::Expression, target: Option<&str>) -> String {
+        let (mut block, value) = match &branch.data {
+            cir::ExpressionData::Block { block, value } => (block.clone(), value.as_deref()),
+            _ => (Vec::new(), Some(branch)),
+        };
+        // The statements of the value only run in this branch
+        let outer = std::mem::take(&mut self.prelude);
+        let tail = match (value, target) {
+            (Some(value), Some(target)) => {
+                let value = self.generate_expression(value);
+                c::expr_stmt(&c::binop_expr(target, &value, c::Binop::Assign))
+            }
+            (Some(value), None) => c::expr_stmt(&self.generate_expression(value)),
+            (None, _) => String::new(),
+        };
+        let tail = std::mem::replace(&mut self.prelude, outer) + &tail;
+        return self.generate_scope(&mut block, tail);
+    }
+
+    // Deferred code, in the order it must run
+    fn generate_deferred(&mut self, defers: Vec<Deferred>) -> String {
+        let mut output = String::new();
+        for deferred in defers {
+            match deferred {
+                Deferred::Block(mut block) => {
+                    output.push_str(&format!("{{\n{}}}\n", self.generate_block(&mut block)));
+                }
+                Deferred::Release(mark) => {
+                    let release = c::function_call_expr("__elo_temporary_release", &c::list(&["ctx".to_string(), mark]));
+                    output.push_str(&c::expr_stmt(&release));
+                }
+            }
+        }
+        return output;
+    }
+
+    // Variable with the mark of the temporaries of the current scope. The temporaries
+    // outside of functions, in the initializers of globals, live until the program ends.
+    fn scope_temporaries(&mut self) {
+        let no = self.temporaries;
+        if let Some(scope) = self.defers.last_mut()
+            && !matches!(scope.first(), Some(Deferred::Release(_)))
+        {
+            self.temporaries += 1;
+            // First, so they are released after the deferred blocks, which may use them
+            scope.insert(0, Deferred::Release(mangle_temporary(no)));
+        }
+    }
+
     pub fn go(&mut self) {
         for mut node in std::mem::take(&mut self.input.nodes) {
             let out = &self.generate_statement(&mut node);
             self.body.push_str(out);
         }
+        let init = std::mem::take(&mut self.init);
+        self.body.push_str(&c::function_sign(INIT, &init));
     }
 }
@@ -0,0 +1,197 @@
+// C header of a program built as a library (`elo build --lib`), which declares its
+// exported functions and the structs and enums C code needs to call them
+use crate::generator::{aggregate_keyword, mangle_enum, mangle_struct};
+use elo_ir::cir;
+
+pub struct HeaderGenerator {
+    pub output: String,
+    guard: String,
+}
+
+// C type of a value, without the dimensions of arrays (see `declaration`)
+fn c_type(typ: &cir::Typing) -> String {
+    match typ {
+        cir::Typing::Primitive(p) => match p {
+            cir::Primitive::I64 => "int64_t",
+            cir::Primitive::I32 | cir::Primitive::Int => "int32_t",
+            cir::Primitive::I16 => "int16_t",
+            cir::Primitive::I8 => "int8_t",
+            cir::Primitive::U64 => "uint64_t",
+            cir::Primitive::U32 | cir::Primitive::UInt | cir::Primitive::Char => "uint32_t",
+            cir::Primitive::U16 => "uint16_t",
+            cir::Primitive::U8 => "uint8_t",
+            cir::Primitive::ISize => "ptrdiff_t",
+            cir::Primitive::USize => "size_t",
+            cir::Primitive::F32 | cir::Primitive::Float => "float",
+            cir::Primitive::F64 => "double",
+            cir::Primitive::Bool => "bool",
+            cir::Primitive::CStr => "char*",
+            cir::Primitive::RawPtr => "void*",
+            cir::Primitive::Str => unreachable!("str is not available in C"),
+        }.to_string(),
+        // A pointer to an array points to its first item
+        cir::Typing::Pointer { typ, .. } => {
+            let mut typ = typ.as_ref();
+            while let cir::Typing::Array { typ: item, .. } = typ {
+                typ = item;
+            }
+            c_type(typ) + "*"
+        }
+        cir::Typing::Struct(s) => s.name.clone(),
+        cir::Typing::Enum(e) => e.name.clone(),
+        cir::Typing::Void => "void".to_string(),
+        _ => unreachable!("type {typ} is not available in C"),
+    }
+}
+
+// Declaration of `name` with the given type, like `int32_t cells[8][8]`
+fn declaration(typ: &cir::Typing, name: &str) -> String {
+    let mut dimensions = String::new();
+    let mut typ = typ;
+    while let cir::Typing::Array { typ: item, amount } = typ {
+        dimensions.push_str(&format!("[{amount}]"));
+        typ = item;
+    }
+    format!("{} {name}{dimensions}", c_type(typ))
+}
+
+// Opaque declarations of the structs and enums that C can only see through pointers, since
+// the header has no definition of them, like `typedef struct _struct_Named Named;`
+fn opaque(typ: &cir::Typing, declarations: &mut Vec<String>) {
+    match typ {
+        cir::Typing::Pointer { typ, .. } | cir::Typing::Array { typ, .. } => opaque(typ, declarations),
+        cir::Typing::Struct(s) if !typ.is_c_compatible() => {
+            let declaration = format!("typedef {} {} {};\n", aggregate_keyword(&s.layout), mangle_struct(&s.name), s.name);
+            if !declarations.contains(&declaration) {
+                declarations.push(declaration);
+            }
+        }
+        cir::Typing::Enum(e) if e.is_tagged() => {
+            let declaration = format!("typedef struct {} {};\n", mangle_enum(&e.name), e.name);
+            if !declarations.contains(&declaration) {
+                declarations.push(declaration);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl HeaderGenerator {
+    // `name` is the name of the library, which the include guard is made from
+    pub fn new(name: &str) -> Self {
+        let guard = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect::<String>();
+        Self {
+            output: String::new(),
+            guard: format!("ELO_{guard}_H"),
+        }
+    }
+
+    pub fn go(&mut self, program: &cir::Program) {
+        self.output.push_str("// Generated by Elo compiler\n");
+        self.output.push_str(&format!("#ifndef {0}\n#define {0}\n\n", self.guard));
+        self.output.push_str("#include <stdint.h>\n#include <stddef.h>\n#include <stdbool.h>\n\n");
+
+        // The layout attributes, like in typing.h of the runtime
+        let annotated = program.nodes.iter().any(|stmt| matches!(&stmt.kind, cir::StatementKind::StructStatement(s) if !s.layout.is_default()));
+        if annotated {
+            self.output.push_str("#ifdef _MSC_VER\n#define __elo_packed_begin __pragma(pack(push, 1))\n#define __elo_packed_end __pragma(pack(pop))\n");
+            self.output.push_str("#define __elo_packed\n#define __elo_align(n) __declspec(align(n))\n#else\n#define __elo_packed_begin\n#define __elo_packed_end\n");
+            self.output.push_str("#define __elo_packed __attribute__((packed))\n#define __elo_align(n) __attribute__((aligned(n)))\n#endif\n\n");
+        }
+
+        let mut opaques = Vec::new();
+        for stmt in &program.nodes {
+            match &stmt.kind {
+                cir::StatementKind::StructStatement(s) if cir::Typing::Struct(s.clone()).is_c_compatible() => {
+                    s.fields.iter().for_each(|(_, typ)| opaque(typ, &mut opaques));
+                }
+                cir::StatementKind::FnStatement(f) if f.export.is_some() => {
+                    f.head.arguments.iter().for_each(|(_, typ)| opaque(typ, &mut opaques));
+                    opaque(&f.head.ret, &mut opaques);
+                }
+                _ => {}
+            }
+        }
+        for declaration in &opaques {
+            self.output.push_str(declaration);
+        }
+        if !opaques.is_empty() {
+            self.output.push('\n');
+        }
+
+        let mut functions = Vec::new();
+        for stmt in &program.nodes {
+            match &stmt.kind {
+                // The tags are the ones of the generated code, so both declare the same types
+                cir::StatementKind::StructStatement(s) => {
+                    if !cir::Typing::Struct(s.clone()).is_c_compatible() {
+                        continue;
+                    }
+                    let layout = &s.layout;
+                    let mut attributes = String::new();
+                    if layout.packed {
+                        self.output.push_str("__elo_packed_begin\n");
+                        attributes.push_str("__elo_packed ");
+                    }
+                    if let Some(align) = layout.align {
+                        attributes.push_str(&format!("__elo_align({align}) "));
+                    }
+                    self.output.push_str(&format!("typedef {} {attributes}{} {{\n", aggregate_keyword(layout), mangle_struct(&s.name)));
+                    for (i, (name, typ)) in s.fields.iter().enumerate() {
+                        let mut attributes = String::new();
+                        if layout.field_packed.get(i).copied().unwrap_or(false) {
+                            attributes.push_str("__elo_packed ");
+                        }
+                        if let Some(align) = layout.field_aligns.get(i).copied().flatten() {
+                            attributes.push_str(&format!("__elo_align({align}) "));
+                        }
+                        self.output.push_str(&format!("    {attributes}{};\n", declaration(typ, name)));
+                    }
+                    self.output.push_str(&format!("}} {};\n", s.name));
+                    if layout.packed {
+                        self.output.push_str("__elo_packed_end\n");
+                    }
+                    self.output.push('\n');
+                }
+                cir::StatementKind::EnumStatement(e) => {
+                    if e.is_tagged() {
+                        continue;
+                    }
+                    if e.backing.is_some() {
+                        let backing = c_type(&e.backing_type());
+                        self.output.push_str(&format!("typedef {backing} {};\n", e.name));
+                        for (variant, value) in &e.variants {
+                            self.output.push_str(&format!("#define {}_{variant} (({backing}){value})\n", e.name));
+                        }
+                        self.output.push('\n');
+                        continue;
+                    }
+                    self.output.push_str(&format!("typedef enum {} {{\n", mangle_enum(&e.name)));
+                    for (variant, value) in &e.variants {
+                        self.output.push_str(&format!("    {}_{variant} = {value},\n", e.name));
+                    }
+                    self.output.push_str(&format!("}} {};\n\n", e.name));
+                }
+                cir::StatementKind::FnStatement(f) => {
+                    if let Some(symbol) = &f.export {
+                        let arguments = f.head.arguments
+                            .iter()
+                            .map(|(name, typ)| declaration(typ, name))
+                            .collect::<Vec<String>>();
+                        let arguments = if arguments.is_empty() { String::from("void") } else { arguments.join(", ") };
+                        functions.push(format!("{} {symbol}({arguments});\n", c_type(&f.head.ret)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for function in functions {
+            self.output.push_str(&function);
+        }
+        self.output.push_str(&format!("\n#endif // {}\n", self.guard));
+    }
+}
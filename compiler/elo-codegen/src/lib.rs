@@ -1,4 +1,5 @@
 pub mod c;
 pub mod generator;
+pub mod header;
 #[cfg(test)]
 pub mod tests;
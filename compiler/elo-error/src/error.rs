@@ -20,7 +20,8 @@ pub fn error(
     }
     let line = line.unwrap();
 
-    let span_length = filespan.end - filespan.start;
+    // Spans merged across lines may end before they start
+    let span_length = filespan.end.saturating_sub(filespan.start);
 
     let line_number_digits = filespan.line.to_string().len();
     let indent = " ".repeat(line_number_digits + 2);
@@ -56,6 +56,58 @@ pub enum SemanticErrorCase {
         name: String,
         from: String,
     },
+    MissingFields {
+        name: String,
+        fields: Vec<String>,
+    },
+    DuplicateField {
+        name: String,
+        field: String,
+    },
+    UnknownArgument {
+        function: String,
+        name: String,
+    },
+    DuplicateArgument {
+        function: String,
+        name: String,
+    },
+    MissingArgument {
+        function: String,
+        name: String,
+    },
+    InvalidSpread {
+        function: String,
+    },
+    ReturnInDefer,
+    MissingBlockValue,
+    UnmatchableType {
+        typ: String,
+    },
+    NonExhaustiveMatch {
+        missing: String,
+    },
+    MisplacedElseArm,
+    NotConstant {
+        what: String,
+    },
+    DivisionByZero,
+    ConstantOverflow {
+        typ: String,
+    },
+    InvalidArraySize {
+        size: String,
+    },
+    EvaluationLimit {
+        limit: String,
+    },
+    IndexOutOfBounds {
+        index: String,
+        len: String,
+    },
+    StaticAssertFailed {
+        message: String,
+    },
     ReturnValueOnVoidFunction {
         function: String,
     },
@@ -91,6 +143,39 @@ pub enum SemanticErrorCase {
         defined: Span,
     },
     MisplacedReturn,
+    NonCType {
+        function: String,
+        typ: String,
+    },
+    InvalidSymbol {
+        symbol: String,
+    },
+    InvalidAlignment {
+        align: String,
+    },
+    UnionFieldCount {
+        name: String,
+        got: usize,
+    },
+    InvalidEnumValue {
+        enumeration: String,
+        value: String,
+    },
+    DuplicateDiscriminant {
+        enumeration: String,
+        variant: String,
+        other: String,
+        value: String,
+    },
+    PayloadOperation {
+        enumeration: String,
+    },
+    ShortLivedValue {
+        what: String,
+    },
+    PointerOperation {
+        operation: String,
+    },
 }
 
 #[derive(Debug)]
@@ -201,6 +286,195 @@ pub fn semantic_error(pe: SemanticErrorCase, filespan: &FileSpan) {
                 Some(&format!("this tuple only contains only {member_count} member(s) but used {member}")),
             );
         }
+        SemanticErrorCase::MissingBlockValue => {
+            error(
+                error_name,
+                &format!("block has no value"),
+                filespan,
+                Some("the value of a block is its last expression"),
+                None,
+            );
+        }
+        SemanticErrorCase::UnmatchableType { typ } => {
+            error(
+                error_name,
+                &format!("cannot match values of type {typ}"),
+                filespan,
+                Some("only integers, characters, booleans and enums can be matched"),
+                None,
+            );
+        }
+        SemanticErrorCase::NonExhaustiveMatch { missing } => {
+            error(
+                error_name,
+                &format!("match does not handle {missing}"),
+                filespan,
+                Some("handle every case or add an else arm"),
+                None,
+            );
+        }
+        SemanticErrorCase::NotConstant { what } => {
+            error(
+                error_name,
+                &format!("{what} can't be evaluated at compile time"),
+                filespan,
+                Some("constants and array sizes can only use literals, operators, casts, other constants and calls to Elo functions that don't use pointers, globals or intrinsics"),
+                None,
+            );
+        }
+        SemanticErrorCase::DivisionByZero => {
+            error(
+                error_name,
+                &format!("division by zero in constant expression"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::ConstantOverflow { typ } => {
+            error(
+                error_name,
+                &format!("constant expression overflows {typ}"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::InvalidArraySize { size } => {
+            error(
+                error_name,
+                &format!("invalid array size {size}"),
+                filespan,
+                Some("the size of an array can't be negative"),
+                None,
+            );
+        }
+        SemanticErrorCase::EvaluationLimit { limit } => {
+            error(
+                error_name,
+                &format!("compile-time evaluation exceeded the limit of {limit}"),
+                filespan,
+                Some("check for infinite loops or infinite recursion in the functions called at compile time"),
+                None,
+            );
+        }
+        SemanticErrorCase::StaticAssertFailed { message } => {
+            error(
+                error_name,
+                &format!("static assertion failed: {message}"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::IndexOutOfBounds { index, len } => {
+            error(
+                error_name,
+                &format!("index {index} is out of bounds for an array of length {len}"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::MisplacedElseArm => {
+            error(
+                error_name,
+                &format!("else arm is not the last arm of the match"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::ReturnInDefer => {
+            error(
+                error_name,
+                &format!("attempt to use return statement inside of defer block"),
+                filespan,
+                Some("deferred code runs when the scope is exited, so it can't return"),
+                None,
+            );
+        }
+        SemanticErrorCase::NonCType { function, typ } => {
+            error(
+                error_name,
+                &format!("exported function {function} uses type {typ}, which C can't use"),
+                filespan,
+                None,
+                Some("exported functions can only take and return integers, floats, bool, char, cstr, pointers, enums and structs of them"),
+            );
+        }
+        SemanticErrorCase::InvalidSymbol { symbol } => {
+            error(
+                error_name,
+                &format!("invalid C symbol '{symbol}'"),
+                filespan,
+                None,
+                Some("symbols must be C identifiers, and main and names starting with __elo_ are reserved"),
+            );
+        }
+        SemanticErrorCase::InvalidAlignment { align } => {
+            error(
+                error_name,
+                &format!("invalid alignment {align}"),
+                filespan,
+                Some("alignments must be powers of two"),
+                None,
+            );
+        }
+        SemanticErrorCase::UnionFieldCount { name, got } => {
+            error(
+                error_name,
+                &format!("union {name} is initialized with {got} fields"),
+                filespan,
+                None,
+                Some("the fields of a union share the same memory, so exactly one of them is initialized"),
+            );
+        }
+        SemanticErrorCase::InvalidEnumValue { enumeration, value } => {
+            error(
+                error_name,
+                &format!("{value} is not the value of a variant of enumeration {enumeration}"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::DuplicateDiscriminant { enumeration, variant, other, value } => {
+            error(
+                error_name,
+                &format!("variant {variant} of enumeration {enumeration} has the value {value} of variant {other}"),
+                filespan,
+                Some("give each variant a different value"),
+                Some("a value converted into the enumeration must match only one variant"),
+            );
+        }
+        SemanticErrorCase::PayloadOperation { enumeration } => {
+            error(
+                error_name,
+                &format!("invalid operation on values of enumeration {enumeration}"),
+                filespan,
+                Some("use match to tell the variants apart"),
+                Some(&format!("the variants of {enumeration} carry payloads, so they are not integers")),
+            );
+        }
+        SemanticErrorCase::PointerOperation { operation } => {
+            error(
+                error_name,
+                &format!("invalid operation {operation} on pointers"),
+                filespan,
+                Some("add or subtract an integer to move a pointer, or subtract two pointers to get their distance"),
+                Some("pointers can only be moved, subtracted, compared and assigned"),
+            );
+        }
+        SemanticErrorCase::ShortLivedValue { what } => {
+            error(
+                error_name,
+                &format!("{what} does not live long enough"),
+                filespan,
+                Some("create the value in the scope it is stored in, or return it"),
+                Some("it refers to memory freed when the scope it was created in is exited"),
+            );
+        }
         SemanticErrorCase::MisplacedReturn => {
             error(
                 error_name,
@@ -319,6 +593,60 @@ pub fn semantic_error(pe: SemanticErrorCase, filespan: &FileSpan) {
                 None,
             );
         }
+        SemanticErrorCase::MissingFields { name, fields } => {
+            error(
+                error_name,
+                &format!("missing field(s) {} in initializer of struct {name}", fields.join(", ")),
+                filespan,
+                Some(&format!("give the missing fields a value, declare default values for them in struct {name} or use ..<expression> to take them from another {name}")),
+                None,
+            );
+        }
+        SemanticErrorCase::DuplicateField { name, field } => {
+            error(
+                error_name,
+                &format!("field '{field}' given more than once in initializer of struct {name}"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::UnknownArgument { function, name } => {
+            error(
+                error_name,
+                &format!("{function} has no argument named '{name}'"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::DuplicateArgument { function, name } => {
+            error(
+                error_name,
+                &format!("argument '{name}' passed more than once to {function}"),
+                filespan,
+                None,
+                None,
+            );
+        }
+        SemanticErrorCase::MissingArgument { function, name } => {
+            error(
+                error_name,
+                &format!("missing argument '{name}' in call to {function}"),
+                filespan,
+                Some(&format!("pass it by position or as {name}: <expression>")),
+                None,
+            );
+        }
+        SemanticErrorCase::InvalidSpread { function } => {
+            error(
+                error_name,
+                &format!("cannot spread a slice into the arguments of {function}"),
+                filespan,
+                Some("only variadic Elo functions accept ...slice, in place of all of their variadic arguments"),
+                None,
+            );
+        }
         SemanticErrorCase::NonTupleMemberAccess { thing, typ } => {
             error(
                 error_name,
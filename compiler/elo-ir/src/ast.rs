@@ -2,7 +2,7 @@
 
 use elo_lexer::{span::Span, token::Token};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Program {
     pub filename: String,
     pub nodes: Vec<Node>,
@@ -10,7 +10,7 @@ pub struct Program {
 
 pub type OperatorPrecedence = usize;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum BinaryOperation {
     Add,
     Sub,
@@ -115,7 +115,7 @@ impl BinaryOperation {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum UnaryOperation {
     Neg,
     Not,
@@ -147,13 +147,13 @@ impl UnaryOperation {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Expression {
     pub span: Span,
     pub data: ExpressionData,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ExpressionData {
     BinaryOperation {
         operator: BinaryOperation,
@@ -203,10 +203,36 @@ pub enum ExpressionData {
     FunctionCall {
         function: Box<Expression>,
         arguments: Vec<Expression>,
+        spread: Option<Box<Expression>>, // Slice passed as the variadic tail (...xs)
+        named: Vec<Field>, // Named arguments (name: value), always after the positional ones
     },
     StructInit {
         name: String,
         fields: Vec<Field>,
+        base: Option<Box<Expression>>,
+    },
+    Closure {
+        arguments: Vec<TypedField>,
+        ret: Option<Type>,
+        block: Block,
+    },
+    // The value of each block is its last expression
+    If {
+        condition: Box<Expression>,
+        block_true: Block,
+        block_false: Block,
+    },
+    Match {
+        value: Box<Expression>,
+        arms: Vec<MatchArm>,
+    },
+    // Expression evaluated by the compiler, replaced by its value
+    Comptime {
+        expr: Box<Expression>,
+    },
+    // Type given as argument to an intrinsic, like the T of sizeof(T)
+    TypeArgument {
+        typ: Type,
     },
     IntegerLiteral {
         value: i128,
@@ -217,50 +243,61 @@ pub enum ExpressionData {
     BooleanLiteral {
         value: bool,
     },
+    NullLiteral,
     Identifier {
         name: String,
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Option<Expression>, // None for the else arm
+    pub block: Block,
+}
+
+#[derive(Debug, Clone)]
 pub struct LetStatement {
     pub binding: String,
     pub assignment: Expression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VarStatement {
     pub binding: String,
     pub assignment: Expression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ConstStatement {
     pub binding: String,
     pub assignment: Expression,
     pub typing: Type,
+    pub doc: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Block {
     pub content: Vec<Node>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Node {
     pub span: Span,
     pub stmt: Statement,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FnStatement {
     pub name: String,
     pub block: Block,
     pub ret: Option<Type>,
     pub arguments: Vec<TypedField>,
+    pub variadic: bool, // The last argument is a variadic tail (xs: ...T)
+    pub doc: Option<String>,
+    pub export: Option<String>, // C symbol of an exported function
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExternFnStatement {
     pub name: String,
     pub ret: Option<Type>,
@@ -268,43 +305,102 @@ pub struct ExternFnStatement {
     pub variadic: bool,
 }
 
-#[derive(Debug)]
+// Variable defined by a C library
+#[derive(Debug, Clone)]
+pub struct ExternVarStatement {
+    pub name: String,
+    pub typing: Type,
+    pub mutable: bool,
+}
+
+#[derive(Debug, Clone)]
 pub struct StructStatement {
     pub name: String,
     pub fields: Vec<TypedField>,
+    pub doc: Option<String>,
+    // Declared with `union`, so the fields share the same memory
+    pub union: bool,
+    // Layout annotations, like in `packed align(4) struct`
+    pub packed: bool,
+    pub align: Option<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EnumStatement {
     pub name: String,
-    pub variants: Vec<String>,
+    pub variants: Vec<EnumVariant>,
+    // Integer type the enum is stored as, like the u16 of `enum Key: u16`
+    pub backing: Option<Type>,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: String,
+    // Explicit value, like in `A = 65`. Without it, the value follows the previous variant's
+    pub value: Option<Expression>,
+    pub payload: Option<Payload>,
+    pub doc: Option<String>,
 }
 
-#[derive(Debug)]
+// Data carried by a variant, like in `Circle(f64)` or `Rect { w: f64, h: f64 }`
+#[derive(Debug, Clone)]
+pub enum Payload {
+    Tuple(Vec<Type>),
+    Struct(Vec<TypedField>),
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeStatement {
+    pub name: String,
+    pub typing: Type,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone)]
 pub struct IfStatement {
     pub condition: Expression,
     pub block_true: Block,
     pub block_false: Option<Block>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WhileStatement {
     pub condition: Expression,
     pub block: Block,
 }
 
-#[derive(Debug)]
+// for binding in iterable { block }
+#[derive(Debug, Clone)]
+pub struct ForStatement {
+    pub binding: String,
+    pub iterable: Expression,
+    pub block: Block,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeferStatement {
+    pub block: Block,
+}
+
+#[derive(Debug, Clone)]
+pub struct StaticAssertStatement {
+    pub condition: Expression,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
 pub struct ReturnStatement {
     pub expr: Option<Expression>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Type {
     pub span: Span,
     pub typing: Typing,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum Typing {
     Named {
         name: String,
@@ -312,7 +408,7 @@ pub enum Typing {
     },
     Array {
         typ: Box<Type>,
-        amount: usize,
+        amount: Box<Expression>, // Evaluated at compile time
     },
     Slice {
         typ: Box<Type>,
@@ -330,29 +426,39 @@ pub enum Typing {
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TypedField {
     pub name: String,
     pub typing: Type,
+    pub default: Option<Expression>,
+    pub doc: Option<String>,
+    // Layout annotations of a struct field, like in `packed align(2) count: u32`
+    pub packed: bool,
+    pub align: Option<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Field {
     pub name: String,
     pub value: Expression,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Statement {
     LetStatement(LetStatement),
     VarStatement(VarStatement),
     ConstStatement(ConstStatement),
     FnStatement(FnStatement),
     ExternFnStatement(ExternFnStatement),
+    ExternVarStatement(ExternVarStatement),
     StructStatement(StructStatement),
     EnumStatement(EnumStatement),
+    TypeStatement(TypeStatement),
     IfStatement(IfStatement),
     WhileStatement(WhileStatement),
+    ForStatement(ForStatement),
     ExpressionStatement(Expression),
     ReturnStatement(ReturnStatement),
+    DeferStatement(DeferStatement),
+    StaticAssertStatement(StaticAssertStatement),
 }
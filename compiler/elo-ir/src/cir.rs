@@ -183,7 +183,11 @@ impl ResolvedIntrinsic {
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Intrinsic {
     Print,
-    Args
+    Args,
+    // Layout queries, replaced by their value during the semantic checking
+    SizeOf,
+    AlignOf,
+    OffsetOf,
 }
 
 impl std::fmt::Display for Intrinsic {
@@ -191,6 +195,9 @@ impl std::fmt::Display for Intrinsic {
         match self {
             Intrinsic::Print => write!(f, "print"),
             Intrinsic::Args => write!(f, "args"),
+            Intrinsic::SizeOf => write!(f, "sizeof"),
+            Intrinsic::AlignOf => write!(f, "alignof"),
+            Intrinsic::OffsetOf => write!(f, "offsetof"),
         }
     }
 }
@@ -200,6 +207,9 @@ impl Intrinsic {
         match s {
             "print" => Some(Intrinsic::Print),
             "args" => Some(Intrinsic::Args),
+            "sizeof" => Some(Intrinsic::SizeOf),
+            "alignof" => Some(Intrinsic::AlignOf),
+            "offsetof" => Some(Intrinsic::OffsetOf),
             _ => None
         }
     }
@@ -226,6 +236,11 @@ pub enum ExpressionData {
         exprs: Vec<Expression>,
         typ: Typing,
     },
+    // Extra arguments of a variadic Elo function, packed into a slice of `typ`
+    SlicePack {
+        typ: Typing,
+        exprs: Vec<Expression>,
+    },
     ArraySubscript {
         origin: Box<Expression>,
         index: Box<Expression>,
@@ -239,13 +254,41 @@ pub enum ExpressionData {
         origin: Box<Expression>,
         index: Box<Expression>,
     },
+    // Indexing through a `*T`, like in C
+    PointerSubscript {
+        origin: Box<Expression>,
+        index: Box<Expression>,
+    },
     FieldAccess {
         origin: Box<Expression>,
         field: String,
     },
     EnumVariant {
-        origin: String,
+        origin: Enum,
+        variant: String,
+    },
+    // Variant with a payload, like `Shape.Circle(1.0)`. The fields are in the order of the payload.
+    EnumInit {
+        origin: Enum,
+        variant: String,
+        fields: Vec<Field>,
+    },
+    // Field of the payload of the value compared by the innermost match, which binds it in an arm
+    Payload {
         variant: String,
+        field: String,
+    },
+    // Name of the variant of an enum value, as a str
+    EnumName {
+        expr: Box<Expression>,
+        origin: Enum,
+    },
+    // Checked conversion of an integer into the variant with its value
+    EnumFromInt {
+        expr: Box<Expression>,
+        origin: Enum,
+        // Whether the integer is signed, which decides how it is compared to the values
+        signed: bool,
     },
     TupleAccess {
         origin: Box<Expression>,
@@ -255,13 +298,47 @@ pub enum ExpressionData {
         function: Box<Expression>,
         arguments: Vec<Expression>,
     },
+    // Call through a function value (a closure or a function used as a value) of type `typ`
+    IndirectCall {
+        function: Box<Expression>,
+        arguments: Vec<Expression>,
+        typ: Typing,
+    },
+    // Anonymous function. The captured variables are copied into its environment when it is created.
+    Closure {
+        arguments: Vec<TypedField>,
+        ret: Typing,
+        captures: Vec<TypedField>,
+        block: Vec<Statement>,
+    },
     IntrinsicCall {
         intrinsic: ResolvedIntrinsic,
         arguments: Vec<Expression>,
     },
+    // Branch of an if or match expression: the statements followed by the expression
+    // that gives the block its value (none in a match used as a statement)
+    Block {
+        block: Vec<Statement>,
+        value: Option<Box<Expression>>,
+    },
+    If {
+        condition: Box<Expression>,
+        r#true: Box<Expression>,
+        r#false: Box<Expression>,
+        typ: Typing,
+    },
+    // Each arm is a pattern compared to the value and its branch. The arm without a pattern is the else arm.
+    Match {
+        value: Box<Expression>,
+        value_typ: Typing,
+        arms: Vec<(Option<Expression>, Expression)>,
+        typ: Typing,
+    },
     StructInit {
         origin: Struct,
         fields: Vec<Field>,
+        // The fields not in `fields` are taken from here (update syntax: ..base)
+        base: Option<Box<Expression>>,
     },
     Tuple {
         exprs: Vec<Expression>,
@@ -276,13 +353,31 @@ pub enum ExpressionData {
     Bool {
         value: bool,
     },
+    Null,
     Identifier {
         name: String,
     },
+    // Variable defined by a C library, which is not mangled
+    ExternVariable {
+        name: String,
+    },
     Cast {
         expr: Box<Expression>,
         typ: Typing,
-    }
+    },
+    // NUL-terminated copy of a str, freed when the scope is exited
+    StrToCStr {
+        expr: Box<Expression>,
+    },
+    // Copy of a cstr in a new str
+    CStrToStr {
+        expr: Box<Expression>,
+    },
+    // Address of the C entry point of an exported function
+    ExportedFunction {
+        name: String,
+        symbol: String,
+    },
 }
 
 impl std::fmt::Display for ExpressionData {
@@ -295,11 +390,20 @@ impl std::fmt::Display for ExpressionData {
             ExpressionData::ArraySubscript { origin, index } => write!(f, "\"{origin}[{index}]\""),
             ExpressionData::SliceSubscript { typ: _, origin, index } => write!(f, "\"{origin}[{index}]\""),
             ExpressionData::StrSubscript { origin, index } => write!(f, "\"{origin}[{index}]\""),
+            ExpressionData::PointerSubscript { origin, index } => write!(f, "\"{origin}[{index}]\""),
             ExpressionData::ArrayLiteral { exprs, .. } => write!(f, "{{{}{}}}", exprs[0], if exprs.len() > 1 { "..." } else { "" }),
+            ExpressionData::SlicePack { exprs, .. } => write!(f, "{{{}}}", exprs.iter().map(|e| e.to_string()).collect::<Vec<String>>().join(", ")),
             ExpressionData::FieldAccess { origin, field } => write!(f, "{}.{}", origin, field),
             ExpressionData::TupleAccess { origin, field } => write!(f, "{}.{}", origin, field),
-            ExpressionData::EnumVariant { origin, variant } => write!(f, "{}.{}", origin, variant),
-            ExpressionData::FunctionCall { function, arguments } => {
+            ExpressionData::EnumVariant { origin, variant } => write!(f, "{}.{}", origin.name, variant),
+            ExpressionData::EnumInit { origin, variant, fields } => {
+                let fields = fields.iter().map(|(_, value)| value.to_string()).collect::<Vec<String>>();
+                write!(f, "{}.{}({})", origin.name, variant, fields.join(", "))
+            }
+            ExpressionData::Payload { variant, field } => write!(f, "{variant}.{field}"),
+            ExpressionData::EnumName { expr, .. } => write!(f, "{expr}.name()"),
+            ExpressionData::EnumFromInt { expr, origin, .. } => write!(f, "{} as {}", expr, origin.name),
+            ExpressionData::FunctionCall { function, arguments } | ExpressionData::IndirectCall { function, arguments, .. } => {
                 let mut fmt = String::from(&format!("{function}("));
                 if arguments.len() == 1 {
                     fmt.push_str(&format!("{}", arguments[0]))
@@ -309,6 +413,20 @@ impl std::fmt::Display for ExpressionData {
                 fmt.push(')');
                 write!(f, "{fmt}")
             }
+            ExpressionData::Closure { arguments, ret, .. } => {
+                let arguments = arguments.iter().map(|(name, typ)| format!("{name}: {typ}")).collect::<Vec<String>>();
+                if let Typing::Void = ret {
+                    write!(f, "fn ({})", arguments.join(", "))
+                } else {
+                    write!(f, "fn ({}): {ret}", arguments.join(", "))
+                }
+            }
+            ExpressionData::Block { value, .. } => match value {
+                Some(value) => write!(f, "{{... {value}}}"),
+                None => write!(f, "{{...}}"),
+            }
+            ExpressionData::If { condition, r#true, r#false, .. } => write!(f, "if {} {{{}}} else {{{}}}", condition, r#true, r#false),
+            ExpressionData::Match { value, .. } => write!(f, "match {value} {{...}}"),
             ExpressionData::IntrinsicCall { intrinsic, arguments } => {
                 let mut fmt = String::from(&format!("{}(", intrinsic.get_origin()));
                 if arguments.len() == 1 {
@@ -319,13 +437,16 @@ impl std::fmt::Display for ExpressionData {
                 fmt.push(')');
                 write!(f, "{fmt}")
             }
-            ExpressionData::StructInit { origin, fields } => {
+            ExpressionData::StructInit { origin, fields, base } => {
                 let mut fmt = String::from(&format!("{} {{", origin.name));
                 if fields.len() == 1 {
                     fmt.push_str(&format!("{}: {}", fields[0].0, fields[0].1))
                 } else if fields.len() >= 2 {
                     fmt.push_str(&format!("{}: {}, ...", fields[0].0, fields[0].1))
                 }
+                if let Some(base) = base {
+                    fmt.push_str(&format!("{}..{}", if fields.is_empty() { "" } else { ", " }, base));
+                }
                 fmt.push_str(" }");
                 write!(f, "{fmt}")
             }
@@ -342,10 +463,14 @@ impl std::fmt::Display for ExpressionData {
             ExpressionData::Cast { expr, typ } => {
                 write!(f, "{expr} as {typ}")
             }
+            ExpressionData::StrToCStr { expr } => write!(f, "{expr} as cstr"),
+            ExpressionData::CStrToStr { expr } => write!(f, "{expr} as str"),
+            ExpressionData::ExportedFunction { name, .. } => write!(f, "{name} as rawptr"),
             ExpressionData::Integer { value } => write!(f, "{}", value),
             ExpressionData::Float { value } => write!(f, "{}", value),
             ExpressionData::Bool { value } => write!(f, "{}", value),
-            ExpressionData::Identifier { name } => write!(f, "{}", name),
+            ExpressionData::Null => write!(f, "null"),
+            ExpressionData::Identifier { name } | ExpressionData::ExternVariable { name } => write!(f, "{}", name),
         }
     }
 }
@@ -362,6 +487,8 @@ pub struct Program {
 pub struct Function {
     pub head: FunctionHead,
     pub block: Block,
+    // C symbol of the function, if it is exported
+    pub export: Option<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -377,12 +504,18 @@ impl std::fmt::Display for FunctionHead {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         let mut fmt = format!("fn {}(", self.name);
         for (i, (name, typ)) in self.arguments.iter().enumerate() {
-            fmt.push_str(&format!("{name}: {typ}"));
+            match typ {
+                // The variadic tail of an Elo function
+                Typing::Slice { typ } if self.variadic && !self.extrn && i == self.arguments.len()-1 => {
+                    fmt.push_str(&format!("{name}: ...{typ}"))
+                }
+                _ => fmt.push_str(&format!("{name}: {typ}")),
+            }
             if i < self.arguments.len()-1 {
                 fmt.push_str(", ");
             }
         }
-        if self.variadic {
+        if self.variadic && self.extrn {
             fmt.push_str(", ...");
         }
         fmt.push(')');
@@ -397,12 +530,231 @@ impl std::fmt::Display for FunctionHead {
 pub struct Struct {
     pub name: String,
     pub fields: Vec<TypedField>,
+    pub layout: Layout,
+}
+
+// Layout annotations of a struct, which the generated C code declares with attributes
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Layout {
+    // The fields share the same memory, like in a C union
+    pub union: bool,
+    // The fields are not padded, so they can be misaligned
+    pub packed: bool,
+    // Minimum alignment of the struct
+    pub align: Option<usize>,
+    // Minimum alignment of each field, in the order of the fields
+    pub field_aligns: Vec<Option<usize>>,
+    // Fields that are not padded, even though the struct is
+    pub field_packed: Vec<bool>,
+}
+
+impl Layout {
+    pub fn is_default(&self) -> bool {
+        !self.packed
+            && self.align.is_none()
+            && self.field_aligns.iter().all(Option::is_none)
+            && !self.field_packed.contains(&true)
+    }
+}
+
+impl Struct {
+    // Offsets of the fields, followed by the size and alignment of the struct,
+    // laid out like a C compiler does: each field is aligned to its own alignment
+    // (1 if it or the struct is packed, raised by align) and the size is rounded up to the largest one.
+    // The fields of a union all start at 0.
+    pub fn layout(&self, target: &Target) -> (Vec<usize>, usize, usize) {
+        let Layout { union, packed, align, field_aligns, field_packed } = &self.layout;
+        let mut offsets = Vec::new();
+        let mut size: usize = 0;
+        let mut struct_align = align.unwrap_or(1);
+        for (i, (_, typ)) in self.fields.iter().enumerate() {
+            let (field_size, natural_align) = typ.layout(target);
+            let minimum = field_aligns.get(i).copied().flatten().unwrap_or(1);
+            let field_packed = *packed || field_packed.get(i).copied().unwrap_or(false);
+            let field_align = if field_packed { minimum } else { natural_align.max(minimum) };
+            if *union {
+                offsets.push(0);
+                size = size.max(field_size);
+            } else {
+                size = size.next_multiple_of(field_align);
+                offsets.push(size);
+                size += field_size;
+            }
+            struct_align = struct_align.max(field_align);
+        }
+        (offsets, size.next_multiple_of(struct_align), struct_align)
+    }
+
+    pub fn offset_of(&self, field: &str, target: &Target) -> Option<usize> {
+        let (offsets, ..) = self.layout(target);
+        self.fields.iter().position(|(name, _)| name == field).map(|i| offsets[i])
+    }
+}
+
+fn layout_fields<'a>(types: impl Iterator<Item = &'a Typing>, target: &Target) -> (Vec<usize>, usize, usize) {
+    let mut offsets = Vec::new();
+    let mut size: usize = 0;
+    let mut align = 1;
+    for typ in types {
+        let (field_size, field_align) = typ.layout(target);
+        size = size.next_multiple_of(field_align);
+        offsets.push(size);
+        size += field_size;
+        align = align.max(field_align);
+    }
+    (offsets, size.next_multiple_of(align), align)
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Enum {
     pub name: String,
-    pub variants: Vec<String>,
+    // Variants and their values, in the order they are declared
+    pub variants: Vec<(String, i128)>,
+    // Integer type the enum is stored as, or None for a C enum (an int)
+    pub backing: Option<Primitive>,
+    // Data carried by each variant, in the order of the variants
+    pub payloads: Vec<Option<Payload>>,
+}
+
+// Data carried by a variant. The fields of a tuple payload are named by their position.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Payload {
+    Tuple(Vec<Typing>),
+    Struct(Vec<TypedField>),
+}
+
+impl Payload {
+    pub fn fields(&self) -> Vec<TypedField> {
+        match self {
+            Payload::Tuple(types) => types.iter().enumerate().map(|(i, typ)| (i.to_string(), typ.clone())).collect(),
+            Payload::Struct(fields) => fields.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Payload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Payload::Tuple(types) => {
+                let types = types.iter().map(|typ| typ.to_string()).collect::<Vec<String>>();
+                write!(f, "({})", types.join(", "))
+            }
+            Payload::Struct(fields) => {
+                let fields = fields.iter().map(|(name, typ)| format!("{name}: {typ}")).collect::<Vec<String>>();
+                write!(f, " {{ {} }}", fields.join(", "))
+            }
+        }
+    }
+}
+
+impl Enum {
+    pub fn value_of(&self, variant: &str) -> Option<i128> {
+        self.variants.iter().find(|(name, _)| name == variant).map(|(_, value)| *value)
+    }
+
+    // First variant with the value, if any
+    pub fn variant_of(&self, value: i128) -> Option<&String> {
+        self.variants.iter().find(|(_, v)| *v == value).map(|(name, _)| name)
+    }
+
+    pub fn backing_type(&self) -> Typing {
+        Typing::Primitive(self.backing.unwrap_or(Primitive::I32))
+    }
+
+    pub fn payload_of(&self, variant: &str) -> Option<&Payload> {
+        let index = self.variants.iter().position(|(name, _)| name == variant)?;
+        self.payloads.get(index)?.as_ref()
+    }
+
+    // Whether a variant carries data, so the enum is a tagged union instead of an integer
+    pub fn is_tagged(&self) -> bool {
+        self.payloads.iter().any(Option::is_some)
+    }
+
+    // Size and alignment. A tagged enum is a struct with the tag (the backing type)
+    // followed by a union of the payloads.
+    pub fn layout(&self, target: &Target) -> (usize, usize) {
+        let (tag_size, tag_align) = self.backing_type().layout(target);
+        if !self.is_tagged() {
+            return (tag_size, tag_align);
+        }
+        let (mut data_size, mut data_align) = (0, 1);
+        for payload in self.payloads.iter().flatten() {
+            let fields = payload.fields();
+            let (_, size, align) = layout_fields(fields.iter().map(|(_, typ)| typ), target);
+            data_size = data_size.max(size);
+            data_align = data_align.max(align);
+        }
+        let offset = tag_size.next_multiple_of(data_align);
+        let align = tag_align.max(data_align);
+        ((offset + data_size).next_multiple_of(align), align)
+    }
+}
+
+// Sizes of the C types that change with the platform the program is compiled for
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Target {
+    pub pointer_size: usize,
+    pub long_size: usize,
+    // Alignment of 64-bit integers and doubles in structs (4 on 32-bit x86)
+    pub int64_align: usize,
+    pub char_signed: bool,
+}
+
+impl Target {
+    pub const X86_64_LINUX: Target = Target { pointer_size: 8, long_size: 8, int64_align: 8, char_signed: true };
+    pub const X86_64_WINDOWS: Target = Target { pointer_size: 8, long_size: 4, int64_align: 8, char_signed: true };
+    pub const AARCH64_LINUX: Target = Target { pointer_size: 8, long_size: 8, int64_align: 8, char_signed: false };
+    pub const I686_LINUX: Target = Target { pointer_size: 4, long_size: 4, int64_align: 4, char_signed: true };
+
+    pub fn from_str(s: &str) -> Option<Target> {
+        match s {
+            "x86_64-linux" | "x86_64-macos" => Some(Target::X86_64_LINUX),
+            "x86_64-windows" => Some(Target::X86_64_WINDOWS),
+            "aarch64-linux" => Some(Target::AARCH64_LINUX),
+            "i686-linux" => Some(Target::I686_LINUX),
+            _ => None,
+        }
+    }
+
+    // The platform the compiler itself runs on
+    pub fn host() -> Target {
+        let pointer_size = std::mem::size_of::<usize>();
+        Target {
+            pointer_size,
+            long_size: if cfg!(windows) { 4 } else { pointer_size },
+            int64_align: if cfg!(target_arch = "x86") { 4 } else { 8 },
+            char_signed: !(cfg!(target_arch = "aarch64") && cfg!(target_os = "linux")),
+        }
+    }
+
+    // Primitive with the size of a C type, for the c_* type names
+    pub fn c_type(&self, name: &str) -> Option<Primitive> {
+        let long = |signed| match (self.long_size, signed) {
+            (8, true) => Primitive::I64,
+            (8, false) => Primitive::U64,
+            (_, true) => Primitive::I32,
+            (_, false) => Primitive::U32,
+        };
+        match name {
+            "c_char" => Some(if self.char_signed { Primitive::I8 } else { Primitive::U8 }),
+            "c_schar" => Some(Primitive::I8),
+            "c_uchar" => Some(Primitive::U8),
+            "c_short" => Some(Primitive::I16),
+            "c_ushort" => Some(Primitive::U16),
+            "c_int" => Some(Primitive::I32),
+            "c_uint" => Some(Primitive::U32),
+            "c_long" => Some(long(true)),
+            "c_ulong" => Some(long(false)),
+            "c_longlong" => Some(Primitive::I64),
+            "c_ulonglong" => Some(Primitive::U64),
+            "c_size_t" => Some(Primitive::USize),
+            "c_ssize_t" | "c_ptrdiff_t" => Some(Primitive::ISize),
+            "c_float" => Some(Primitive::F32),
+            "c_double" => Some(Primitive::F64),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
@@ -417,6 +769,9 @@ pub enum Primitive {
     U8,
     Int,
     UInt,
+    // Pointer-sized integers
+    ISize,
+    USize,
     Float,
     F32,
     F64,
@@ -424,6 +779,8 @@ pub enum Primitive {
     Str,
     Char,
     CStr,
+    // Opaque pointer, like void* in C
+    RawPtr,
 }
 
 impl Primitive {
@@ -435,6 +792,8 @@ impl Primitive {
             "float" => Some(Primitive::Float),
             "int" => Some(Primitive::Int),
             "uint" => Some(Primitive::UInt),
+            "isize" => Some(Primitive::ISize),
+            "usize" => Some(Primitive::USize),
             "i8" => Some(Primitive::I8),
             "i16" => Some(Primitive::I16),
             "i32" => Some(Primitive::I32),
@@ -447,6 +806,7 @@ impl Primitive {
             "str" => Some(Primitive::Str),
             "char" => Some(Primitive::Char),
             "cstr" => Some(Primitive::CStr),
+            "rawptr" => Some(Primitive::RawPtr),
             _ => None,
         }
     }
@@ -461,6 +821,8 @@ impl std::fmt::Display for Primitive {
             Primitive::Float => write!(f, "float"),
             Primitive::Int => write!(f, "int"),
             Primitive::UInt => write!(f, "uint"),
+            Primitive::ISize => write!(f, "isize"),
+            Primitive::USize => write!(f, "usize"),
             Primitive::I8 => write!(f, "i8"),
             Primitive::I16 => write!(f, "i16"),
             Primitive::I32 => write!(f, "i32"),
@@ -473,6 +835,7 @@ impl std::fmt::Display for Primitive {
             Primitive::Str => write!(f, "str"),
             Primitive::Char => write!(f, "char"),
             Primitive::CStr => write!(f, "cstr"),
+            Primitive::RawPtr => write!(f, "rawptr"),
         }
     }
 }
@@ -521,6 +884,7 @@ impl Typing {
             Typing::Primitive(Primitive::U16) => Some(Typing::Primitive(Primitive::I16)),
             Typing::Primitive(Primitive::U8) => Some(Typing::Primitive(Primitive::I8)),
             Typing::Primitive(Primitive::UInt) => Some(Typing::Primitive(Primitive::Int)),
+            Typing::Primitive(Primitive::USize) => Some(Typing::Primitive(Primitive::ISize)),
             _ => None,
         }
     }
@@ -536,7 +900,9 @@ impl Typing {
             | Typing::Primitive(Primitive::U16)
             | Typing::Primitive(Primitive::U8)
             | Typing::Primitive(Primitive::Int)
-            | Typing::Primitive(Primitive::UInt) => true,
+            | Typing::Primitive(Primitive::UInt)
+            | Typing::Primitive(Primitive::ISize)
+            | Typing::Primitive(Primitive::USize) => true,
             _ => false,
         }
     }
@@ -547,7 +913,8 @@ impl Typing {
             | Typing::Primitive(Primitive::U32)
             | Typing::Primitive(Primitive::U16)
             | Typing::Primitive(Primitive::U8)
-            | Typing::Primitive(Primitive::UInt) => true,
+            | Typing::Primitive(Primitive::UInt)
+            | Typing::Primitive(Primitive::USize) => true,
             _ => false,
         }
     }
@@ -558,11 +925,13 @@ impl Typing {
             | Typing::Primitive(Primitive::I32)
             | Typing::Primitive(Primitive::I16)
             | Typing::Primitive(Primitive::I8)
-            | Typing::Primitive(Primitive::Int) => true,
+            | Typing::Primitive(Primitive::Int)
+            | Typing::Primitive(Primitive::ISize) => true,
             _ => false,
         }
     }
 
+    // Rank of the number types in the implicit conversions. This is not their size in C, see `layout`.
     pub fn get_size(&self) -> u8 {
         match self {
             Typing::Primitive(Primitive::I64) => 8,
@@ -582,6 +951,67 @@ impl Typing {
         }
     }
 
+    // Smallest and largest rank of the type on any target. Pointer-sized integers are 4 or 8 bytes,
+    // so the implicit conversions from and into them are the ones valid on every target.
+    pub fn get_size_range(&self) -> (u8, u8) {
+        match self {
+            Typing::Primitive(Primitive::ISize | Primitive::USize) => (4, 8),
+            _ => (self.get_size(), self.get_size()),
+        }
+    }
+
+    // Size and alignment of the type in the generated C code
+    pub fn layout(&self, target: &Target) -> (usize, usize) {
+        let pointer = (target.pointer_size, target.pointer_size);
+        match self {
+            Typing::Void | Typing::Intrinsic(_) => (0, 1),
+            Typing::Primitive(p) => match p {
+                Primitive::I8 | Primitive::U8 | Primitive::Bool => (1, 1),
+                Primitive::I16 | Primitive::U16 => (2, 2),
+                Primitive::I32 | Primitive::U32 | Primitive::Int | Primitive::UInt
+                | Primitive::Char | Primitive::F32 | Primitive::Float => (4, 4),
+                Primitive::I64 | Primitive::U64 | Primitive::F64 => (8, target.int64_align),
+                Primitive::ISize | Primitive::USize | Primitive::CStr | Primitive::RawPtr => pointer,
+                // Slot, offset and size
+                Primitive::Str => (3 * target.pointer_size, target.pointer_size),
+            },
+            Typing::Enum(e) => e.layout(target),
+            Typing::Pointer { .. } => pointer,
+            // Slot and length
+            Typing::Slice { .. } => (2 * target.pointer_size, target.pointer_size),
+            // Slot, length, capacity and element size
+            Typing::List { .. } => (4 * target.pointer_size, target.pointer_size),
+            // Function and environment pointers
+            Typing::Function { .. } => (2 * target.pointer_size, target.pointer_size),
+            Typing::Array { typ, amount } => {
+                let (size, align) = typ.layout(target);
+                (size * amount, align)
+            }
+            Typing::Tuple { types } => {
+                let (_, size, align) = layout_fields(types.iter(), target);
+                (size, align)
+            }
+            Typing::Struct(s) => {
+                let (_, size, align) = s.layout(target);
+                (size, align)
+            }
+        }
+    }
+
+    // Whether values of the type may refer to runtime temporaries, which are freed
+    // when the scope that created them is exited
+    pub fn holds_temporaries(&self) -> bool {
+        match self {
+            Typing::Primitive(Primitive::CStr | Primitive::RawPtr) => true,
+            Typing::Pointer { .. } | Typing::Slice { .. } | Typing::Function { .. } => true,
+            Typing::Array { typ, .. } => typ.holds_temporaries(),
+            Typing::Tuple { types } => types.iter().any(|t| t.holds_temporaries()),
+            Typing::Struct(s) => s.fields.iter().any(|(_, t)| t.holds_temporaries()),
+            Typing::Enum(e) => e.payloads.iter().flatten().any(|p| p.fields().iter().any(|(_, t)| t.holds_temporaries())),
+            _ => false,
+        }
+    }
+
     pub fn is_bool(&self) -> bool {
         if let Typing::Primitive(Primitive::Bool) = self {
             return true;
@@ -589,6 +1019,22 @@ impl Typing {
         false
     }
 
+    pub fn is_pointer(&self) -> bool {
+        matches!(self, Typing::Pointer { .. } | Typing::Primitive(Primitive::RawPtr))
+    }
+
+    // Whether C sees the type the same way, so it can cross between Elo and C code
+    pub fn is_c_compatible(&self) -> bool {
+        match self {
+            Typing::Primitive(p) => *p != Primitive::Str,
+            Typing::Pointer { .. } => true,
+            Typing::Struct(s) => s.fields.iter().all(|(_, t)| t.is_c_compatible()),
+            Typing::Enum(e) => !e.is_tagged(),
+            Typing::Array { typ, .. } => typ.is_c_compatible(),
+            _ => false,
+        }
+    }
+
     pub fn is_decimal(&self) -> bool {
         match self {
               Typing::Primitive(Primitive::F64)
@@ -621,15 +1067,20 @@ impl std::fmt::Display for Typing {
                 write!(f, "{}", fmt)
             }
             Typing::Pointer { typ, mutable } => write!(f, "*{}{}", if *mutable { "mut " } else { "" }, typ),
-            Typing::Function { ret, arguments, variadic, extrn: _ } => {
+            Typing::Function { ret, arguments, variadic, extrn } => {
                 let mut fmt = String::from("fn (");
                 for (i, typ) in arguments.iter().enumerate() {
-                    fmt.push_str(&format!("{}", typ));
+                    match typ {
+                        Typing::Slice { typ } if *variadic && !*extrn && i == arguments.len()-1 => {
+                            fmt.push_str(&format!("...{}", typ))
+                        }
+                        _ => fmt.push_str(&format!("{}", typ)),
+                    }
                     if i < arguments.len()-1 {
                         fmt.push_str(", ");
                     }
                 }
-                if *variadic {
+                if *variadic && *extrn {
                     fmt.push_str(", ...");
                 }
                 fmt.push(')');
@@ -666,6 +1117,12 @@ pub enum StatementKind {
         value: Expression,
         typing: Typing,
     },
+    // Variable declared at the top level
+    Global {
+        binding: String,
+        assignment: Expression,
+        typing: Typing,
+    },
     ReturnStatement {
         value: Option<Expression>,
         typing: Typing,
@@ -679,9 +1136,37 @@ pub enum StatementKind {
         condition: Expression,
         block: Block,
     },
+    // Runs the block for each item of an array of `amount` items of type `typing`,
+    // which is bound to `binding`. The array is evaluated once, before the first item.
+    ForStatement {
+        binding: String,
+        typing: Typing,
+        amount: usize,
+        iterable: Expression,
+        block: Block,
+    },
+    // Runs when the scope it is in is exited
+    DeferStatement {
+        block: Block,
+    },
     FnStatement(Function),
     ExternFnStatement(FunctionHead),
+    ExternVarStatement {
+        binding: String,
+        typing: Typing,
+        mutable: bool,
+    },
     StructStatement(Struct),
     EnumStatement(Enum),
+    // Type aliases are resolved during the semantic checking, this is just kept for tooling
+    TypeAlias {
+        name: String,
+        typing: Typing,
+    },
+    // Checked during the semantic checking, this is just kept for tooling
+    StaticAssert {
+        condition: Expression,
+        message: String,
+    },
     ExpressionStatement(Expression),
 }
@@ -6,13 +6,21 @@ pub enum Keyword {
     Fn,
     Struct,
     Enum,
+    Type,
     If,
     Else,
+    Match,
     While,
+    For,
     Return,
+    Defer,
+    Comptime,
+    StaticAssert,
     Extern,
+    Export,
     True,
     False,
+    Null,
     Mut,
     As,
 }
@@ -26,14 +34,22 @@ impl Keyword {
             "fn" => Some(Keyword::Fn),
             "struct" => Some(Keyword::Struct),
             "enum" => Some(Keyword::Enum),
+            "type" => Some(Keyword::Type),
             "if" => Some(Keyword::If),
             "else" => Some(Keyword::Else),
+            "match" => Some(Keyword::Match),
             "while" => Some(Keyword::While),
+            "for" => Some(Keyword::For),
             "return" => Some(Keyword::Return),
             "ret" => Some(Keyword::Return),
+            "defer" => Some(Keyword::Defer),
+            "comptime" => Some(Keyword::Comptime),
+            "static_assert" => Some(Keyword::StaticAssert),
             "extern" => Some(Keyword::Extern),
+            "export" => Some(Keyword::Export),
             "true" => Some(Keyword::True),
             "false" => Some(Keyword::False),
+            "null" => Some(Keyword::Null),
             "mut" => Some(Keyword::Mut),
             "as" => Some(Keyword::As),
             _ => None,
@@ -50,13 +66,21 @@ impl std::fmt::Display for Keyword {
             Keyword::Fn => write!(f, "fn"),
             Keyword::Struct => write!(f, "struct"),
             Keyword::Enum => write!(f, "enum"),
+            Keyword::Type => write!(f, "type"),
             Keyword::If => write!(f, "if"),
             Keyword::Else => write!(f, "else"),
+            Keyword::Match => write!(f, "match"),
             Keyword::While => write!(f, "while"),
+            Keyword::For => write!(f, "for"),
             Keyword::Return => write!(f, "return"),
+            Keyword::Defer => write!(f, "defer"),
+            Keyword::Comptime => write!(f, "comptime"),
+            Keyword::StaticAssert => write!(f, "static_assert"),
             Keyword::Extern => write!(f, "extern"),
+            Keyword::Export => write!(f, "export"),
             Keyword::True => write!(f, "true"),
             Keyword::False => write!(f, "false"),
+            Keyword::Null => write!(f, "null"),
             Keyword::Mut => write!(f, "mut"),
             Keyword::As => write!(f, "as"),
         }
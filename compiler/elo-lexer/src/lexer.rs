@@ -1,24 +1,28 @@
+use std::collections::VecDeque;
 use std::iter::Peekable;
 use std::str::Chars;
 
 use crate::inputfile::InputFile;
 use crate::keyword::Keyword;
 use crate::lexem::Lexem;
-use crate::span::FileSpan;
+use crate::span::{FileSpan, Span};
 use crate::token::{Token, StringKind};
 
 #[derive(Eq, PartialEq, Clone)]
 enum State {
     Normal,
-    String { kind: StringKind, buffer: String },
+    String { kind: StringKind, buffer: String, origin: Span },
     Interpolation { string: StringKind, depth: usize }
 }
 
+#[derive(Clone)]
 pub struct Lexer<'a> {
     pub input_file: InputFile<'a>,
     pub chars: Peekable<Chars<'a>>,
     pub span: FileSpan<'a>,
     state: State,
+    // Lexems already lexed ahead, returned before lexing more (see token_verbatim_string)
+    pending: VecDeque<Lexem>,
 }
 
 macro_rules! whitespace {
@@ -117,6 +121,56 @@ fn unescape(escape_char: char) -> char {
     }
 }
 
+// Process the escape sequences of a literal's source text.
+// The text is split where an interpolation (\() was, so there is one more part than interpolations.
+fn unescape_all(text: &str) -> Vec<String> {
+    let mut parts = vec![String::with_capacity(text.len())];
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('(') => parts.push(String::new()),
+                Some(e) => parts.last_mut().unwrap().push(unescape(e)),
+                None => parts.last_mut().unwrap().push(c),
+            }
+            continue;
+        }
+        parts.last_mut().unwrap().push(c);
+    }
+    parts
+}
+
+// Indentation policy for multi-line literals ('''...'''):
+// - A line break right after the opening delimiter is not part of the literal.
+// - If the closing delimiter is alone in its line, the whitespace before it is the
+//   indentation of the literal: it is removed from the start of every line, together
+//   with the line break before the closing delimiter.
+//   Lines indented less than that just lose all their leading whitespace.
+// - Otherwise, the text is kept as it is written.
+fn strip_indentation(text: &str) -> String {
+    let mut lines: Vec<&str> = text.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l)).collect();
+    if lines.len() > 1 && lines[0].chars().all(|c| matches!(c, whitespace!())) {
+        lines.remove(0);
+    }
+    if lines.len() > 1 && lines[lines.len() - 1].chars().all(|c| matches!(c, whitespace!())) {
+        let indentation = lines.pop().unwrap().chars().count();
+        let stripped = lines
+            .iter()
+            .map(|line| {
+                let mut chars = line.chars().peekable();
+                let mut i = 0;
+                while i < indentation && matches!(chars.peek(), Some(&whitespace!())) {
+                    chars.next();
+                    i += 1;
+                }
+                chars.collect::<String>()
+            })
+            .collect::<Vec<String>>();
+        return stripped.join("\n");
+    }
+    lines.join("\n")
+}
+
 impl<'a> Lexer<'a> {
     pub fn new(input_file: InputFile<'a>) -> Lexer<'a> {
         Lexer {
@@ -124,6 +178,7 @@ impl<'a> Lexer<'a> {
             chars: input_file.content.chars().peekable(),
             span: FileSpan::empty(input_file),
             state: State::Normal,
+            pending: VecDeque::new(),
         }
     }
 
@@ -226,6 +281,193 @@ impl<'a> Lexer<'a> {
         return Token::Identifier(s);
     }
 
+    // Check if the next characters (after skipping `skip` of them) open a raw literal,
+    // like the r' in r'foo' or the r#" in r#"foo"#, without consuming them.
+    // Returns the amount of # and the quote of the delimiter if so.
+    fn raw_string_delimiter(&self, skip: usize) -> Option<(usize, char)> {
+        let mut hashes = 0;
+        for c in self.chars.clone().skip(skip) {
+            match c {
+                '#' => hashes += 1,
+                '\'' | '"' => return Some((hashes, c)),
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    // Check if the next characters are the two remaining quotes of a ''' delimiter
+    fn is_block_quote(&self, quote: char) -> bool {
+        let mut chars = self.chars.clone();
+        chars.next() == Some(quote) && chars.next() == Some(quote)
+    }
+
+    // Span of a literal that started at `origin`.
+    // Literals spanning multiple lines are anchored at their first line, until the end of it.
+    fn literal_span(&self, origin: Span) -> Span {
+        if self.span.line == origin.line {
+            return origin.merge(self.span.into_span());
+        }
+        let line = self.input_file.content.lines().nth(origin.line - 1).unwrap_or("");
+        Span {
+            line: origin.line,
+            start: origin.start,
+            end: line.chars().count() + 1,
+        }
+    }
+
+    // Lex the expression of an interpolation (\(...)) after its backslash has been consumed,
+    // up to and including the parenthesis that closes it.
+    fn token_interpolation(&mut self) -> Vec<Lexem> {
+        let mut lexems = Vec::new();
+        let mut depth = 0;
+        while let Some(mut lexem) = self.next() {
+            match lexem.token {
+                Token::Delimiter('(') => {
+                    depth += 1;
+                    if depth == 1 {
+                        lexem.token = Token::InterpolationBegin;
+                    }
+                }
+                Token::Delimiter(')') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        lexem.token = Token::InterpolationEnd;
+                        lexems.push(lexem);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            lexems.push(lexem);
+        }
+        lexems
+    }
+
+    // Lex the rest of a raw (r'...', r#'...'#) or multi-line ('''...''') literal,
+    // after its opening delimiter has been consumed.
+    // `hashes` is the amount of # around a raw literal, or None if the literal is not raw.
+    // Interpolations in a multi-line literal give the same lexems as in a regular one: the
+    // parts of the text around them, each one followed by the lexems of an interpolation.
+    // The lexems after the first part are left in `pending`.
+    fn token_verbatim_string(&mut self, origin: Span, kind: StringKind, quote: char, hashes: Option<usize>, block: bool) -> Lexem {
+        let quotes = if block { 3 } else { 1 };
+        let closing = format!("{}{}", quote.to_string().repeat(quotes), "#".repeat(hashes.unwrap_or(0)));
+        let mut text = String::new();
+        let mut interpolations = Vec::new();
+        loop {
+            if self.chars.clone().take(closing.len()).eq(closing.chars()) {
+                for _ in 0..closing.len() {
+                    self.chars.next();
+                }
+                self.span.end += closing.len();
+                break;
+            }
+            match self.chars.next() {
+                Some('\n') => {
+                    text.push('\n');
+                    self.advance_line();
+                }
+                Some('\\') if hashes.is_none() => {
+                    // Keep the escape sequence as is, so it is not mistaken for the closing delimiter.
+                    // It gets processed after the indentation is stripped
+                    text.push('\\');
+                    self.span.end += 1;
+                    if self.chars.peek() == Some(&'(') {
+                        // Only the \( is kept in the text, to know where the interpolation goes
+                        text.push('(');
+                        interpolations.push(self.token_interpolation());
+                        continue;
+                    }
+                    if let Some(c) = self.chars.next() {
+                        text.push(c);
+                        if c == '\n' {
+                            self.advance_line();
+                        } else {
+                            self.span.end += 1;
+                        }
+                    }
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.span.end += 1;
+                }
+                None => break,
+            }
+        }
+        if block {
+            text = strip_indentation(&text);
+        }
+        let span = self.literal_span(origin);
+        if hashes.is_some() {
+            return Lexem::new(span, Token::String(kind, text));
+        }
+        let mut parts = unescape_all(&text).into_iter();
+        let first = parts.next().unwrap();
+        for (interpolation, part) in interpolations.into_iter().zip(parts) {
+            self.pending.extend(interpolation);
+            self.pending.push_back(Lexem::new(span, Token::String(kind, part)));
+        }
+        Lexem::new(span, Token::String(kind, first))
+    }
+
+    // Lex a string literal of the given kind, after its prefix (c, r, cr) has been consumed.
+    // Regular literals are handled by State::String, the others by token_verbatim_string.
+    fn token_string(&mut self, origin: Span, kind: StringKind, raw: bool) -> Option<Lexem> {
+        let mut hashes = None;
+        if raw {
+            let mut n = 0;
+            while self.chars.peek() == Some(&'#') {
+                self.chars.next();
+                n += 1;
+            }
+            self.span.end += n;
+            hashes = Some(n);
+        }
+        let quote = self.chars.next().unwrap();
+        self.span.end += 1;
+        if self.is_block_quote(quote) {
+            self.chars.next();
+            self.chars.next();
+            self.span.end += 2;
+            return Some(self.token_verbatim_string(origin, kind, quote, hashes, true));
+        }
+        if raw {
+            return Some(self.token_verbatim_string(origin, kind, quote, hashes, false));
+        }
+        self.state = State::String { kind, buffer: String::new(), origin };
+        None
+    }
+
+    // Skip a block comment after its opening /* has been consumed.
+    // Block comments can be nested, so every /* inside needs its own */.
+    fn skip_block_comment(&mut self) {
+        let mut depth = 1;
+        while let Some(c) = self.chars.next() {
+            match c {
+                '\n' => {
+                    self.advance_line();
+                    continue;
+                }
+                '/' if self.chars.peek() == Some(&'*') => {
+                    self.chars.next();
+                    self.span.end += 1;
+                    depth += 1;
+                }
+                '*' if self.chars.peek() == Some(&'/') => {
+                    self.chars.next();
+                    self.span.end += 1;
+                    depth -= 1;
+                }
+                _ => {}
+            }
+            self.span.end += 1;
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+
     fn token_op(&mut self, ch: &char) -> Token {
         self.advance_span(1);
         match self.chars.peek() {
@@ -245,22 +487,31 @@ impl<'a> Iterator for Lexer<'a> {
     type Item = Lexem;
 
     fn next(&mut self) -> Option<Lexem> {
+        if let Some(lexem) = self.pending.pop_front() {
+            return Some(lexem);
+        }
         while let Some(ch) = self.chars.next() {
-            if let State::String { buffer, kind } = &mut self.state {
+            if let State::String { buffer, kind, origin } = &mut self.state {
+                let origin = *origin;
                 match ch {
                     '\'' if matches!(kind, StringKind::Static | StringKind::C) => {
                         self.span.end += 1;
                         let buffer = std::mem::take(buffer);
                         let kind = *kind;
                         self.state = State::Normal;
-                        return Some(Lexem::new(self.span.into_span(), Token::String(kind, buffer)));
+                        return Some(Lexem::new(self.literal_span(origin), Token::String(kind, buffer)));
                     }
                     '"' if *kind == StringKind::Dynamic => {
                         self.span.end += 1;
                         let buffer = std::mem::take(buffer);
                         let kind = *kind;
                         self.state = State::Normal;
-                        return Some(Lexem::new(self.span.into_span(), Token::String(kind, buffer)));
+                        return Some(Lexem::new(self.literal_span(origin), Token::String(kind, buffer)));
+                    }
+                    '\n' => {
+                        buffer.push(ch);
+                        self.advance_line();
+                        continue;
                     }
                     '\\' => {
                         self.span.end += 1;
@@ -269,7 +520,13 @@ impl<'a> Iterator for Lexer<'a> {
                                 let buffer = std::mem::take(buffer);
                                 let kind = *kind;
                                 self.state = State::Interpolation { string: kind, depth: 0 };
-                                return Some(Lexem::new(self.span.into_span(), Token::String(kind, buffer)));
+                                return Some(Lexem::new(self.literal_span(origin), Token::String(kind, buffer)));
+                            }
+                            if *c == '\n' {
+                                buffer.push('\n');
+                                self.chars.next();
+                                self.advance_line();
+                                continue;
                             }
                             self.span.end += 1;
                             buffer.push(unescape(*c));
@@ -288,19 +545,39 @@ impl<'a> Iterator for Lexer<'a> {
             }
             return match ch {
                 'c' if self.chars.peek() == Some(&'\'') => {
+                    self.advance_span(1); // acount for 'c', the quote is consumed by token_string
+                    let origin = self.span.into_span();
+                    match self.token_string(origin, StringKind::C, false) {
+                        Some(lexem) => return Some(lexem),
+                        None => continue,
+                    }
+                }
+                'c' if self.chars.peek() == Some(&'r') && matches!(self.raw_string_delimiter(1), Some((_, '\''))) => {
                     self.chars.next();
-                    self.advance_span(2); // acount for 'c' + quote
-                    self.state = State::String { kind: StringKind::C, buffer: String::new() };
-                    continue;
+                    self.advance_span(2); // acount for 'c' + 'r'
+                    let origin = self.span.into_span();
+                    return self.token_string(origin, StringKind::C, true);
                 }
-                '\'' => {
-                    self.advance_span(1); // acount for quote
-                    self.state = State::String { kind: StringKind::Static, buffer: String::new() };
-                    continue;
+                'r' if self.raw_string_delimiter(0).is_some() => {
+                    self.advance_span(1); // acount for 'r'
+                    let origin = self.span.into_span();
+                    let kind = match self.raw_string_delimiter(0) {
+                        Some((_, '"')) => StringKind::Dynamic,
+                        _ => StringKind::Static,
+                    };
+                    return self.token_string(origin, kind, true);
                 }
-                '"' => {
+                '\'' | '"' => {
                     self.advance_span(1); // acount for quote
-                    self.state = State::String { kind: StringKind::Dynamic, buffer: String::new() };
+                    let origin = self.span.into_span();
+                    let kind = if ch == '"' { StringKind::Dynamic } else { StringKind::Static };
+                    if self.is_block_quote(ch) {
+                        self.chars.next();
+                        self.chars.next();
+                        self.span.end += 2;
+                        return Some(self.token_verbatim_string(origin, kind, ch, None, true));
+                    }
+                    self.state = State::String { kind, buffer: String::new(), origin };
                     continue;
                 }
                 '`' => {
@@ -330,7 +607,20 @@ impl<'a> Iterator for Lexer<'a> {
                     return Some(Lexem::new(self.span.into_span(), Token::Character(buf)));
                 }
                 '/' if self.state == State::Normal && self.chars.peek() == Some(&'/') => {
-                    let _ = self.consume_while(Some(&ch), |c| c != '\n');
+                    let (comment, _) = self.consume_while(Some(&ch), |c| c != '\n');
+                    // Doc comments start with exactly three slashes (//// is a regular comment)
+                    if let Some(doc) = comment.strip_prefix("///") && !doc.starts_with('/') {
+                        self.advance_span(comment.chars().count());
+                        let doc = doc.strip_prefix(' ').unwrap_or(doc);
+                        let doc = doc.strip_suffix('\r').unwrap_or(doc);
+                        return Some(Lexem::new(self.span.into_span(), Token::DocComment(doc.to_string())));
+                    }
+                    continue;
+                }
+                '/' if self.state == State::Normal && self.chars.peek() == Some(&'*') => {
+                    self.chars.next();
+                    self.advance_span(2);
+                    self.skip_block_comment();
                     continue;
                 }
                 '\n' => {
@@ -362,7 +652,7 @@ impl<'a> Iterator for Lexer<'a> {
                             return Some(Lexem::new(self.span.into_span(), Token::Variadic));
                         }
                         self.advance_span(2);
-                        Some(Lexem::new(self.span.into_span(), Token::Delimiter(ch)))
+                        Some(Lexem::new(self.span.into_span(), Token::DoubleDot))
                     } else {
                         self.advance_span(1);
                         if ch == '(' {
@@ -379,7 +669,7 @@ impl<'a> Iterator for Lexer<'a> {
                                 // it always starts with 1 anyway because of the first ( after backslash.
                                 // But just in case, let's put <=...
                                 if *depth <= 1 { // end
-                                    self.state = State::String { kind: *string, buffer: String::new() };
+                                    self.state = State::String { kind: *string, buffer: String::new(), origin: self.span.into_span() };
                                     return Some(Lexem::new(self.span.into_span(), Token::InterpolationEnd));
                                 }
                                 *depth -= 1;
@@ -394,11 +684,12 @@ impl<'a> Iterator for Lexer<'a> {
                 }
             };
         }
-        if let State::String { kind, buffer } = &mut self.state {
+        if let State::String { kind, buffer, origin } = &mut self.state {
             let buffer = std::mem::take(buffer);
             let kind = *kind;
+            let origin = *origin;
             self.state = State::Normal;
-            return Some(Lexem::new(self.span.into_span(), Token::String(kind, buffer)));
+            return Some(Lexem::new(self.literal_span(origin), Token::String(kind, buffer)));
         }
         None
     }
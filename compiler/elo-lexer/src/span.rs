@@ -1,8 +1,10 @@
 // TODO: Support for multiline spans. This can cause problems later in the parser....
+// For now, tokens spanning multiple lines (like multi-line string literals) are anchored
+// at the line they start in, and span until the end of it.
 
 use crate::inputfile::InputFile;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FileSpan<'a> {
     pub input_file: InputFile<'a>,
     pub line: usize,
@@ -24,7 +26,7 @@ impl<'a> FileSpan<'a> {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Span {
     pub line: usize,
     pub start: usize,
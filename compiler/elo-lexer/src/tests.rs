@@ -101,6 +101,22 @@ fn test_dot() {
     );
 }
 
+#[test]
+fn test_double_dot() {
+    let source_text = "..a ... .";
+    let lx = Lexer::new(InputFile::new("test", source_text));
+    let xs = lx.map(|lx| lx.token).collect::<Vec<Token>>();
+    assert_eq!(
+        xs,
+        vec![
+            Token::DoubleDot,
+            Token::Identifier(String::from("a")),
+            Token::Variadic,
+            Token::Delimiter('.'),
+        ]
+    );
+}
+
 #[test]
 fn test_whitespaces() {
     let source_text = "\t\n\x0C\x0B69 \x0C 420 foo \x0B bar     \t\n\x0C\x0B";
@@ -118,3 +134,112 @@ fn test_whitespaces() {
         ]
     );
 }
+
+#[test]
+fn test_raw_strings() {
+    let source_text = r###"r'C:\path\n' r"a\(b)" cr'\0' r#'it's "quoted"'# raw"###;
+    let lx = Lexer::new(InputFile::new("test", source_text));
+    let xs = lx.map(|lx| lx.token).collect::<Vec<Token>>();
+    assert_eq!(
+        xs,
+        vec![
+            Token::String(StringKind::Static, String::from("C:\\path\\n")),
+            Token::String(StringKind::Dynamic, String::from("a\\(b)")),
+            Token::String(StringKind::C, String::from("\\0")),
+            Token::String(StringKind::Static, String::from("it's \"quoted\"")),
+            Token::Identifier(String::from("raw")),
+        ]
+    );
+}
+
+#[test]
+fn test_multiline_strings() {
+    let source_text = "'''\n    foo\n      bar\\t\n    ''' c'''\n  x\n''' r'''a\\n\nb''' 'a\nb'";
+    let lx = Lexer::new(InputFile::new("test", source_text));
+    let xs = lx.map(|lx| lx.token).collect::<Vec<Token>>();
+    assert_eq!(
+        xs,
+        vec![
+            Token::String(StringKind::Static, String::from("foo\n  bar\t")),
+            Token::String(StringKind::C, String::from("  x")),
+            Token::String(StringKind::Static, String::from("a\\n\nb")),
+            Token::String(StringKind::Static, String::from("a\nb")),
+        ]
+    );
+}
+
+#[test]
+fn test_multiline_string_interpolation() {
+    let source_text = "'''\n  a \\(f(x)) b\n  \\(y)\n  ''' z";
+    let lx = Lexer::new(InputFile::new("test", source_text));
+    let xs = lx.map(|lx| lx.token).collect::<Vec<Token>>();
+    assert_eq!(
+        xs,
+        vec![
+            Token::String(StringKind::Static, String::from("a ")),
+            Token::InterpolationBegin,
+            Token::Identifier(String::from("f")),
+            Token::Delimiter('('),
+            Token::Identifier(String::from("x")),
+            Token::Delimiter(')'),
+            Token::InterpolationEnd,
+            Token::String(StringKind::Static, String::from(" b\n")),
+            Token::InterpolationBegin,
+            Token::Identifier(String::from("y")),
+            Token::InterpolationEnd,
+            Token::String(StringKind::Static, String::from("")),
+            Token::Identifier(String::from("z")),
+        ]
+    );
+}
+
+#[test]
+fn test_multiline_string_spans() {
+    let source_text = "x '''\n  foo\n  ''' y\n'a\nb' z";
+    let lx = Lexer::new(InputFile::new("test", source_text));
+    let xs = lx.map(|lx| (lx.span.line, lx.span.start, lx.token)).collect::<Vec<_>>();
+    assert_eq!(
+        xs,
+        vec![
+            (1, 1, Token::Identifier(String::from("x"))),
+            (1, 3, Token::String(StringKind::Static, String::from("foo"))),
+            (3, 7, Token::Identifier(String::from("y"))),
+            (4, 1, Token::Newline),
+            (4, 1, Token::String(StringKind::Static, String::from("a\nb"))),
+            (5, 4, Token::Identifier(String::from("z"))),
+        ]
+    );
+}
+
+#[test]
+fn test_block_comments() {
+    let source_text = "1 /* one /* nested\n */ still a comment */ 2 /**/ 3";
+    let lx = Lexer::new(InputFile::new("test", source_text));
+    let xs = lx.map(|lx| (lx.span.line, lx.span.start, lx.token)).collect::<Vec<_>>();
+    assert_eq!(
+        xs,
+        vec![
+            (1, 1, Token::Integer(String::from("1"), 10)),
+            (2, 24, Token::Integer(String::from("2"), 10)),
+            (2, 31, Token::Integer(String::from("3"), 10)),
+        ]
+    );
+}
+
+#[test]
+fn test_doc_comments() {
+    let source_text = "/// Adds numbers\n///\n//// not a doc comment\nfn";
+    let lx = Lexer::new(InputFile::new("test", source_text));
+    let xs = lx.map(|lx| lx.token).collect::<Vec<Token>>();
+    assert_eq!(
+        xs,
+        vec![
+            Token::DocComment(String::from("Adds numbers")),
+            Token::Newline,
+            Token::DocComment(String::new()),
+            Token::Newline,
+            Token::Newline,
+            Token::Keyword(crate::keyword::Keyword::Fn),
+        ]
+    );
+}
@@ -21,6 +21,8 @@ pub enum Token {
     String(StringKind, String),     // 'foo', "bar", 'hello'
     Character(String),    // `a`, `b`, `\n`
     Variadic,               // ...: Special token for variadic functions for C FFI
+    DoubleDot,              // ..: Struct update syntax
+    DocComment(String),     // /// foo: The text of a doc comment, without the slashes
     Unknown(char),          // Any other character
     InterpolationBegin,
     InterpolationEnd,
@@ -41,6 +43,8 @@ impl Token {
             Token::String(_, s) => format!("{s}"),
             Token::Character(s) => format!("{s}"),
             Token::Variadic => format!("..."),
+            Token::DoubleDot => format!(".."),
+            Token::DocComment(s) => format!("///{s}"),
             Token::Unknown(c) => format!("{c}"),
             Token::InterpolationBegin => format!("\\("),
             Token::InterpolationEnd => format!(")"),
@@ -77,6 +81,8 @@ impl std::fmt::Display for Token {
             }
             Token::Character(s) => write!(f, "`{}`", s),
             Token::Variadic => write!(f, "..."),
+            Token::DoubleDot => write!(f, ".."),
+            Token::DocComment(_) => write!(f, "doc comment"),
             Token::Unknown(c) => write!(f, "{}", c),
         }
     }
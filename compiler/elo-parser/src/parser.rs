@@ -1,4 +1,7 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::iter::Peekable;
+use std::rc::Rc;
 
 use elo_error::parseerror::*;
 
@@ -14,18 +17,54 @@ use elo_ir::ast::*;
 
 pub const EOF: &str = "EOF";
 
+// Doc comments of the lexems they precede, by the span of the lexem
+type Docs = Rc<RefCell<HashMap<Span, String>>>;
+
+// Lexems without the doc comments, so these can be written anywhere a regular comment can.
+// Each run of doc comment lines is kept for the lexem after it, which may be an item to document.
+#[derive(Clone)]
+pub struct DocLexer<'a> {
+    lexer: Lexer<'a>,
+    lines: Vec<String>,
+    docs: Docs,
+}
+
+impl<'a> Iterator for DocLexer<'a> {
+    type Item = Lexem;
+
+    fn next(&mut self) -> Option<Lexem> {
+        loop {
+            let lexem = self.lexer.next()?;
+            match lexem.token {
+                Token::DocComment(line) => self.lines.push(line),
+                Token::Newline => return Some(lexem),
+                _ => {
+                    if !self.lines.is_empty() {
+                        self.docs.borrow_mut().insert(lexem.span, self.lines.join("\n"));
+                        self.lines.clear();
+                    }
+                    return Some(lexem);
+                }
+            }
+        }
+    }
+}
+
 pub struct Parser<'a> {
     pub inputfile: InputFile<'a>,
-    pub lexer: Peekable<Lexer<'a>>,
+    pub lexer: Peekable<DocLexer<'a>>,
+    docs: Docs,
     current_span: Span,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(lexer: Lexer<'a>) -> Parser<'a> {
         let inputfile = lexer.span.input_file;
+        let docs = Docs::default();
         Parser {
-            lexer: lexer.peekable(),
+            lexer: DocLexer { lexer, lines: Vec::new(), docs: docs.clone() }.peekable(),
             inputfile,
+            docs,
             current_span: Span::end_of_file(inputfile),
         }
     }
@@ -78,23 +117,19 @@ impl<'a> Parser<'a> {
                 Token::Keyword(Keyword::Fn) => {
                     self.expect_token(Token::Delimiter('('))?;
                     let mut types = Vec::new();
-                    if let Some(_) = self.test_token(&Token::Delimiter(')'), false) {
-                        return Ok(Type {
-                            span: lexem.span.merge(self.current_span),
-                            typing: Typing::Function { args: vec![], ret: None }
-                        });
-                    }
-                    types.push(self.parse_type()?);
-                    while let Some(Lexem {
-                        token: Token::Delimiter(','),
-                        ..
-                    }) = self.lexer.peek()
-                    {
-                        self.next();
-                        let t = self.parse_type()?;
-                        types.push(t);
+                    if let None = self.test_token(&Token::Delimiter(')'), false) {
+                        types.push(self.parse_type()?);
+                        while let Some(Lexem {
+                            token: Token::Delimiter(','),
+                            ..
+                        }) = self.lexer.peek()
+                        {
+                            self.next();
+                            let t = self.parse_type()?;
+                            types.push(t);
+                        }
+                        self.expect_token(Token::Delimiter(')'))?;
                     }
-                    self.expect_token(Token::Delimiter(')'))?;
                     let mut ret = None;
                     if let Some(_) = self.test_token(&Token::Delimiter(':'), false) {
                         ret = Some(self.parse_type()?);
@@ -140,28 +175,15 @@ impl<'a> Parser<'a> {
                 Token::Delimiter('{') => {
                     let typ = self.parse_type()?;
                     if let Some(_) = self.test_token(&Token::Delimiter(';'), false) {
-                        match self.parse_number()?.data {
-                            ExpressionData::IntegerLiteral { value: x } => {
-                                self.expect_token(Token::Delimiter('}'))?;
-                                return Ok(Type {
-                                    span: lexem.span.merge(self.current_span),
-                                    typing: Typing::Array {
-                                        typ: Box::new(typ),
-                                        amount: x as usize,
-                                    },
-                                });
-                            }
-                            ExpressionData::FloatLiteral { .. } => {
-                                return Err(ParseError {
-                                    span: lexem.span,
-                                    case: ParseErrorCase::UnexpectedToken {
-                                        got: "float literal".to_string(),
-                                        expected: "integer literal".to_string(),
-                                    },
-                                });
-                            }
-                            _ => unreachable!(),
-                        }
+                        let amount = self.parse_expr(0, false)?;
+                        self.expect_token(Token::Delimiter('}'))?;
+                        return Ok(Type {
+                            span: lexem.span.merge(self.current_span),
+                            typing: Typing::Array {
+                                typ: Box::new(typ),
+                                amount: Box::new(amount),
+                            },
+                        });
                     }
                     self.expect_token(Token::Delimiter('}'))?;
                     return Ok(Type {
@@ -214,19 +236,72 @@ impl<'a> Parser<'a> {
         });
     }
 
-    fn parse_typed_field(&mut self) -> Result<TypedField, ParseError> {
+    // Skip the newlines before an item, and take the doc comments written before it.
+    // Their lines are joined together as the documentation of the item.
+    fn parse_doc_comments(&mut self) -> Option<String> {
+        while let Some(Lexem { token: Token::Newline, .. }) = self.lexer.peek() {
+            self.next();
+        }
+        let span = self.lexer.peek()?.span;
+        self.docs.borrow_mut().remove(&span)
+    }
+
+    // `defaults` tells if the field can have a default value, like in `port: int = 8080`
+    // Struct fields (with `defaults`) can also be annotated, like in `packed align(2) count: u32`
+    fn parse_typed_field(&mut self, defaults: bool) -> Result<TypedField, ParseError> {
+        let doc = self.parse_doc_comments();
+        let mut packed = false;
+        let mut align = None;
+        // The annotations are not keywords, so a field can still be named like them
+        while defaults && let Some(Token::Identifier(name)) = self.lexer.peek().map(|lexem| lexem.token.clone()) {
+            if name == "packed" && self.second_token() != Some(Token::Delimiter(':')) {
+                self.next();
+                packed = true;
+            } else if name == "align" && self.second_token() == Some(Token::Delimiter('(')) {
+                self.next();
+                align = Some(self.parse_align()?);
+            } else {
+                break;
+            }
+        }
         let ident = self.expect_identifier()?;
         self.expect_token(Token::Delimiter(':'))?;
         let typ = self.parse_type()?;
+        let mut default = None;
+        if defaults && self.test_token(&Token::Op('=', None), false).is_some() {
+            default = Some(self.parse_expr(0, true)?);
+        }
         return Ok(TypedField {
             name: ident,
             typing: typ,
+            default,
+            doc,
+            packed,
+            align,
         });
     }
 
+    // (expr) after the align keyword
+    fn parse_align(&mut self) -> Result<Expression, ParseError> {
+        self.expect_token(Token::Delimiter('('))?;
+        let align = self.parse_expr(0, true)?;
+        self.expect_token(Token::Delimiter(')'))?;
+        Ok(align)
+    }
+
+    // identifier[: expr]
+    // Without the value, it is a shorthand for `identifier: identifier`
     fn parse_field(&mut self) -> Result<Field, ParseError> {
         let ident = self.expect_identifier()?;
-        self.expect_token(Token::Delimiter(':'))?;
+        if let None = self.test_token(&Token::Delimiter(':'), true) {
+            return Ok(Field {
+                name: ident.clone(),
+                value: Expression {
+                    span: self.current_span,
+                    data: ExpressionData::Identifier { name: ident },
+                },
+            });
+        }
         let value = self.parse_expr(0, true)?;
         return Ok(Field {
             name: ident,
@@ -234,13 +309,13 @@ impl<'a> Parser<'a> {
         });
     }
 
-    // identifier: type[, identifier: type]*,?
-    fn parse_typed_fields(&mut self, termination: Token) -> Result<Vec<TypedField>, ParseError> {
+    // identifier: type[ = expr][, identifier: type[ = expr]]*,?
+    fn parse_typed_fields(&mut self, termination: Token, defaults: bool) -> Result<Vec<TypedField>, ParseError> {
         let mut fields = Vec::new();
         if let Some(_) = self.seek_token(&termination, true) {
             return Ok(fields);
         }
-        let x = self.parse_typed_field()?;
+        let x = self.parse_typed_field(defaults)?;
         fields.push(x);
 
         while let Some(Lexem {
@@ -252,12 +327,44 @@ impl<'a> Parser<'a> {
             if let Some(_) = self.seek_token(&termination, true) {
                 break;
             }
-            let f = self.parse_typed_field()?;
+            let f = self.parse_typed_field(defaults)?;
             fields.push(f);
         }
         Ok(fields)
     }
 
+    // identifier: type[ = expr][, identifier: type[ = expr]]*[, identifier: ...type],?
+    // Arguments of an Elo function. The last one may be a variadic tail, which the function sees as a slice.
+    //                                                     ---- This field means if the last argument is a variadic tail.
+    fn parse_fn_args(&mut self) -> Result<(Vec<TypedField>, bool), ParseError> {
+        let mut fields = Vec::new();
+        loop {
+            if let Some(_) = self.seek_token(&Token::Delimiter(')'), true) {
+                break;
+            }
+            let doc = self.parse_doc_comments();
+            let name = self.expect_identifier()?;
+            self.expect_token(Token::Delimiter(':'))?;
+            if let Some(_) = self.test_token(&Token::Variadic, false) {
+                let typing = self.parse_type()?;
+                fields.push(TypedField { name, typing, default: None, doc, packed: false, align: None });
+                // The variadic tail must be the last argument
+                self.test_token(&Token::Delimiter(','), true);
+                return Ok((fields, true));
+            }
+            let typing = self.parse_type()?;
+            let mut default = None;
+            if self.test_token(&Token::Op('=', None), false).is_some() {
+                default = Some(self.parse_expr(0, true)?);
+            }
+            fields.push(TypedField { name, typing, default, doc, packed: false, align: None });
+            if let None = self.test_token(&Token::Delimiter(','), true) {
+                break;
+            }
+        }
+        Ok((fields, false))
+    }
+
     // Parse function declaration arguments, this is made to include parsing of the ... token for
     // variadic functions, in compatibility with C FFI.
     //                                                           ---- This field means if the arguments are variadic or not.
@@ -269,7 +376,7 @@ impl<'a> Parser<'a> {
             variadic = true;
             return Ok((fields, variadic));
         }
-        if let Ok(first) = self.parse_typed_field() {
+        if let Ok(first) = self.parse_typed_field(false) {
             fields.push(first);
         }
         while let Some(Lexem {
@@ -287,31 +394,32 @@ impl<'a> Parser<'a> {
                 self.next();
                 break;
             }
-            let f = self.parse_typed_field()?;
+            let f = self.parse_typed_field(false)?;
             fields.push(f);
         }
         Ok((fields, variadic))
     }
 
-    // identifier: expr[, identifier: expr]*,?
-    fn parse_fields(&mut self, termination: Token) -> Result<Vec<Field>, ParseError> {
+    // field[, field]*[, ..expr],?
+    // Returns the fields and the base expression of the update syntax (..expr), if any.
+    fn parse_fields(&mut self, termination: Token) -> Result<(Vec<Field>, Option<Expression>), ParseError> {
         let mut fields = Vec::new();
-        if let Ok(first) = self.parse_field() {
-            fields.push(first);
-        }
-        while let Some(Lexem {
-            token: Token::Delimiter(','),
-            ..
-        }) = self.lexer.peek()
-        {
-            self.next();
+        loop {
             if let Some(_) = self.seek_token(&termination, true) {
                 break;
             }
+            if let Some(_) = self.test_token(&Token::DoubleDot, true) {
+                // The base must be the last thing in the struct literal
+                let base = self.parse_expr(0, true)?;
+                return Ok((fields, Some(base)));
+            }
             let f = self.parse_field()?;
             fields.push(f);
+            if let None = self.test_token(&Token::Delimiter(','), true) {
+                break;
+            }
         }
-        Ok(fields)
+        Ok((fields, None))
     }
 
     // expr[, expr]*[,]?
@@ -341,11 +449,56 @@ impl<'a> Parser<'a> {
         Ok(fields)
     }
 
-    // identifier[, identifier]*,?
-    fn parse_enum_variants(&mut self, termination: Token) -> Result<Vec<String>, ParseError> {
+    // expr[, expr]*[, ...expr][, identifier: expr]*,?
+    // Returns the positional arguments, the spread slice (...expr) and the named arguments.
+    // The spread comes after the positional arguments and the named ones come last.
+    fn parse_call_arguments(&mut self, termination: Token) -> Result<(Vec<Expression>, Option<Expression>, Vec<Field>), ParseError> {
+        let mut arguments = Vec::new();
+        let mut spread = None;
+        let mut named = Vec::new();
+        loop {
+            if let Some(_) = self.seek_token(&termination, true) {
+                break;
+            }
+            if spread.is_none() && named.is_empty() && self.test_token(&Token::Variadic, true).is_some() {
+                spread = Some(self.parse_expr(0, true)?);
+                if let None = self.test_token(&Token::Delimiter(','), true) {
+                    break;
+                }
+                continue;
+            }
+            let expr = self.parse_expr(0, true)?;
+            if let ExpressionData::Identifier { name } = &expr.data
+                && self.test_token(&Token::Delimiter(':'), true).is_some() {
+                let value = self.parse_expr(0, true)?;
+                named.push(Field {
+                    name: name.clone(),
+                    value,
+                });
+            } else if !named.is_empty() || spread.is_some() {
+                return Err(ParseError {
+                    span: expr.span,
+                    case: ParseErrorCase::UnexpectedToken {
+                        got: format!("positional argument"),
+                        expected: format!("named argument"),
+                    },
+                });
+            } else {
+                arguments.push(expr);
+            }
+            if let None = self.test_token(&Token::Delimiter(','), true) {
+                break;
+            }
+        }
+        Ok((arguments, spread, named))
+    }
+
+    // variant[, variant]*,?
+    fn parse_enum_variants(&mut self, termination: Token) -> Result<Vec<EnumVariant>, ParseError> {
         let mut fields = Vec::new();
+        let doc = self.parse_doc_comments();
         if let Ok(first) = self.expect_identifier() {
-            fields.push(first);
+            fields.push(self.parse_enum_variant(first, doc)?);
         }
         while let Some(Lexem {
             token: Token::Delimiter(','),
@@ -356,12 +509,38 @@ impl<'a> Parser<'a> {
             if let Some(_) = self.seek_token(&termination, true) {
                 break;
             }
+            let doc = self.parse_doc_comments();
             let f = self.expect_identifier()?;
-            fields.push(f);
+            fields.push(self.parse_enum_variant(f, doc)?);
         }
         Ok(fields)
     }
 
+    // identifier[(type[, type]*) | { fields }][ = expr]
+    fn parse_enum_variant(&mut self, name: String, doc: Option<String>) -> Result<EnumVariant, ParseError> {
+        let mut payload = None;
+        if let Some(_) = self.test_token(&Token::Delimiter('('), false) {
+            let mut types = Vec::new();
+            while self.seek_token(&Token::Delimiter(')'), true).is_none() {
+                types.push(self.parse_type()?);
+                if let None = self.test_token(&Token::Delimiter(','), true) {
+                    break;
+                }
+            }
+            self.expect_token(Token::Delimiter(')'))?;
+            payload = Some(Payload::Tuple(types));
+        } else if let Some(_) = self.test_token(&Token::Delimiter('{'), false) {
+            let fields = self.parse_typed_fields(Token::Delimiter('}'), false)?;
+            self.expect_token(Token::Delimiter('}'))?;
+            payload = Some(Payload::Struct(fields));
+        }
+        let mut value = None;
+        if let Some(_) = self.test_token(&Token::Op('=', None), false) {
+            value = Some(self.parse_expr(0, true)?);
+        }
+        Ok(EnumVariant { name, value, payload, doc })
+    }
+
     fn parse_number(&mut self) -> Result<Expression, ParseError> {
         let token = self.expect_numeric()?;
         match token {
@@ -590,6 +769,31 @@ impl<'a> Parser<'a> {
                 Token::Identifier(_) => {
                     let i = self.parse_identifier()?;
 
+                    // Intrinsics that take a type instead of a value
+                    if let ExpressionData::Identifier { name } = &i.data
+                        && matches!(name.as_str(), "sizeof" | "alignof" | "offsetof")
+                        && let Some(_) = self.test_token(&Token::Delimiter('('), false)
+                    {
+                        let typ = self.parse_type()?;
+                        let mut arguments = vec![Expression {
+                            span: typ.span,
+                            data: ExpressionData::TypeArgument { typ },
+                        }];
+                        while let Some(_) = self.test_token(&Token::Delimiter(','), true) {
+                            arguments.push(self.parse_expr(0, true)?);
+                        }
+                        self.expect_token(Token::Delimiter(')'))?;
+                        return Ok(Expression {
+                            span: i.span.merge(self.current_span),
+                            data: ExpressionData::FunctionCall {
+                                function: Box::new(i),
+                                arguments,
+                                spread: None,
+                                named: Vec::new(),
+                            },
+                        });
+                    }
+
                     if let Some(Lexem {
                         token: Token::Delimiter('{'),
                         ..
@@ -600,12 +804,12 @@ impl<'a> Parser<'a> {
                             // In case of not allowed, it will just not parse it at all
                             self.next();
                             let span = i.span.merge(self.current_span);
-                            let fields = self.parse_fields(Token::Delimiter('}'))?;
+                            let (fields, base) = self.parse_fields(Token::Delimiter('}'))?;
                             self.expect_token(Token::Delimiter('}'))?;
                             if let ExpressionData::Identifier { name } = i.data {
                                 return Ok(Expression {
                                     span: span,
-                                    data: ExpressionData::StructInit { name: name, fields },
+                                    data: ExpressionData::StructInit { name: name, fields, base: base.map(Box::new) },
                                 });
                             } else {
                                 unreachable!()
@@ -672,6 +876,28 @@ impl<'a> Parser<'a> {
                         data,
                     });
                 }
+                Token::Keyword(Keyword::Fn) => {
+                    self.next();
+                    return self.parse_closure();
+                }
+                Token::Keyword(Keyword::If) => {
+                    self.next();
+                    return self.parse_if_expr();
+                }
+                Token::Keyword(Keyword::Match) => {
+                    self.next();
+                    return self.parse_match_expr();
+                }
+                Token::Keyword(Keyword::Comptime) => {
+                    let start = lexem.span;
+                    self.next();
+                    // Binds like a unary operator: comptime f(x) + 1 is (comptime f(x)) + 1
+                    let expr = self.parse_expr(UnaryOperation::Not.precedence(), true)?;
+                    return Ok(Expression {
+                        span: start.merge(self.current_span),
+                        data: ExpressionData::Comptime { expr: Box::new(expr) },
+                    });
+                }
                 Token::Keyword(Keyword::True) => {
                     self.next();
                     return Ok(Expression {
@@ -686,6 +912,13 @@ impl<'a> Parser<'a> {
                         data: ExpressionData::BooleanLiteral { value: false },
                     });
                 }
+                Token::Keyword(Keyword::Null) => {
+                    self.next();
+                    return Ok(Expression {
+                        span: self.current_span,
+                        data: ExpressionData::NullLiteral,
+                    });
+                }
                 token @ Token::Op(..) => {
                     let op = UnaryOperation::from_token(token);
                     if let Some(unop) = op {
@@ -784,13 +1017,15 @@ impl<'a> Parser<'a> {
                     },
                 };
             } else if let Some(_) = self.test_token(&Token::Delimiter('('), false) { // Call
-                let args = self.parse_expression_list(Token::Delimiter(')'))?;
+                let (args, spread, named) = self.parse_call_arguments(Token::Delimiter(')'))?;
                 self.expect_token(Token::Delimiter(')'))?;
                 left = Expression {
                     span: left.span.merge(self.current_span),
                     data: ExpressionData::FunctionCall {
                         function: Box::new(left),
                         arguments: args,
+                        spread: spread.map(Box::new),
+                        named,
                     },
                 };
             } else if let Some(_) = self.test_token(&Token::Delimiter('['), false) { // Subscript
@@ -836,7 +1071,7 @@ impl<'a> Parser<'a> {
         }))
     }
 
-    fn parse_const_stmt(&mut self) -> Result<Statement, ParseError> {
+    fn parse_const_stmt(&mut self, doc: Option<String>) -> Result<Statement, ParseError> {
         let ident = self.expect_identifier()?;
         let _ = self.expect_token(Token::Delimiter(':'))?;
         let typing = self.parse_type()?;
@@ -848,6 +1083,7 @@ impl<'a> Parser<'a> {
             binding: ident,
             assignment: expr,
             typing,
+            doc,
         }))
     }
 
@@ -889,10 +1125,10 @@ impl<'a> Parser<'a> {
         Ok(block)
     }
 
-    fn parse_fn_stmt(&mut self) -> Result<Statement, ParseError> {
+    fn parse_fn_stmt(&mut self, doc: Option<String>) -> Result<Statement, ParseError> {
         let name = self.expect_identifier()?;
         self.expect_token(Token::Delimiter('('))?;
-        let arguments = self.parse_typed_fields(Token::Delimiter(')'))?;
+        let (arguments, variadic) = self.parse_fn_args()?;
         self.expect_token(Token::Delimiter(')'))?;
         let mut typ = None;
         if let Some(_) = self.test_token(&Token::Delimiter(':'), false) {
@@ -905,9 +1141,77 @@ impl<'a> Parser<'a> {
             block,
             ret: typ,
             arguments,
+            variadic,
+            doc,
+            export: None,
         }))
     }
 
+    // export fn name(arguments)[: type] { block }
+    // export('symbol') fn name(arguments)[: type] { block }
+    fn parse_export_stmt(&mut self, doc: Option<String>) -> Result<Statement, ParseError> {
+        let mut symbol = None;
+        if let Some(_) = self.test_token(&Token::Delimiter('('), false) {
+            symbol = Some(self.expect_str_literal()?);
+            self.expect_token(Token::Delimiter(')'))?;
+        }
+        self.expect_token(Token::Keyword(Keyword::Fn))?;
+        let Statement::FnStatement(mut stmt) = self.parse_fn_stmt(doc)? else {
+            unreachable!("parsed a function that is not a function statement")
+        };
+        stmt.export = Some(symbol.unwrap_or_else(|| stmt.name.clone()));
+        Ok(Statement::FnStatement(stmt))
+    }
+
+    // fn (arguments)[: type] => expr
+    // fn (arguments)[: type] { block }
+    // With =>, the expression is the returned value if the closure returns something.
+    fn parse_closure(&mut self) -> Result<Expression, ParseError> {
+        let start = self.current_span;
+        self.expect_token(Token::Delimiter('('))?;
+        let arguments = self.parse_typed_fields(Token::Delimiter(')'), false)?;
+        self.expect_token(Token::Delimiter(')'))?;
+        let mut ret = None;
+        if let Some(_) = self.test_token(&Token::Delimiter(':'), false) {
+            ret = Some(self.parse_type()?);
+        }
+        let block;
+        if let Some(_) = self.test_token(&Token::Op('=', Some('>')), true) {
+            let expr = self.parse_expr(0, true)?;
+            let span = expr.span;
+            let stmt = if ret.is_some() {
+                Statement::ReturnStatement(ReturnStatement { expr: Some(expr) })
+            } else {
+                Statement::ExpressionStatement(expr)
+            };
+            block = Block { content: vec![Node { span, stmt }] };
+        } else {
+            self.expect_token(Token::Delimiter('{'))?;
+            block = self.parse_stmts()?;
+            self.expect_token(Token::Delimiter('}'))?;
+        }
+        return Ok(Expression {
+            span: start.merge(self.current_span),
+            data: ExpressionData::Closure { arguments, ret, block },
+        });
+    }
+
+    // extern fn name(arguments)[: type]
+    // extern var name: type
+    // extern let name: type
+    fn parse_extern_stmt(&mut self) -> Result<Statement, ParseError> {
+        if let Some(Lexem { token: Token::Keyword(k @ (Keyword::Var | Keyword::Let)), .. }) = self.lexer.peek() {
+            let mutable = *k == Keyword::Var;
+            self.next();
+            let name = self.expect_identifier()?;
+            self.expect_token(Token::Delimiter(':'))?;
+            let typing = self.parse_type()?;
+            self.expect_end()?;
+            return Ok(Statement::ExternVarStatement(ExternVarStatement { name, typing, mutable }));
+        }
+        self.parse_extern_fn_stmt()
+    }
+
     fn parse_extern_fn_stmt(&mut self) -> Result<Statement, ParseError> {
         self.expect_token(Token::Keyword(Keyword::Fn))?;
         let name = self.expect_identifier()?;
@@ -927,22 +1231,68 @@ impl<'a> Parser<'a> {
         }))
     }
 
-    fn parse_struct_stmt(&mut self) -> Result<Statement, ParseError> {
+    // struct name { fields }
+    // union name { fields }
+    fn parse_struct_stmt(&mut self, doc: Option<String>, union: bool) -> Result<StructStatement, ParseError> {
         let name = self.expect_identifier()?;
         self.expect_token(Token::Delimiter('{'))?;
-        let fields = self.parse_typed_fields(Token::Delimiter('}'))?;
+        let fields = self.parse_typed_fields(Token::Delimiter('}'), true)?;
         self.expect_token(Token::Delimiter('}'))?;
         self.expect_end()?;
-        Ok(Statement::StructStatement(StructStatement { name, fields }))
+        Ok(StructStatement { name, fields, doc, union, packed: false, align: None })
+    }
+
+    // packed struct name { fields }
+    // align(expr) union name { fields }
+    // The annotations can be combined, like in `packed align(4) struct`.
+    // They and `union` are only keywords here (see layout_ahead), so they can still be used as names.
+    fn parse_layout_stmt(&mut self, doc: Option<String>) -> Result<Statement, ParseError> {
+        let mut packed = false;
+        let mut align = None;
+        let union = loop {
+            match self.next() {
+                Some(Lexem { token: Token::Identifier(name), .. }) if name == "packed" => packed = true,
+                Some(Lexem { token: Token::Identifier(name), .. }) if name == "align" => align = Some(self.parse_align()?),
+                Some(Lexem { token: Token::Identifier(name), .. }) if name == "union" => break true,
+                Some(Lexem { token: Token::Keyword(Keyword::Struct), .. }) => break false,
+                Some(Lexem { token, span }) => {
+                    return Err(ParseError {
+                        span,
+                        case: ParseErrorCase::UnexpectedToken { got: token.to_string(), expected: "struct or union".to_string() },
+                    });
+                }
+                None => {
+                    return Err(ParseError {
+                        span: self.current_span,
+                        case: ParseErrorCase::UnexpectedToken { got: EOF.to_string(), expected: "struct or union".to_string() },
+                    });
+                }
+            }
+        };
+        let stmt = self.parse_struct_stmt(doc, union)?;
+        Ok(Statement::StructStatement(StructStatement { packed, align, ..stmt }))
     }
 
-    fn parse_enum_stmt(&mut self) -> Result<Statement, ParseError> {
+    // enum name[: type] { variants }
+    fn parse_enum_stmt(&mut self, doc: Option<String>) -> Result<Statement, ParseError> {
         let name = self.expect_identifier()?;
+        let mut backing = None;
+        if let Some(_) = self.test_token(&Token::Delimiter(':'), false) {
+            backing = Some(self.parse_type()?);
+        }
         self.expect_token(Token::Delimiter('{'))?;
         let variants = self.parse_enum_variants(Token::Delimiter('}'))?;
         self.expect_token(Token::Delimiter('}'))?;
         self.expect_end()?;
-        Ok(Statement::EnumStatement(EnumStatement { name, variants }))
+        Ok(Statement::EnumStatement(EnumStatement { name, variants, backing, doc }))
+    }
+
+    fn parse_type_stmt(&mut self, doc: Option<String>) -> Result<Statement, ParseError> {
+        let name = self.expect_identifier()?;
+        self.expect_token(Token::Op('=', None))?;
+        let typing = self.parse_type()?;
+        self.expect_end()?;
+        Ok(Statement::TypeStatement(TypeStatement { name, typing, doc }))
     }
 
     fn parse_if_stmt(&mut self) -> Result<Statement, ParseError> {
@@ -969,6 +1319,20 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    // for binding in iterable { block }
+    // `in` is not a keyword, so it can still be used as a name
+    fn parse_for_stmt(&mut self) -> Result<Statement, ParseError> {
+        let binding = self.expect_identifier()?;
+        self.expect_token(Token::Identifier("in".to_string()))?;
+        let iterable = self.parse_expr(0, false)?;
+        let block = self.parse_block(true, true)?;
+        Ok(Statement::ForStatement(ForStatement {
+            binding,
+            iterable,
+            block,
+        }))
+    }
+
     fn parse_while_stmt(&mut self) -> Result<Statement, ParseError> {
         let condition = self.parse_expr(0, false)?;
         let block = self.parse_block(true, true)?;
@@ -978,6 +1342,134 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    // Block of an if or match expression: `=> node` or `{ ... }`.
+    // A single expression after `=>` does not need to end the line, so `if c => a else => b` works.
+    fn parse_value_block(&mut self) -> Result<Block, ParseError> {
+        if let Some(_) = self.test_token(&Token::Op('=', Some('>')), true) {
+            if let Some(Lexem { token: Token::Keyword(k), span }) = self.lexer.peek()
+                && !matches!(k, Keyword::True | Keyword::False | Keyword::Null | Keyword::If | Keyword::Match | Keyword::Fn | Keyword::Comptime)
+            {
+                let span = *span;
+                let stmt = self.parse_stmt(None)?;
+                return Ok(Block { content: vec![Node { span, stmt }] });
+            }
+            let expr = self.parse_expr(0, true)?;
+            let span = expr.span;
+            return Ok(Block { content: vec![Node { span, stmt: Statement::ExpressionStatement(expr) }] });
+        }
+        self.parse_block(false, true)
+    }
+
+    // if condition { block } else { block }
+    fn parse_if_expr(&mut self) -> Result<Expression, ParseError> {
+        let span = self.current_span;
+        let condition = self.parse_expr(0, false)?;
+        let block_true = self.parse_value_block()?;
+        // Without the else branch there would be no value when the condition is false
+        self.expect_token(Token::Keyword(Keyword::Else))?;
+        let block_false;
+        if let Some(_) = self.test_token(&Token::Keyword(Keyword::If), false) {
+            let elseif = self.parse_if_expr()?;
+            let span = elseif.span;
+            block_false = Block { content: vec![Node { span, stmt: Statement::ExpressionStatement(elseif) }] };
+        } else {
+            block_false = self.parse_value_block()?;
+        }
+        Ok(Expression {
+            span: span.merge(self.current_span),
+            data: ExpressionData::If {
+                condition: Box::new(condition),
+                block_true,
+                block_false,
+            },
+        })
+    }
+
+    // match value {
+    //     pattern => node
+    //     pattern { block }
+    //     else => node
+    // }
+    fn parse_match_expr(&mut self) -> Result<Expression, ParseError> {
+        let span = self.current_span;
+        let value = self.parse_expr(0, false)?;
+        self.expect_token(Token::Delimiter('{'))?;
+        let mut arms = Vec::new();
+        loop {
+            while let Some(Lexem { token: Token::Newline | Token::Delimiter(',' | ';'), .. }) = self.lexer.peek() {
+                self.next();
+            }
+            if let Some(_) = self.test_token(&Token::Delimiter('}'), false) {
+                break;
+            }
+            let mut pattern = None;
+            if let None = self.test_token(&Token::Keyword(Keyword::Else), false) {
+                pattern = Some(self.parse_expr(0, false)?);
+            }
+            let block = self.parse_value_block()?;
+            arms.push(MatchArm { pattern, block });
+        }
+        Ok(Expression {
+            span: span.merge(self.current_span),
+            data: ExpressionData::Match {
+                value: Box::new(value),
+                arms,
+            },
+        })
+    }
+
+    // defer statement
+    // defer { block }
+    fn parse_defer_stmt(&mut self) -> Result<Statement, ParseError> {
+        let span = self.current_span;
+        let block;
+        if let Some(_) = self.seek_token(&Token::Delimiter('{'), false) {
+            block = self.parse_block(false, true)?;
+        } else if let Some(node) = self.parse_node(true)? {
+            block = Block { content: vec![node] };
+        } else {
+            return Err(ParseError {
+                span,
+                case: ParseErrorCase::ExpectedStatement,
+            });
+        }
+        Ok(Statement::DeferStatement(DeferStatement { block }))
+    }
+
+    fn parse_static_assert_stmt(&mut self) -> Result<Statement, ParseError> {
+        self.expect_token(Token::Delimiter('('))?;
+        let condition = self.parse_expr(0, true)?;
+        self.expect_token(Token::Delimiter(','))?;
+        let message = self.expect_str_literal()?;
+        self.expect_token(Token::Delimiter(')'))?;
+        self.expect_end()?;
+        Ok(Statement::StaticAssertStatement(StaticAssertStatement { condition, message }))
+    }
+
+    fn expect_str_literal(&mut self) -> Result<String, ParseError> {
+        match self.next() {
+            Some(Lexem { token: Token::String(StringKind::Static, value), .. }) => Ok(value),
+            Some(Lexem { token, span }) => {
+                return Err(ParseError {
+                    span,
+                    case: ParseErrorCase::UnexpectedToken {
+                        got: token.to_string(),
+                        expected: "str literal".to_string(),
+                    },
+                });
+            }
+            None => {
+                return Err(ParseError {
+                    span: self.current_span,
+                    case: ParseErrorCase::UnexpectedToken {
+                        got: EOF.to_string(),
+                        expected: "str literal".to_string(),
+                    },
+                });
+            }
+        }
+    }
+
     fn parse_return_stmt(&mut self) -> Result<Statement, ParseError> {
         if self.test_end() {
             return Ok(Statement::ReturnStatement(ReturnStatement { expr: None }));
@@ -990,25 +1482,37 @@ impl<'a> Parser<'a> {
         }))
     }
 
-    fn parse_stmt(&mut self) -> Result<Statement, ParseError> {
+    // `doc` is the documentation of the statement, if it is an item that can be documented
+    fn parse_stmt(&mut self, doc: Option<String>) -> Result<Statement, ParseError> {
         if let Some(Lexem {
             token: Token::Keyword(kw),
             span,
         }) = self.next()
         {
             let result = match kw {
-                Keyword::Struct => self.parse_struct_stmt(),
-                Keyword::Fn => self.parse_fn_stmt(),
-                Keyword::Extern => self.parse_extern_fn_stmt(),
-                Keyword::Enum => self.parse_enum_stmt(),
-                Keyword::Const => self.parse_const_stmt(),
+                Keyword::Struct => self.parse_struct_stmt(doc, false).map(Statement::StructStatement),
+                Keyword::Fn => self.parse_fn_stmt(doc),
+                Keyword::Extern => self.parse_extern_stmt(),
+                Keyword::Export => self.parse_export_stmt(doc),
+                Keyword::Enum => self.parse_enum_stmt(doc),
+                Keyword::Type => self.parse_type_stmt(doc),
+                Keyword::Const => self.parse_const_stmt(doc),
                 Keyword::Return => self.parse_return_stmt(),
                 Keyword::Var => self.parse_var_stmt(),
                 Keyword::Let => self.parse_let_stmt(),
                 Keyword::If => self.parse_if_stmt(),
                 Keyword::While => self.parse_while_stmt(),
+                Keyword::For => self.parse_for_stmt(),
+                Keyword::Defer => self.parse_defer_stmt(),
+                Keyword::StaticAssert => self.parse_static_assert_stmt(),
+                Keyword::Match => {
+                    let expr = self.parse_match_expr()?;
+                    self.expect_end()?;
+                    Ok(Statement::ExpressionStatement(expr))
+                }
                 Keyword::True => unreachable!("asked to parse true keyword in statement"),
                 Keyword::False => unreachable!("asked to parse false keyword in statement"),
+                Keyword::Null => unreachable!("asked to parse null keyword in statement"),
                 other => Err(ParseError {
                     span: span,
                     case: ParseErrorCase::UnexpectedToken {
@@ -1036,9 +1540,42 @@ impl<'a> Parser<'a> {
         return None;
     }
 
+    // The token after the next one, which is looked ahead without consuming anything
+    fn second_token(&self) -> Option<Token> {
+        self.lexer.clone().nth(1).map(|lexem| lexem.token)
+    }
+
+    // Tells if the next lexems declare a struct with layout annotations, like `packed align(4) struct`,
+    // or a union, like `union Number`. Otherwise `packed`, `align` and `union` are just identifiers.
+    fn layout_ahead(&self) -> bool {
+        let mut tokens = self.lexer.clone().map(|lexem| lexem.token);
+        loop {
+            match tokens.next() {
+                Some(Token::Identifier(name)) if name == "packed" => {}
+                Some(Token::Identifier(name)) if name == "align" => {
+                    if tokens.next() != Some(Token::Delimiter('(')) {
+                        return false;
+                    }
+                    let mut depth = 1;
+                    while depth > 0 {
+                        match tokens.next() {
+                            Some(Token::Delimiter('(')) => depth += 1,
+                            Some(Token::Delimiter(')')) => depth -= 1,
+                            Some(_) => {}
+                            None => return false,
+                        }
+                    }
+                }
+                Some(Token::Identifier(name)) if name == "union" => return matches!(tokens.next(), Some(Token::Identifier(_))),
+                Some(Token::Keyword(Keyword::Struct)) => return true,
+                _ => return false,
+            }
+        }
+    }
+
     fn parse_node(&mut self, inside_block: bool) -> Result<Option<Node>, ParseError> {
         let mut x = None;
-        if let Some(lexem) = self.lexer.peek() {
+        if let Some(lexem) = self.lexer.peek().cloned() {
             x = Some(match lexem.token {
                 Token::Newline => {
                     while let Some(Lexem { token: Token::Newline, .. }) = self.lexer.peek() {
@@ -1055,10 +1592,23 @@ impl<'a> Parser<'a> {
                 //                         so the parse_stmt function does not think that true or false is a statement keyword,
                 //                         so if this condition fails, it falls through to the next case (_) which parses it as an
                 //                         expression, the correct way to threat true and false.
-                Token::Keyword(k) if k != Keyword::True && k != Keyword::False => Node {
-                    span: lexem.span,
-                    stmt: self.parse_stmt()?,
-                },
+                Token::Keyword(k) if k != Keyword::True && k != Keyword::False && k != Keyword::Null && k != Keyword::Comptime => {
+                    let span = lexem.span;
+                    // Doc comments that don't document any item are just like regular comments
+                    let doc = self.docs.borrow_mut().remove(&span);
+                    Node {
+                        span,
+                        stmt: self.parse_stmt(doc)?,
+                    }
+                }
+                Token::Identifier(_) if self.layout_ahead() => {
+                    let span = lexem.span;
+                    let doc = self.docs.borrow_mut().remove(&span);
+                    Node {
+                        span,
+                        stmt: self.parse_layout_stmt(doc)?,
+                    }
+                }
                 _ => {
                     let span = lexem.span;
                     // Ensure that the next token is an token valid for an expression. Otherwise, stop parsing.
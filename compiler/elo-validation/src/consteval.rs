@@ -0,0 +1,703 @@
+// Compile-time evaluation of constant expressions (`const` values, array sizes and `comptime`).
+// Calls to Elo functions are executed by interpreting their checked body.
+use std::collections::HashMap;
+
+use elo_error::semerror::{SemanticError, SemanticErrorCase};
+use elo_ir::cir::{self, Primitive, Target, Typing};
+use elo_ir::cir::ExpressionIdentity;
+use elo_lexer::span::Span;
+
+// Statements executed (loop iterations included) before the evaluation is given up
+pub const MAX_STEPS: usize = 1_000_000;
+// Depth of nested function calls before the evaluation is given up
+pub const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    CStr(String),
+    Enum(String),
+    Array(Vec<Value>),
+    Tuple(Vec<Value>),
+    Struct(Vec<(String, Value)>),
+    Void,
+}
+
+impl Value {
+    // Literal expression of the value, with the type `typ`
+    pub fn to_expression(&self, expr: &cir::Expression, typ: &Typing) -> cir::Expression {
+        let immediate = |data| cir::Expression { span: expr.span, data, identity: ExpressionIdentity::Immediate };
+        let (data, natural) = match (self, typ) {
+            (Value::Int(value), _) => (cir::ExpressionData::Integer { value: *value }, Typing::Primitive(Primitive::UInt)),
+            (Value::Float(value), _) => (cir::ExpressionData::Float { value: *value }, Typing::Primitive(Primitive::Float)),
+            (Value::Bool(value), _) => (cir::ExpressionData::Bool { value: *value }, Typing::Primitive(Primitive::Bool)),
+            (Value::Str(value), _) => (cir::ExpressionData::StrLiteral { value: value.clone() }, Typing::Primitive(Primitive::Str)),
+            (Value::CStr(value), _) => (cir::ExpressionData::CStrLiteral { value: value.clone() }, Typing::Primitive(Primitive::CStr)),
+            (Value::Enum(variant), Typing::Enum(e)) => {
+                return immediate(cir::ExpressionData::EnumVariant { origin: e.clone(), variant: variant.clone() });
+            }
+            (Value::Array(items), Typing::Array { typ, .. }) => {
+                let exprs = items.iter().map(|item| item.to_expression(expr, typ)).collect();
+                return immediate(cir::ExpressionData::ArrayLiteral { exprs, typ: (**typ).clone() });
+            }
+            (Value::Tuple(items), Typing::Tuple { types }) => {
+                let exprs = items.iter().zip(types).map(|(item, typ)| item.to_expression(expr, typ)).collect();
+                return immediate(cir::ExpressionData::Tuple { exprs, types: types.clone() });
+            }
+            (Value::Struct(fields), Typing::Struct(s)) => {
+                let fields = fields
+                    .iter()
+                    .zip(&s.fields)
+                    .map(|((name, value), (_, typ))| (name.clone(), value.to_expression(expr, typ)))
+                    .collect();
+                return immediate(cir::ExpressionData::StructInit { origin: s.clone(), fields, base: None });
+            }
+            _ => unreachable!("value {self:?} does not have the type {typ:?}"),
+        };
+        let literal = immediate(data);
+        if &natural == typ {
+            return literal;
+        }
+        immediate(cir::ExpressionData::Cast { expr: Box::new(literal), typ: typ.clone() })
+    }
+}
+
+// Smallest and largest values of an integer type
+pub fn integer_range(typ: &Typing, target: &Target) -> Option<(i128, i128)> {
+    let bits = target.pointer_size as u32 * 8;
+    match typ {
+        Typing::Primitive(p) => match p {
+            Primitive::ISize => Some((-(1 << (bits - 1)), (1 << (bits - 1)) - 1)),
+            Primitive::USize => Some((0, (1 << bits) - 1)),
+            Primitive::I8 => Some((i8::MIN as i128, i8::MAX as i128)),
+            Primitive::I16 => Some((i16::MIN as i128, i16::MAX as i128)),
+            Primitive::I32 | Primitive::Int => Some((i32::MIN as i128, i32::MAX as i128)),
+            Primitive::I64 => Some((i64::MIN as i128, i64::MAX as i128)),
+            Primitive::U8 => Some((0, u8::MAX as i128)),
+            Primitive::U16 => Some((0, u16::MAX as i128)),
+            Primitive::U32 | Primitive::UInt | Primitive::Char => Some((0, u32::MAX as i128)),
+            Primitive::U64 => Some((0, u64::MAX as i128)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Convert the integer to the type like C does, keeping only its lower bits
+fn wrap_integer(value: i128, typ: &Typing, target: &Target) -> i128 {
+    let (min, max) = integer_range(typ, target).unwrap();
+    let modulo = max - min + 1;
+    let value = value.rem_euclid(modulo);
+    if value > max { value - modulo } else { value }
+}
+
+// Whether the values of the type can be computed at compile time (pointers, functions, slices
+// and lists only exist at runtime)
+pub fn is_evaluable(typ: &Typing) -> bool {
+    match typ {
+        Typing::Primitive(_) => true,
+        // The payloads are only built at runtime
+        Typing::Enum(e) => !e.is_tagged(),
+        Typing::Array { typ, .. } => is_evaluable(typ),
+        Typing::Tuple { types } => types.iter().all(is_evaluable),
+        Typing::Struct(s) => s.fields.iter().all(|(_, typ)| is_evaluable(typ)),
+        _ => false,
+    }
+}
+
+fn bounds_check(index: i128, len: usize, span: Span) -> Result<usize, SemanticError> {
+    match usize::try_from(index) {
+        Ok(i) if i < len => Ok(i),
+        _ => Err(SemanticError {
+            span,
+            case: SemanticErrorCase::IndexOutOfBounds { index: index.to_string(), len: len.to_string() },
+        }),
+    }
+}
+
+// Part of a variable, like the `[i]` and `.x` of `points[i].x`
+enum Place {
+    Index(i128, Span),
+    Field(String),
+    Tuple(usize),
+}
+
+// Variables of a function call, by scope
+type Frame = Vec<Scope>;
+
+#[derive(Default)]
+struct Scope {
+    variables: HashMap<String, (Value, Typing)>,
+    defers: Vec<cir::Block>,
+}
+
+pub struct Evaluator<'a> {
+    // Values of the constants evaluated so far
+    constants: &'a HashMap<String, (Value, Typing)>,
+    // Checked bodies of the Elo functions, which can be called at compile time
+    functions: &'a HashMap<String, cir::Function>,
+    target: Target,
+    frames: Vec<Frame>,
+    steps: usize,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(
+        constants: &'a HashMap<String, (Value, Typing)>,
+        functions: &'a HashMap<String, cir::Function>,
+        target: Target,
+    ) -> Self {
+        Self { constants, functions, target, frames: Vec::new(), steps: 0 }
+    }
+
+    fn not_constant(&self, expr: &cir::Expression) -> SemanticError {
+        SemanticError {
+            span: expr.span,
+            case: SemanticErrorCase::NotConstant { what: format!("{}", expr) },
+        }
+    }
+
+    fn check_range(&self, value: Option<i128>, typ: &Typing, expr: &cir::Expression) -> Result<Value, SemanticError> {
+        let (min, max) = integer_range(typ, &self.target).ok_or_else(|| self.not_constant(expr))?;
+        match value {
+            Some(value) if value >= min && value <= max => Ok(Value::Int(value)),
+            _ => Err(SemanticError {
+                span: expr.span,
+                case: SemanticErrorCase::ConstantOverflow { typ: format!("{typ}") },
+            }),
+        }
+    }
+
+    fn step(&mut self, span: Span) -> Result<(), SemanticError> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return Err(SemanticError {
+                span,
+                case: SemanticErrorCase::EvaluationLimit { limit: format!("{MAX_STEPS} steps") },
+            });
+        }
+        Ok(())
+    }
+
+    fn variable(&self, name: &str) -> Option<&(Value, Typing)> {
+        let frame = self.frames.last()?;
+        frame.iter().rev().find_map(|scope| scope.variables.get(name))
+    }
+
+    // Value and type of a typechecked expression. The expressions that evaluate others are
+    // handled by their own methods, so the frame of this one stays small in deep recursions.
+    pub fn evaluate(&mut self, expr: &cir::Expression) -> Result<(Value, Typing), SemanticError> {
+        match &expr.data {
+            cir::ExpressionData::Integer { value } => Ok((Value::Int(*value), Typing::Primitive(Primitive::UInt))),
+            cir::ExpressionData::Float { value } => Ok((Value::Float(*value), Typing::Primitive(Primitive::Float))),
+            cir::ExpressionData::Bool { value } => Ok((Value::Bool(*value), Typing::Primitive(Primitive::Bool))),
+            cir::ExpressionData::StrLiteral { value } => Ok((Value::Str(value.clone()), Typing::Primitive(Primitive::Str))),
+            cir::ExpressionData::CStrLiteral { value } => Ok((Value::CStr(value.clone()), Typing::Primitive(Primitive::CStr))),
+            cir::ExpressionData::Identifier { name } => {
+                if let ExpressionIdentity::Function(_) = expr.identity {
+                    return Err(self.not_constant(expr));
+                }
+                self.variable(name)
+                    .or_else(|| self.constants.get(name))
+                    .cloned()
+                    .ok_or_else(|| self.not_constant(expr))
+            }
+            cir::ExpressionData::EnumVariant { origin, variant } => Ok((Value::Enum(variant.clone()), Typing::Enum(origin.clone()))),
+            cir::ExpressionData::EnumName { expr: inner, .. } => {
+                let (Value::Enum(variant), _) = self.evaluate(inner)? else { unreachable!() };
+                Ok((Value::Str(variant), Typing::Primitive(Primitive::Str)))
+            }
+            cir::ExpressionData::EnumFromInt { expr: inner, origin, .. } => {
+                let (Value::Int(value), _) = self.evaluate(inner)? else { unreachable!() };
+                match origin.variant_of(value) {
+                    Some(variant) => Ok((Value::Enum(variant.clone()), Typing::Enum(origin.clone()))),
+                    None => Err(SemanticError {
+                        span: expr.span,
+                        case: SemanticErrorCase::InvalidEnumValue { enumeration: origin.name.clone(), value: value.to_string() },
+                    }),
+                }
+            }
+            cir::ExpressionData::Cast { expr: inner, typ } => self.cast(expr, inner, typ),
+            cir::ExpressionData::StrToCStr { expr: inner } | cir::ExpressionData::CStrToStr { expr: inner } => {
+                self.convert_str(expr, inner)
+            }
+            cir::ExpressionData::UnaryOperation { operator, operand } => self.unop(expr, operator, operand),
+            cir::ExpressionData::BinaryOperation { operator, left, right } => self.binop(expr, operator, left, right),
+            cir::ExpressionData::ArrayLiteral { exprs, typ } => {
+                let items = self.items(exprs)?;
+                let amount = items.len();
+                Ok((Value::Array(items), Typing::Array { typ: Box::new(typ.clone()), amount }))
+            }
+            cir::ExpressionData::Tuple { exprs, types } => {
+                Ok((Value::Tuple(self.items(exprs)?), Typing::Tuple { types: types.clone() }))
+            }
+            cir::ExpressionData::ArraySubscript { origin, index } => self.subscript(expr, origin, index),
+            cir::ExpressionData::FieldAccess { origin, field } => self.field(expr, origin, field),
+            cir::ExpressionData::TupleAccess { origin, field } => self.tuple_field(expr, origin, *field),
+            // Reading a union depends on how the target stores the field that was written
+            cir::ExpressionData::StructInit { origin, .. } if origin.layout.union => Err(self.not_constant(expr)),
+            cir::ExpressionData::StructInit { origin, fields, base } => self.struct_init(origin, fields, base.as_deref()),
+            cir::ExpressionData::Block { block, value } => self.block(expr, block, value.as_deref()),
+            cir::ExpressionData::If { condition, r#true, r#false, typ } => self.if_expr(condition, r#true, r#false, typ),
+            cir::ExpressionData::Match { value, arms, typ, .. } => self.match_expr(value, arms, typ),
+            cir::ExpressionData::FunctionCall { function, arguments } => self.call(expr, function, arguments),
+            _ => Err(self.not_constant(expr)),
+        }
+    }
+
+    fn cast(&mut self, expr: &cir::Expression, inner: &cir::Expression, typ: &Typing) -> Result<(Value, Typing), SemanticError> {
+        let (value, origin) = self.evaluate(inner)?;
+        let value = match (value, typ) {
+            (Value::Enum(variant), t) if integer_range(t, &self.target).is_some() => {
+                let Typing::Enum(e) = origin else { unreachable!() };
+                Value::Int(wrap_integer(e.value_of(&variant).unwrap(), t, &self.target))
+            }
+            (Value::Int(x), t) if integer_range(t, &self.target).is_some() => Value::Int(wrap_integer(x, t, &self.target)),
+            (Value::Int(x), t) if t.is_decimal() => Value::Float(x as f64),
+            (Value::Float(x), t) if integer_range(t, &self.target).is_some() => {
+                self.check_range(Some(x.trunc() as i128).filter(|_| x.is_finite()), t, expr)?
+            }
+            (Value::Float(x), Typing::Primitive(Primitive::F32)) => Value::Float(x as f32 as f64),
+            (Value::Float(x), t) if t.is_decimal() => Value::Float(x),
+            (Value::Bool(x), t) if integer_range(t, &self.target).is_some() => Value::Int(x as i128),
+            (Value::Bool(x), t) if t.is_bool() => Value::Bool(x),
+            (Value::Str(x), Typing::Primitive(Primitive::Str)) => Value::Str(x),
+            _ => return Err(self.not_constant(expr)),
+        };
+        Ok((value, typ.clone()))
+    }
+
+    // The value of both str and cstr is their characters, so only the type changes
+    fn convert_str(&mut self, expr: &cir::Expression, inner: &cir::Expression) -> Result<(Value, Typing), SemanticError> {
+        match self.evaluate(inner)? {
+            (Value::Str(x), _) => Ok((Value::CStr(x), Typing::Primitive(Primitive::CStr))),
+            (Value::CStr(x), _) => Ok((Value::Str(x), Typing::Primitive(Primitive::Str))),
+            _ => Err(self.not_constant(expr)),
+        }
+    }
+
+    fn unop(&mut self, expr: &cir::Expression, operator: &cir::UnaryOperation, operand: &cir::Expression) -> Result<(Value, Typing), SemanticError> {
+        let (value, typ) = self.evaluate(operand)?;
+        match (operator, value) {
+            (cir::UnaryOperation::Neg, Value::Int(x)) => {
+                let typ = typ.get_signed().unwrap_or(typ);
+                Ok((self.check_range(Some(-x), &typ, expr)?, typ))
+            }
+            (cir::UnaryOperation::Neg, Value::Float(x)) => Ok((Value::Float(-x), typ)),
+            (cir::UnaryOperation::Not, Value::Bool(x)) => Ok((Value::Bool(!x), typ)),
+            (cir::UnaryOperation::BNot, Value::Int(x)) => Ok((Value::Int(wrap_integer(!x, &typ, &self.target)), typ)),
+            _ => Err(self.not_constant(expr)),
+        }
+    }
+
+    fn binop(
+        &mut self,
+        expr: &cir::Expression,
+        operator: &cir::BinaryOperation,
+        left: &cir::Expression,
+        right: &cir::Expression,
+    ) -> Result<(Value, Typing), SemanticError> {
+        use cir::BinaryOperation as Op;
+        let compound = match operator {
+            Op::Assign => None,
+            Op::AssignAdd => Some(Op::Add),
+            Op::AssignSub => Some(Op::Sub),
+            Op::AssignMul => Some(Op::Mul),
+            Op::AssignDiv => Some(Op::Div),
+            Op::AssignMod => Some(Op::Mod),
+            Op::AssignBAnd => Some(Op::BAnd),
+            Op::AssignBOr => Some(Op::BOr),
+            Op::AssignBXor => Some(Op::BXor),
+            _ => {
+                let (lhs, typ) = self.evaluate(left)?;
+                // && and || don't evaluate their right side when the left one decides
+                match (operator, &lhs) {
+                    (Op::And, Value::Bool(false)) | (Op::Or, Value::Bool(true)) => return Ok((lhs, typ)),
+                    _ => {}
+                }
+                let (rhs, _) = self.evaluate(right)?;
+                return self.evaluate_binop(operator, lhs, rhs, typ, expr);
+            }
+        };
+        let (mut value, typ) = self.evaluate(right)?;
+        if let Some(operator) = compound {
+            let (lhs, typ) = self.evaluate(left)?;
+            value = self.evaluate_binop(&operator, lhs, value, typ, expr)?.0;
+        }
+        self.assign(left, value)?;
+        Ok((Value::Void, typ))
+    }
+
+    fn items(&mut self, exprs: &[cir::Expression]) -> Result<Vec<Value>, SemanticError> {
+        let mut items = Vec::new();
+        for e in exprs {
+            items.push(self.evaluate(e)?.0);
+        }
+        Ok(items)
+    }
+
+    fn subscript(&mut self, expr: &cir::Expression, origin: &cir::Expression, index: &cir::Expression) -> Result<(Value, Typing), SemanticError> {
+        if let Some(result) = self.read(expr)? {
+            return Ok(result);
+        }
+        let (array, typ) = self.evaluate(origin)?;
+        let Typing::Array { typ, .. } = typ else { return Err(self.not_constant(expr)) };
+        let (Value::Int(i), _) = self.evaluate(index)? else { return Err(self.not_constant(expr)) };
+        let Value::Array(mut items) = array else { return Err(self.not_constant(expr)) };
+        let i = bounds_check(i, items.len(), expr.span)?;
+        Ok((items.swap_remove(i), *typ))
+    }
+
+    fn field(&mut self, expr: &cir::Expression, origin: &cir::Expression, field: &str) -> Result<(Value, Typing), SemanticError> {
+        if let Some(result) = self.read(expr)? {
+            return Ok(result);
+        }
+        let (value, typ) = self.evaluate(origin)?;
+        match (value, typ) {
+            (Value::Struct(fields), Typing::Struct(s)) => {
+                let (_, value) = fields.into_iter().find(|(f, _)| f == field).unwrap();
+                let (_, typ) = s.fields.into_iter().find(|(f, _)| f == field).unwrap();
+                Ok((value, typ))
+            }
+            _ => Err(self.not_constant(expr)),
+        }
+    }
+
+    fn tuple_field(&mut self, expr: &cir::Expression, origin: &cir::Expression, field: usize) -> Result<(Value, Typing), SemanticError> {
+        if let Some(result) = self.read(expr)? {
+            return Ok(result);
+        }
+        let (value, typ) = self.evaluate(origin)?;
+        match (value, typ) {
+            (Value::Tuple(mut items), Typing::Tuple { mut types }) => {
+                Ok((items.swap_remove(field), types.swap_remove(field)))
+            }
+            _ => Err(self.not_constant(expr)),
+        }
+    }
+
+    fn struct_init(&mut self, origin: &cir::Struct, fields: &[cir::Field], base: Option<&cir::Expression>) -> Result<(Value, Typing), SemanticError> {
+        // The fields are evaluated before the base, in the order they are written
+        let mut given = Vec::new();
+        for (field, e) in fields {
+            given.push((field, self.evaluate(e)?.0));
+        }
+        let mut values = match base {
+            Some(base) => {
+                let (Value::Struct(values), _) = self.evaluate(base)? else { unreachable!() };
+                values
+            }
+            None => origin.fields.iter().map(|(f, _)| (f.clone(), Value::Void)).collect(),
+        };
+        for (field, value) in given {
+            values.iter_mut().find(|(f, _)| f == field).unwrap().1 = value;
+        }
+        Ok((Value::Struct(values), Typing::Struct(origin.clone())))
+    }
+
+    fn block(&mut self, expr: &cir::Expression, block: &cir::Block, value: Option<&cir::Expression>) -> Result<(Value, Typing), SemanticError> {
+        self.frame_mut().push(Scope::default());
+        let result = self.execute_block(block).and_then(|returned| {
+            if returned.is_some() {
+                // Returning from a branch of an if or match expression can't be given a value here
+                return Err(self.not_constant(expr));
+            }
+            match value {
+                Some(value) => self.evaluate(value),
+                None => Ok((Value::Void, Typing::Void)),
+            }
+        });
+        self.exit_scope()?;
+        result
+    }
+
+    fn if_expr(&mut self, condition: &cir::Expression, r#true: &cir::Expression, r#false: &cir::Expression, typ: &Typing) -> Result<(Value, Typing), SemanticError> {
+        let (Value::Bool(condition), _) = self.evaluate(condition)? else { unreachable!() };
+        let branch = if condition { r#true } else { r#false };
+        Ok((self.evaluate(branch)?.0, typ.clone()))
+    }
+
+    fn match_expr(&mut self, value: &cir::Expression, arms: &[(Option<cir::Expression>, cir::Expression)], typ: &Typing) -> Result<(Value, Typing), SemanticError> {
+        let (value, _) = self.evaluate(value)?;
+        for (pattern, branch) in arms {
+            let matches = match pattern {
+                Some(pattern) => self.evaluate(pattern)?.0 == value,
+                None => true,
+            };
+            if matches {
+                return Ok((self.evaluate(branch)?.0, typ.clone()));
+            }
+        }
+        Ok((Value::Void, Typing::Void))
+    }
+
+    fn call(&mut self, expr: &cir::Expression, function: &cir::Expression, arguments: &[cir::Expression]) -> Result<(Value, Typing), SemanticError> {
+        let cir::ExpressionData::Identifier { name } = &function.data else { unreachable!() };
+        let Some(function) = self.functions.get(name) else { return Err(self.not_constant(expr)) };
+        if self.frames.len() >= MAX_DEPTH {
+            return Err(SemanticError {
+                span: expr.span,
+                case: SemanticErrorCase::EvaluationLimit { limit: format!("{MAX_DEPTH} nested calls") },
+            });
+        }
+        let mut variables = HashMap::new();
+        for ((name, typ), argument) in function.head.arguments.iter().zip(arguments) {
+            let (value, _) = self.evaluate(argument)?;
+            variables.insert(name.clone(), (value, typ.clone()));
+        }
+        self.frames.push(vec![Scope { variables, defers: Vec::new() }]);
+        let result = self.execute_block(&function.block);
+        let exited = self.exit_scope();
+        self.frames.pop();
+        let returned = result?;
+        exited?;
+        let value = returned.unwrap_or(Value::Void);
+        Ok((value, function.head.ret.clone()))
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame {
+        if self.frames.is_empty() {
+            self.frames.push(Vec::new());
+        }
+        self.frames.last_mut().unwrap()
+    }
+
+    // Leave the innermost scope, running its deferred blocks
+    fn exit_scope(&mut self) -> Result<(), SemanticError> {
+        let scope = self.frame_mut().last_mut().unwrap();
+        let defers = std::mem::take(&mut scope.defers);
+        let mut result = Ok(());
+        for block in defers.iter().rev() {
+            if result.is_ok() {
+                self.frame_mut().push(Scope::default());
+                result = self.execute_block(block).map(|_| ());
+                self.frame_mut().pop();
+            }
+        }
+        self.frame_mut().pop();
+        result
+    }
+
+    fn execute_scope(&mut self, block: &cir::Block) -> Result<Option<Value>, SemanticError> {
+        self.frame_mut().push(Scope::default());
+        let result = self.execute_block(block);
+        let exited = self.exit_scope();
+        let returned = result?;
+        exited?;
+        Ok(returned)
+    }
+
+    // Execute the statements in the current scope, giving the returned value if any
+    fn execute_block(&mut self, block: &cir::Block) -> Result<Option<Value>, SemanticError> {
+        for stmt in block {
+            self.step(stmt.span)?;
+            match &stmt.kind {
+                cir::StatementKind::Variable { binding, assignment, typing }
+                | cir::StatementKind::Constant { binding, value: assignment, typing } => {
+                    let (value, _) = self.evaluate(assignment)?;
+                    let scope = self.frame_mut().last_mut().unwrap();
+                    scope.variables.insert(binding.clone(), (value, typing.clone()));
+                }
+                cir::StatementKind::ExpressionStatement(expr) => {
+                    self.evaluate(expr)?;
+                }
+                cir::StatementKind::ReturnStatement { value, .. } => {
+                    let value = match value {
+                        Some(value) => self.evaluate(value)?.0,
+                        None => Value::Void,
+                    };
+                    return Ok(Some(value));
+                }
+                cir::StatementKind::IfStatement { condition, block_true, block_false } => {
+                    let (Value::Bool(condition), _) = self.evaluate(condition)? else { unreachable!() };
+                    let branch = if condition { block_true } else { block_false };
+                    if let Some(value) = self.execute_scope(branch)? {
+                        return Ok(Some(value));
+                    }
+                }
+                cir::StatementKind::WhileStatement { condition, block } => {
+                    loop {
+                        let (Value::Bool(condition), _) = self.evaluate(condition)? else { unreachable!() };
+                        if !condition {
+                            break;
+                        }
+                        self.step(stmt.span)?;
+                        if let Some(value) = self.execute_scope(block)? {
+                            return Ok(Some(value));
+                        }
+                    }
+                }
+                cir::StatementKind::ForStatement { binding, typing, iterable, block, .. } => {
+                    let (Value::Array(items), _) = self.evaluate(iterable)? else { unreachable!() };
+                    for item in items {
+                        self.step(stmt.span)?;
+                        // Each item is bound in the scope of the block
+                        let mut scope = Scope::default();
+                        scope.variables.insert(binding.clone(), (item, typing.clone()));
+                        self.frame_mut().push(scope);
+                        let result = self.execute_block(block);
+                        let exited = self.exit_scope();
+                        let returned = result?;
+                        exited?;
+                        if let Some(value) = returned {
+                            return Ok(Some(value));
+                        }
+                    }
+                }
+                cir::StatementKind::DeferStatement { block } => {
+                    let scope = self.frame_mut().last_mut().unwrap();
+                    scope.defers.push(block.clone());
+                }
+                cir::StatementKind::StaticAssert { .. } => {}
+                _ => {
+                    return Err(SemanticError {
+                        span: stmt.span,
+                        case: SemanticErrorCase::NotConstant { what: String::from("this statement") },
+                    });
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // Name of the variable and the parts of it that `expr` refers to, like `points` and `[i]`, `.x` in `points[i].x`.
+    // The indices are evaluated here, in the order they appear.
+    fn path<'e>(&mut self, expr: &'e cir::Expression) -> Result<Option<(&'e String, Vec<Place>)>, SemanticError> {
+        let (name, mut places) = match &expr.data {
+            cir::ExpressionData::Identifier { name } => return Ok(Some((name, Vec::new()))),
+            cir::ExpressionData::ArraySubscript { origin, .. }
+            | cir::ExpressionData::FieldAccess { origin, .. }
+            | cir::ExpressionData::TupleAccess { origin, .. } => match self.path(origin)? {
+                Some(path) => path,
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        match &expr.data {
+            cir::ExpressionData::ArraySubscript { index, .. } => {
+                let (Value::Int(i), _) = self.evaluate(index)? else { return Err(self.not_constant(expr)) };
+                places.push(Place::Index(i, expr.span));
+            }
+            cir::ExpressionData::FieldAccess { field, .. } => places.push(Place::Field(field.clone())),
+            cir::ExpressionData::TupleAccess { field, .. } => places.push(Place::Tuple(*field)),
+            _ => {}
+        }
+        Ok(Some((name, places)))
+    }
+
+    // Value of a variable or constant (or the part of it) described by `expr`, without copying the rest of it
+    fn read(&mut self, expr: &cir::Expression) -> Result<Option<(Value, Typing)>, SemanticError> {
+        let Some((name, places)) = self.path(expr)? else { return Ok(None) };
+        let Some((mut value, mut typ)) = self.variable(name).or_else(|| self.constants.get(name)).map(|(v, t)| (v, t.clone())) else {
+            return Err(self.not_constant(expr));
+        };
+        for place in &places {
+            (value, typ) = match (place, value, typ) {
+                (Place::Index(i, span), Value::Array(items), Typing::Array { typ, .. }) => {
+                    (&items[bounds_check(*i, items.len(), *span)?], *typ)
+                }
+                (Place::Tuple(i), Value::Tuple(items), Typing::Tuple { mut types }) => (&items[*i], types.swap_remove(*i)),
+                (Place::Field(field), Value::Struct(fields), Typing::Struct(s)) => {
+                    let (_, value) = fields.iter().find(|(f, _)| f == field).unwrap();
+                    let (_, typ) = s.fields.into_iter().find(|(f, _)| f == field).unwrap();
+                    (value, typ)
+                }
+                _ => return Err(self.not_constant(expr)),
+            };
+        }
+        Ok(Some((value.clone(), typ)))
+    }
+
+    // Store the value in the variable (or the part of it) described by `target`
+    fn assign(&mut self, target: &cir::Expression, value: Value) -> Result<(), SemanticError> {
+        let Some((name, places)) = self.path(target)? else { return Err(self.not_constant(target)) };
+        let variable = self
+            .frames
+            .last_mut()
+            .and_then(|frame| frame.iter_mut().rev().find_map(|scope| scope.variables.get_mut(name)));
+        let Some((root, _)) = variable else {
+            return Err(self.not_constant(target));
+        };
+        let mut place = root;
+        for p in &places {
+            place = match (p, place) {
+                (Place::Index(i, span), Value::Array(items)) => {
+                    let i = bounds_check(*i, items.len(), *span)?;
+                    &mut items[i]
+                }
+                (Place::Tuple(i), Value::Tuple(items)) => &mut items[*i],
+                (Place::Field(field), Value::Struct(fields)) => &mut fields.iter_mut().find(|(f, _)| f == field).unwrap().1,
+                _ => unreachable!(),
+            };
+        }
+        *place = value;
+        Ok(())
+    }
+
+    fn evaluate_binop(
+        &self,
+        operator: &cir::BinaryOperation,
+        lhs: Value,
+        rhs: Value,
+        typ: Typing,
+        expr: &cir::Expression,
+    ) -> Result<(Value, Typing), SemanticError> {
+        use cir::BinaryOperation as Op;
+        let division_by_zero = SemanticError { span: expr.span, case: SemanticErrorCase::DivisionByZero };
+        let boolean = Typing::Primitive(Primitive::Bool);
+        let value = match (lhs, rhs) {
+            (Value::Int(x), Value::Int(y)) => match operator {
+                Op::Add => self.check_range(x.checked_add(y), &typ, expr)?,
+                Op::Sub => self.check_range(x.checked_sub(y), &typ, expr)?,
+                Op::Mul => self.check_range(x.checked_mul(y), &typ, expr)?,
+                Op::Div | Op::Mod if y == 0 => return Err(division_by_zero),
+                Op::Div => self.check_range(x.checked_div(y), &typ, expr)?,
+                Op::Mod => self.check_range(x.checked_rem(y), &typ, expr)?,
+                Op::BAnd => Value::Int(x & y),
+                Op::BOr => Value::Int(x | y),
+                Op::BXor => Value::Int(x ^ y),
+                Op::LShift => self.check_range(u32::try_from(y).ok().and_then(|y| x.checked_shl(y)).filter(|_| y < 64), &typ, expr)?,
+                Op::RShift => self.check_range(u32::try_from(y).ok().and_then(|y| x.checked_shr(y)), &typ, expr)?,
+                Op::Eq => return Ok((Value::Bool(x == y), boolean)),
+                Op::Ne => return Ok((Value::Bool(x != y), boolean)),
+                Op::Lt => return Ok((Value::Bool(x < y), boolean)),
+                Op::Le => return Ok((Value::Bool(x <= y), boolean)),
+                Op::Gt => return Ok((Value::Bool(x > y), boolean)),
+                Op::Ge => return Ok((Value::Bool(x >= y), boolean)),
+                _ => return Err(self.not_constant(expr)),
+            },
+            (Value::Float(x), Value::Float(y)) => match operator {
+                Op::Add => Value::Float(x + y),
+                Op::Sub => Value::Float(x - y),
+                Op::Mul => Value::Float(x * y),
+                Op::Div | Op::Mod if y == 0.0 => return Err(division_by_zero),
+                Op::Div => Value::Float(x / y),
+                Op::Mod => Value::Float(x % y),
+                Op::Eq => return Ok((Value::Bool(x == y), boolean)),
+                Op::Ne => return Ok((Value::Bool(x != y), boolean)),
+                Op::Lt => return Ok((Value::Bool(x < y), boolean)),
+                Op::Le => return Ok((Value::Bool(x <= y), boolean)),
+                Op::Gt => return Ok((Value::Bool(x > y), boolean)),
+                Op::Ge => return Ok((Value::Bool(x >= y), boolean)),
+                _ => return Err(self.not_constant(expr)),
+            },
+            (Value::Bool(x), Value::Bool(y)) => match operator {
+                Op::And => Value::Bool(x && y),
+                Op::Or => Value::Bool(x || y),
+                Op::Eq => Value::Bool(x == y),
+                Op::Ne => Value::Bool(x != y),
+                _ => return Err(self.not_constant(expr)),
+            },
+            (x @ (Value::Str(_) | Value::Enum(_)), y) => match operator {
+                Op::Eq => return Ok((Value::Bool(x == y), boolean)),
+                Op::Ne => return Ok((Value::Bool(x != y), boolean)),
+                _ => return Err(self.not_constant(expr)),
+            },
+            _ => return Err(self.not_constant(expr)),
+        };
+        Ok((value, typ))
+    }
+}
@@ -1,3 +1,4 @@
 pub mod tests;
 pub mod semcheck;
+pub mod consteval;
 pub mod validation;
@@ -2,14 +2,42 @@ use elo_ir::{ast::{self, TypedField}, cir::{ExpressionIdentity, Intrinsic, Primi
 use elo_error::semerror::*;
 use elo_ir::cir;
 use elo_lexer::span::Span;
+use crate::consteval::{self, Evaluator, Value};
 use std::collections::HashMap;
 
+// Declaration with a written type, as a key of Namespace::written
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Declaration {
+    // Argument of a function, by the names of both
+    Argument(String, String),
+    // Return type of a function
+    Return(String),
+    // Field of a struct, by the names of both
+    Field(String, String),
+}
+
 pub struct Namespace {
     pub name: Option<String>,
     pub constants: HashMap<String, (Span, cir::Typing)>,
+    // Values of the constants evaluated at compile time
+    pub constant_values: HashMap<String, (Value, cir::Typing)>,
     pub structs: HashMap<String, (Span, cir::Struct)>,
     pub enums: HashMap<String, (Span, cir::Enum)>,
     pub functions: HashMap<String, (Span, cir::FunctionHead)>,
+    // Checked bodies of the Elo functions, used to call them at compile time
+    pub function_bodies: HashMap<String, cir::Function>,
+    pub aliases: HashMap<String, (Span, cir::Typing)>,
+    // Aliases that declared types are written through, by declaration
+    pub written: HashMap<Declaration, String>,
+    pub globals: HashMap<String, (Span, Variable)>,
+    // Variables defined by C libraries
+    pub externs: HashMap<String, (Span, Variable)>,
+    // C symbols of the exported functions, by function name
+    pub exports: HashMap<String, (Span, String)>,
+    // Default values of the struct fields, by struct name
+    pub field_defaults: HashMap<String, Vec<cir::Field>>,
+    // Default values of the function arguments, by function name
+    pub argument_defaults: HashMap<String, Vec<cir::Field>>,
     pub locals: Vec<Scope>,
 }
 
@@ -23,6 +51,8 @@ pub enum Inference {
 pub struct Variable {
     pub mutable: bool,
     pub typing: cir::Typing,
+    // Innermost scope whose temporaries the value refers to (see SemanticChecker::lifetime)
+    pub lifetime: Option<usize>,
 }
 
 pub type Scope = HashMap<String, Variable>;
@@ -30,6 +60,13 @@ pub type Scope = HashMap<String, Variable>;
 pub struct SemanticChecker {
     namespace: Namespace,
     current_function: String,
+    // Closures being checked (innermost last): the number of scopes outside of the closure
+    // and the variables it captures from them
+    captures: Vec<(usize, Vec<cir::TypedField>)>,
+    // Return type of the function being checked, for the statements inside of expressions (like if blocks)
+    return_type: Option<cir::Typing>,
+    // Platform the program is compiled for, which gives the size of the C types
+    target: cir::Target,
     pub errors: Vec<SemanticError>,
 }
 
@@ -42,17 +79,69 @@ pub fn struct_field_linear_search<'a>(fields: &'a Vec<cir::TypedField>, field_na
     None
 }
 
+// Expression that gives a block its value, when it is the last node of the block
+fn block_value(node: ast::Node) -> Option<ast::Expression> {
+    match node.stmt {
+        ast::Statement::ExpressionStatement(expr) => Some(expr),
+        // An if statement at the end of the block is an if expression, when it has an else branch
+        ast::Statement::IfStatement(stmt) => Some(ast::Expression {
+            span: node.span,
+            data: ast::ExpressionData::If {
+                condition: Box::new(stmt.condition),
+                block_true: stmt.block_true,
+                block_false: stmt.block_false?,
+            },
+        }),
+        _ => None,
+    }
+}
+
+// Span of the first return statement in the block, including the nested blocks
+fn find_return(block: &cir::Block) -> Option<Span> {
+    for stmt in block {
+        let found = match &stmt.kind {
+            cir::StatementKind::ReturnStatement { .. } => Some(stmt.span),
+            cir::StatementKind::IfStatement { block_true, block_false, .. } => {
+                find_return(block_true).or_else(|| find_return(block_false))
+            }
+            cir::StatementKind::WhileStatement { block, .. } | cir::StatementKind::ForStatement { block, .. } => find_return(block),
+            cir::StatementKind::DeferStatement { block } => find_return(block),
+            _ => None,
+        };
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
 impl SemanticChecker {
     pub fn new() -> Self {
+        Self::with_target(cir::Target::host())
+    }
+
+    pub fn with_target(target: cir::Target) -> Self {
         Self {
             errors: Vec::new(),
             current_function: String::new(),
+            captures: Vec::new(),
+            return_type: None,
+            target,
             namespace: Namespace {
                 name: None,
                 structs: HashMap::new(),
                 enums: HashMap::new(),
                 constants: HashMap::new(),
+                constant_values: HashMap::new(),
                 functions: HashMap::new(),
+                function_bodies: HashMap::new(),
+                aliases: HashMap::new(),
+                written: HashMap::new(),
+                globals: HashMap::new(),
+                externs: HashMap::new(),
+                exports: HashMap::new(),
+                field_defaults: HashMap::new(),
+                argument_defaults: HashMap::new(),
                 locals: Vec::new(),
             },
         }
@@ -62,12 +151,14 @@ impl SemanticChecker {
         match &typ.typing {
             // TODO: Add generics
             ast::Typing::Named { name, .. } => {
-                if let Some(t) = cir::Primitive::from_str(name) {
+                if let Some(t) = cir::Primitive::from_str(name).or_else(|| self.target.c_type(name)) {
                     return Ok(cir::Typing::Primitive(t));
                 } else if let Some((_, e)) = self.namespace.enums.get(name) {
                     return Ok(cir::Typing::Enum(e.clone()));
                 } else if let Some((_, e)) = self.namespace.structs.get(name) {
                     return Ok(cir::Typing::Struct(e.clone()));
+                } else if let Some((_, t)) = self.namespace.aliases.get(name) {
+                    return Ok(t.clone());
                 }
                 return Err(SemanticError {
                     span: typ.span,
@@ -107,9 +198,27 @@ impl SemanticChecker {
                 });
             }
             ast::Typing::Array { typ, amount } => {
+                let (amount, amount_type) = self.typecheck_expr(amount, false)?;
+                if !amount_type.is_integer() {
+                    return Err(SemanticError {
+                        span: amount.span,
+                        case: SemanticErrorCase::TypeMismatch {
+                            got: format!("{}", amount_type),
+                            expected: "integer".to_string(),
+                        },
+                    });
+                }
+                let mut evaluator = Evaluator::new(&self.namespace.constant_values, &self.namespace.function_bodies, self.target);
+                let Value::Int(size) = evaluator.evaluate(&amount)?.0 else { unreachable!() };
+                if size < 0 {
+                    return Err(SemanticError {
+                        span: amount.span,
+                        case: SemanticErrorCase::InvalidArraySize { size: size.to_string() },
+                    });
+                }
                 return Ok(cir::Typing::Array {
                     typ: Box::new(self.check_type(typ)?),
-                    amount: *amount
+                    amount: size as usize
                 });
             }
             ast::Typing::Slice { typ } => {
@@ -128,7 +237,56 @@ impl SemanticChecker {
         span: Span,
     ) -> Result<cir::TypedExpression, SemanticError> {
         let ir_binop = cir::BinaryOperation::from_ast(&binop);
-        let rhs_inferred = self.make_inference(rhs.0, &rhs.1, &lhs.1);
+        // An enum with payloads is a struct in C, which can only be assigned
+        if let cir::Typing::Enum(e) = &lhs.1 && e.is_tagged() && !matches!(ir_binop, cir::BinaryOperation::Assign) {
+            return Err(SemanticError {
+                span,
+                case: SemanticErrorCase::PayloadOperation { enumeration: e.name.clone() },
+            });
+        }
+        // *T + n and *T - n move the pointer by n items, like in C
+        let offset = matches!(lhs.1, cir::Typing::Pointer { .. })
+            && rhs.1.is_integer()
+            && matches!(
+                ir_binop,
+                cir::BinaryOperation::Add
+                | cir::BinaryOperation::Sub
+                | cir::BinaryOperation::AssignAdd
+                | cir::BinaryOperation::AssignSub
+            );
+        // *T - *T is the distance between the pointers in items
+        let distance = !offset
+            && matches!(lhs.1, cir::Typing::Pointer { .. })
+            && matches!(ir_binop, cir::BinaryOperation::Sub);
+        // Other than those, pointers can only be compared and assigned
+        let pointers = matches!(lhs.1, cir::Typing::Pointer { .. }) || matches!(rhs.1, cir::Typing::Pointer { .. });
+        if pointers && !offset && !distance && !matches!(
+            ir_binop,
+            cir::BinaryOperation::Eq
+            | cir::BinaryOperation::Ne
+            | cir::BinaryOperation::Lt
+            | cir::BinaryOperation::Le
+            | cir::BinaryOperation::Gt
+            | cir::BinaryOperation::Ge
+            | cir::BinaryOperation::Assign
+        ) {
+            return Err(SemanticError {
+                span,
+                case: SemanticErrorCase::PointerOperation { operation: ir_binop.to_string() },
+            });
+        }
+        // `null == s` compares a C string like `s == null`
+        let lhs = match (&lhs.0.data, &rhs.1) {
+            (cir::ExpressionData::Null, cir::Typing::Primitive(cir::Primitive::CStr)) => {
+                (self.make_inference(lhs.0, &lhs.1, &rhs.1).unwrap(), rhs.1.clone())
+            }
+            _ => lhs,
+        };
+        let rhs_inferred = if offset {
+            Some(rhs.0)
+        } else {
+            self.make_inference(rhs.0, &rhs.1, &lhs.1)
+        };
 
         if rhs_inferred.is_none() {
             return Err(SemanticError {
@@ -141,6 +299,7 @@ impl SemanticChecker {
         }
 
         let typing = match ir_binop {
+            cir::BinaryOperation::Sub if distance => cir::Typing::Primitive(cir::Primitive::ISize),
             cir::BinaryOperation::Add
             | cir::BinaryOperation::Sub
             | cir::BinaryOperation::Mul
@@ -188,6 +347,23 @@ impl SemanticChecker {
                     }
                     _ => {} // Ok! For assignment, the lhs must be a mutable locatable value
                 }
+                if let cir::BinaryOperation::Assign = ir_binop
+                    && let Some(lifetime) = self.value_lifetime(rhs_inferred.as_ref().unwrap(), &lhs.1)
+                {
+                    // The temporaries of the value must live as long as the variable it is stored in
+                    match self.owner(&lhs.0) {
+                        Some((depth, name)) if lifetime <= depth => {
+                            let variable = self.namespace.locals[depth].get_mut(name).unwrap();
+                            variable.lifetime = variable.lifetime.max(Some(lifetime));
+                        }
+                        _ => {
+                            return Err(SemanticError {
+                                span,
+                                case: SemanticErrorCase::ShortLivedValue { what: format!("value assigned to {}", lhs.0) },
+                            });
+                        }
+                    }
+                }
                 cir::Typing::Void
             }
         };
@@ -216,6 +392,9 @@ impl SemanticChecker {
         let mut checked_arguments = Vec::new();
         let ret_type: cir::Typing;
         match intrinsic {
+            cir::Intrinsic::SizeOf | cir::Intrinsic::AlignOf | cir::Intrinsic::OffsetOf => {
+                return self.typecheck_layout_intrinsic(intrinsic, arguments, call_span);
+            }
             cir::Intrinsic::Args => {
                 let expected_len = 0;
                 ret_type = cir::Typing::Slice { typ: Box::new(cir::Typing::Primitive(cir::Primitive::Str)) };
@@ -258,11 +437,19 @@ impl SemanticChecker {
                     });
                 }
                 for expression in arguments.iter() {
-                    let (checked, got_type) = self.typecheck_expr(expression, false)?;
+                    let (mut checked, got_type) = self.typecheck_expr(expression, false)?;
+                    // Enums are printed by the name of their variant
+                    if let cir::Typing::Enum(e) = &got_type {
+                        checked = cir::Expression {
+                            span: checked.span,
+                            data: cir::ExpressionData::EnumName { expr: Box::new(checked), origin: e.clone() },
+                            identity: ExpressionIdentity::Immediate,
+                        };
+                    }
                     checked_arguments.push(checked);
 
                     resolved = match got_type {
-                        cir::Typing::Primitive(cir::Primitive::Str)  => Some(ResolvedIntrinsic::PrintStr),
+                        cir::Typing::Primitive(cir::Primitive::Str) | cir::Typing::Enum(_) => Some(ResolvedIntrinsic::PrintStr),
                         typ if typ.is_decimal()                      => Some(ResolvedIntrinsic::PrintDecimal),
                         typ if typ.is_unsigned()                     => Some(ResolvedIntrinsic::PrintUnsigned),
                         typ if typ.is_signed()                       => Some(ResolvedIntrinsic::PrintSigned),
@@ -292,29 +479,199 @@ impl SemanticChecker {
         ));
     }
 
+    // Value of an enum variant, like the 65 of `A = 65`
+    fn check_discriminant(&mut self, value: &ast::Expression) -> Result<i128, SemanticError> {
+        let (value, value_type) = self.typecheck_expr(value, false)?;
+        if !value_type.is_integer() {
+            return Err(SemanticError {
+                span: value.span,
+                case: SemanticErrorCase::TypeMismatch {
+                    got: format!("{}", value_type),
+                    expected: "integer".to_string(),
+                },
+            });
+        }
+        let mut evaluator = Evaluator::new(&self.namespace.constant_values, &self.namespace.function_bodies, self.target);
+        let Value::Int(value) = evaluator.evaluate(&value)?.0 else { unreachable!() };
+        Ok(value)
+    }
+
+    // Value of the N in align(N), which must be a power of two
+    fn check_align(&mut self, align: &ast::Expression) -> Result<usize, SemanticError> {
+        let (align, align_type) = self.typecheck_expr(align, false)?;
+        if !align_type.is_integer() {
+            return Err(SemanticError {
+                span: align.span,
+                case: SemanticErrorCase::TypeMismatch {
+                    got: format!("{}", align_type),
+                    expected: "integer".to_string(),
+                },
+            });
+        }
+        let mut evaluator = Evaluator::new(&self.namespace.constant_values, &self.namespace.function_bodies, self.target);
+        let Value::Int(value) = evaluator.evaluate(&align)?.0 else { unreachable!() };
+        match usize::try_from(value) {
+            Ok(value) if value.is_power_of_two() => Ok(value),
+            _ => Err(SemanticError {
+                span: align.span,
+                case: SemanticErrorCase::InvalidAlignment { align: value.to_string() },
+            }),
+        }
+    }
+
+    // sizeof(T), alignof(T) and offsetof(Struct, field) are replaced by their value,
+    // so they can be used in constants and array sizes
+    fn typecheck_layout_intrinsic(
+        &mut self,
+        intrinsic: cir::Intrinsic,
+        arguments: &Vec<ast::Expression>,
+        call_span: Span,
+    ) -> Result<cir::TypedExpression, SemanticError> {
+        let expected_len = if let cir::Intrinsic::OffsetOf = intrinsic { 2 } else { 1 };
+        if arguments.len() != expected_len {
+            return Err(SemanticError {
+                span: call_span,
+                case: SemanticErrorCase::UnmatchedArguments {
+                    function: format!("{intrinsic}"),
+                    got: arguments.len(),
+                    expected: expected_len,
+                    too_much: arguments.len() > expected_len,
+                },
+            });
+        }
+        let ast::ExpressionData::TypeArgument { typ } = &arguments[0].data else {
+            return Err(SemanticError {
+                span: arguments[0].span,
+                case: SemanticErrorCase::TypeMismatch { got: "value".to_string(), expected: "type".to_string() },
+            });
+        };
+        let typ = self.check_type(typ)?;
+        let value = match intrinsic {
+            cir::Intrinsic::SizeOf => typ.layout(&self.target).0,
+            cir::Intrinsic::AlignOf => typ.layout(&self.target).1,
+            _ => {
+                let cir::Typing::Struct(strukt) = &typ else {
+                    return Err(SemanticError {
+                        span: arguments[0].span,
+                        case: SemanticErrorCase::TypeMismatch { got: format!("{}", typ), expected: "struct".to_string() },
+                    });
+                };
+                let ast::ExpressionData::Identifier { name } = &arguments[1].data else {
+                    return Err(SemanticError {
+                        span: arguments[1].span,
+                        case: SemanticErrorCase::TypeMismatch { got: "expression".to_string(), expected: "field name".to_string() },
+                    });
+                };
+                strukt.offset_of(name, &self.target).ok_or_else(|| SemanticError {
+                    span: arguments[1].span,
+                    case: SemanticErrorCase::UnresolvedField {
+                        name: name.clone(),
+                        from: format!("struct {}", strukt.name),
+                    },
+                })?
+            }
+        };
+        Ok((
+            cir::Expression {
+                span: call_span,
+                data: cir::ExpressionData::Integer { value: value as i128 },
+                identity: ExpressionIdentity::Immediate,
+            },
+            cir::Typing::Primitive(cir::Primitive::UInt),
+        ))
+    }
+
     fn typecheck_function_call(
         &mut self,
         expr: cir::Expression,
         ret: cir::Typing,
         arguments: &Vec<cir::Typing>,
         variadic: bool,
+        extrn: bool,
         caller_arguments: &Vec<ast::Expression>,
+        spread: &Option<Box<ast::Expression>>,
+        named_arguments: &Vec<ast::Field>,
         call_span: Span,
     ) -> Result<cir::TypedExpression, SemanticError> {
         let return_type = ret;
-        let passed_length = caller_arguments.len();
-        let expected_len = arguments.len();
-        if passed_length < expected_len {
+        let passed_length = caller_arguments.len() + named_arguments.len();
+        // Variadic Elo functions take the extra arguments in a slice, which is their last argument.
+        // Extern variadic functions take them as C variadic arguments instead.
+        let tail = variadic && !extrn;
+        let expected_len = if tail { arguments.len() - 1 } else { arguments.len() };
+
+        // A slice can only be spread in place of all the variadic arguments of an Elo function
+        if let Some(spread) = spread && (!tail || caller_arguments.len() > expected_len) {
+            return Err(SemanticError {
+                span: spread.span,
+                case: SemanticErrorCase::InvalidSpread { function: format!("{expr}") },
+            });
+        }
+
+        // Argument names and default values are only known when calling a function by its name
+        let mut names = Vec::new();
+        let mut defaults = Vec::new();
+        let mut function = None;
+        if let (cir::ExpressionData::Identifier { name }, ExpressionIdentity::Function(_)) = (&expr.data, &expr.identity) {
+            function = Some(name.clone());
+            if let Some((_, head)) = self.namespace.functions.get(name) {
+                names = head.arguments.iter().take(expected_len).map(|(name, _)| name.clone()).collect();
+            }
+            if let Some(d) = self.namespace.argument_defaults.get(name) {
+                defaults = d.clone();
+            }
+        }
+
+        // Place every argument at the position of the parameter it is passed to
+        let mut slots: Vec<Option<&ast::Expression>> = vec![None; expected_len];
+        for (slot, expression) in slots.iter_mut().zip(caller_arguments.iter()) {
+            *slot = Some(expression);
+        }
+        for ast::Field { name, value } in named_arguments {
+            let Some(index) = names.iter().position(|n| n == name) else {
+                return Err(SemanticError {
+                    span: value.span,
+                    case: SemanticErrorCase::UnknownArgument {
+                        function: format!("{expr}"),
+                        name: name.clone(),
+                    },
+                });
+            };
+            if slots[index].is_some() {
+                return Err(SemanticError {
+                    span: value.span,
+                    case: SemanticErrorCase::DuplicateArgument {
+                        function: format!("{expr}"),
+                        name: name.clone(),
+                    },
+                });
+            }
+            slots[index] = Some(value);
+        }
+        for (index, slot) in slots.iter().enumerate() {
+            if slot.is_some() || defaults.iter().any(|(name, _)| name == &names[index]) {
+                continue;
+            }
+            if named_arguments.is_empty() {
+                return Err(SemanticError {
+                    span: call_span,
+                    case: SemanticErrorCase::UnmatchedArguments {
+                        function: format!("{expr}"),
+                        got: passed_length,
+                        expected: expected_len,
+                        too_much: false,
+                    },
+                });
+            }
             return Err(SemanticError {
                 span: call_span,
-                case: SemanticErrorCase::UnmatchedArguments {
+                case: SemanticErrorCase::MissingArgument {
                     function: format!("{expr}"),
-                    got: passed_length,
-                    expected: expected_len,
-                    too_much: false,
+                    name: names[index].clone(),
                 },
             });
-        } else if (passed_length > expected_len) && !variadic {
+        }
+        if (caller_arguments.len() > expected_len) && !variadic {
             return Err(SemanticError {
                 span: call_span,
                 case: SemanticErrorCase::UnmatchedArguments {
@@ -326,42 +683,381 @@ impl SemanticChecker {
             });
         }
         let mut checked_arguments = Vec::new();
-        let iter = caller_arguments.into_iter().zip(arguments.clone());
-        for (expression, expected_type) in iter {
+        let iter = slots.into_iter().zip(arguments.clone()).enumerate();
+        for (index, (expression, expected_type)) in iter {
+            let Some(expression) = expression else {
+                // The argument was not passed, so it takes its default value
+                let (_, default) = defaults.iter().find(|(name, _)| name == &names[index]).unwrap();
+                checked_arguments.push(default.clone());
+                continue;
+            };
             let (checked, got_type) = self.typecheck_expr(expression, false)?;
             if let Some(checked) = self.make_inference(checked, &got_type, &expected_type) {
                 checked_arguments.push(checked);
             } else {
+                let alias = function.as_ref().and_then(|f| self.namespace.written.get(&Declaration::Argument(f.clone(), names[index].clone())));
                 return Err(SemanticError {
                     span: expression.span,
                     case: SemanticErrorCase::TypeMismatch {
                         got: format!("{}", got_type),
-                        expected: format!("{}", expected_type),
+                        expected: self.written_type_name(&expected_type, alias),
                     },
                 });
             }
         }
 
-        // get the remaining extra arguments if the fn is variadic
-        for extra in caller_arguments.iter().skip(expected_len) {
-            // remaining if the function is variadic
-            let (extra, _) = self.typecheck_expr(extra, false)?;
-            checked_arguments.push(extra);
+        if let Some(spread) = spread {
+            let (checked, got_type) = self.typecheck_expr(spread, false)?;
+            let expected_type = arguments[expected_len].clone();
+            if got_type != expected_type {
+                return Err(SemanticError {
+                    span: spread.span,
+                    case: SemanticErrorCase::TypeMismatch {
+                        got: format!("{}", got_type),
+                        expected: format!("{}", expected_type),
+                    },
+                });
+            }
+            checked_arguments.push(checked);
+        } else if tail {
+            // Pack the extra arguments into the slice
+            let Some(cir::Typing::Slice { typ }) = arguments.last() else { unreachable!() };
+            let mut exprs = Vec::new();
+            for extra in caller_arguments.iter().skip(expected_len) {
+                let (checked, got_type) = self.typecheck_expr(extra, false)?;
+                if let Some(checked) = self.make_inference(checked, &got_type, typ) {
+                    exprs.push(checked);
+                } else {
+                    return Err(SemanticError {
+                        span: extra.span,
+                        case: SemanticErrorCase::TypeMismatch {
+                            got: format!("{}", got_type),
+                            expected: format!("{}", typ),
+                        },
+                    });
+                }
+            }
+            checked_arguments.push(cir::Expression {
+                span: call_span,
+                data: cir::ExpressionData::SlicePack { typ: *typ.clone(), exprs },
+                identity: ExpressionIdentity::Immediate,
+            });
+        } else {
+            // get the remaining extra arguments if the fn is variadic
+            for extra in caller_arguments.iter().skip(expected_len) {
+                // remaining if the function is variadic
+                let (extra, _) = self.typecheck_expr(extra, false)?;
+                checked_arguments.push(extra);
+            }
         }
 
+        let data = if let ExpressionIdentity::Function(_) = expr.identity {
+            cir::ExpressionData::FunctionCall {
+                function: Box::new(expr),
+                arguments: checked_arguments,
+            }
+        } else {
+            cir::ExpressionData::IndirectCall {
+                function: Box::new(expr),
+                arguments: checked_arguments,
+                typ: cir::Typing::Function {
+                    ret: Box::new(return_type.clone()),
+                    arguments: arguments.clone(),
+                    variadic,
+                    extrn,
+                },
+            }
+        };
         return Ok((
             cir::Expression {
                 span: call_span,
-                data: cir::ExpressionData::FunctionCall {
-                    function: Box::new(expr),
-                    arguments: checked_arguments,
-                },
+                data,
                 identity: ExpressionIdentity::Immediate,
             },
             return_type,
         ));
     }
 
+    // Place the arguments of `Enum.Variant(...)` at the position of the payload fields they give,
+    // like the arguments of a function call. Unless `partial`, every field must be given.
+    fn place_payload<'a>(
+        &self,
+        e: &cir::Enum,
+        variant: &str,
+        positional: &'a [ast::Expression],
+        named: &'a [ast::Field],
+        partial: bool,
+        span: Span,
+    ) -> Result<Vec<Option<&'a ast::Expression>>, SemanticError> {
+        let function = format!("{}.{}", e.name, variant);
+        let fields = e.payload_of(variant).map(cir::Payload::fields).unwrap_or_default();
+        if positional.len() > fields.len() {
+            return Err(SemanticError {
+                span,
+                case: SemanticErrorCase::UnmatchedArguments {
+                    function,
+                    got: positional.len() + named.len(),
+                    expected: fields.len(),
+                    too_much: true,
+                },
+            });
+        }
+        let mut slots: Vec<Option<&ast::Expression>> = vec![None; fields.len()];
+        for (slot, expression) in slots.iter_mut().zip(positional.iter()) {
+            *slot = Some(expression);
+        }
+        for ast::Field { name, value } in named {
+            let Some(index) = fields.iter().position(|(field, _)| field == name) else {
+                return Err(SemanticError {
+                    span: value.span,
+                    case: SemanticErrorCase::UnknownArgument { function, name: name.clone() },
+                });
+            };
+            if slots[index].is_some() {
+                return Err(SemanticError {
+                    span: value.span,
+                    case: SemanticErrorCase::DuplicateArgument { function, name: name.clone() },
+                });
+            }
+            slots[index] = Some(value);
+        }
+        if partial {
+            return Ok(slots);
+        }
+        if let Some(index) = slots.iter().position(Option::is_none) {
+            let case = if named.is_empty() {
+                SemanticErrorCase::UnmatchedArguments {
+                    function,
+                    got: positional.len(),
+                    expected: fields.len(),
+                    too_much: false,
+                }
+            } else {
+                SemanticErrorCase::MissingArgument { function, name: fields[index].0.clone() }
+            };
+            return Err(SemanticError { span, case });
+        }
+        Ok(slots)
+    }
+
+    // Enum.Variant(arguments), which gives the payload of the variant
+    fn typecheck_variant_init(
+        &mut self,
+        e: &cir::Enum,
+        variant: &str,
+        arguments: &Vec<ast::Expression>,
+        spread: &Option<Box<ast::Expression>>,
+        named: &Vec<ast::Field>,
+        span: Span,
+    ) -> Result<cir::TypedExpression, SemanticError> {
+        if let Some(spread) = spread {
+            return Err(SemanticError {
+                span: spread.span,
+                case: SemanticErrorCase::InvalidSpread { function: format!("{}.{}", e.name, variant) },
+            });
+        }
+        let slots = self.place_payload(e, variant, arguments, named, false, span)?;
+        let payload = e.payload_of(variant).map(cir::Payload::fields).unwrap_or_default();
+        let mut fields = Vec::new();
+        for (expression, (field, expected_type)) in slots.into_iter().zip(payload) {
+            let expression = expression.unwrap();
+            let (checked, got_type) = self.typecheck_expr(expression, false)?;
+            let Some(checked) = self.make_inference(checked, &got_type, &expected_type) else {
+                return Err(SemanticError {
+                    span: expression.span,
+                    case: SemanticErrorCase::TypeMismatch {
+                        got: format!("{}", got_type),
+                        expected: format!("{}", expected_type),
+                    },
+                });
+            };
+            fields.push((field, checked));
+        }
+        let data = if fields.is_empty() {
+            cir::ExpressionData::EnumVariant { origin: e.clone(), variant: variant.to_string() }
+        } else {
+            cir::ExpressionData::EnumInit { origin: e.clone(), variant: variant.to_string(), fields }
+        };
+        Ok((
+            cir::Expression { span, data, identity: ExpressionIdentity::Immediate },
+            cir::Typing::Enum(e.clone()),
+        ))
+    }
+
+    // Enum.variants(), an array with every variant in the order they are declared
+    fn typecheck_enum_variants(&mut self, e: &cir::Enum, span: Span) -> Result<cir::TypedExpression, SemanticError> {
+        // The variants with payloads have no value without their data
+        if e.is_tagged() {
+            return Err(SemanticError {
+                span,
+                case: SemanticErrorCase::PayloadOperation { enumeration: e.name.clone() },
+            });
+        }
+        let typ = cir::Typing::Enum(e.clone());
+        let exprs = e.variants
+            .iter()
+            .map(|(variant, _)| cir::Expression {
+                span,
+                data: cir::ExpressionData::EnumVariant { origin: e.clone(), variant: variant.clone() },
+                identity: ExpressionIdentity::Immediate,
+            })
+            .collect::<Vec<cir::Expression>>();
+        let amount = exprs.len();
+        Ok((
+            cir::Expression {
+                span,
+                data: cir::ExpressionData::ArrayLiteral { exprs, typ: typ.clone() },
+                identity: ExpressionIdentity::Immediate,
+            },
+            cir::Typing::Array { typ: Box::new(typ), amount },
+        ))
+    }
+
+    // Pattern of a match on an enum with payloads: `Enum.Variant`, or `Enum.Variant(names)` to bind
+    // the fields of the payload to variables. Returns the variant and the variables with their values.
+    fn typecheck_variant_pattern(
+        &mut self,
+        e: &cir::Enum,
+        pattern: &ast::Expression,
+    ) -> Result<(cir::Expression, Vec<(String, cir::TypedExpression)>), SemanticError> {
+        let (access, arguments, named) = match &pattern.data {
+            ast::ExpressionData::FunctionCall { function, arguments, named, .. } => (function.as_ref(), &arguments[..], &named[..]),
+            _ => (pattern, &[][..], &[][..]),
+        };
+        let variant = match &access.data {
+            ast::ExpressionData::FieldAccess { origin, field } if matches!(&origin.data, ast::ExpressionData::Identifier { name } if name == &e.name) => {
+                if e.value_of(field).is_none() {
+                    return Err(SemanticError {
+                        span: origin.span,
+                        case: SemanticErrorCase::UnknownEnumVariant { enumeration: e.name.clone(), variant: field.clone() },
+                    });
+                }
+                field.clone()
+            }
+            _ => {
+                let (checked, _) = self.typecheck_expr(pattern, false)?;
+                return Err(SemanticError {
+                    span: pattern.span,
+                    case: SemanticErrorCase::InvalidExpression {
+                        what: format!("{checked}"),
+                        should: format!("a variant of enumeration {}", e.name),
+                    },
+                });
+            }
+        };
+
+        let slots = self.place_payload(e, &variant, arguments, named, !named.is_empty(), pattern.span)?;
+        let fields = e.payload_of(&variant).map(cir::Payload::fields).unwrap_or_default();
+        let mut bindings: Vec<(String, cir::TypedExpression)> = Vec::new();
+        for (expression, (field, typ)) in slots.into_iter().zip(fields) {
+            let Some(expression) = expression else { continue };
+            let ast::ExpressionData::Identifier { name } = &expression.data else {
+                let (checked, _) = self.typecheck_expr(expression, false)?;
+                return Err(SemanticError {
+                    span: expression.span,
+                    case: SemanticErrorCase::InvalidExpression {
+                        what: format!("{checked}"),
+                        should: "a name to bind the field to".to_string(),
+                    },
+                });
+            };
+            // `_` leaves the field out
+            if name == "_" {
+                continue;
+            }
+            if bindings.iter().any(|(binding, _)| binding == name) || self.namespace.locals.iter().any(|scope| scope.contains_key(name)) {
+                return Err(SemanticError {
+                    span: expression.span,
+                    case: SemanticErrorCase::VariableRedefinition { name: name.clone() },
+                });
+            }
+            let value = cir::Expression {
+                span: expression.span,
+                data: cir::ExpressionData::Payload { variant: variant.clone(), field },
+                identity: ExpressionIdentity::Immediate,
+            };
+            bindings.push((name.clone(), (value, typ)));
+        }
+        let pattern = cir::Expression {
+            span: pattern.span,
+            data: cir::ExpressionData::EnumVariant { origin: e.clone(), variant },
+            identity: ExpressionIdentity::Immediate,
+        };
+        Ok((pattern, bindings))
+    }
+
+    // Typecheck the default value of a struct field or function argument.
+    // `declaration` is the field or argument, as a key of Namespace::written
+    fn check_default(&mut self, default: &ast::Expression, typing: &cir::Typing, declaration: &Declaration) -> Result<cir::Expression, SemanticError> {
+        let (expr, default_type) = self.typecheck_expr(default, false)?;
+        if let Some(expr) = self.make_inference(expr, &default_type, typing) {
+            return Ok(expr);
+        }
+        return Err(SemanticError {
+            span: default.span,
+            case: SemanticErrorCase::TypeMismatch {
+                got: format!("{}", default_type),
+                expected: self.written_type_name(typing, self.namespace.written.get(declaration)),
+            },
+        });
+    }
+
+    // Innermost scope whose runtime temporaries the value refers to, or None when it refers to
+    // none that are ever released. The temporaries created by the expression itself belong to `current`.
+    fn lifetime(&self, expr: &cir::Expression, current: Option<usize>) -> Option<usize> {
+        let all = |exprs: &mut dyn Iterator<Item = &cir::Expression>| exprs.map(|e| self.lifetime(e, current)).max().flatten();
+        match &expr.data {
+            cir::ExpressionData::StrToCStr { .. } | cir::ExpressionData::SlicePack { .. } => current,
+            // Returned values keep the temporaries of the function, which become the caller's.
+            // The statements of a block may create temporaries for its value too.
+            cir::ExpressionData::FunctionCall { .. }
+            | cir::ExpressionData::IndirectCall { .. }
+            | cir::ExpressionData::Block { .. }
+            | cir::ExpressionData::Payload { .. } => current,
+            cir::ExpressionData::Identifier { name } => {
+                self.namespace.locals.iter().rev().find_map(|scope| scope.get(name)).and_then(|var| var.lifetime)
+            }
+            // The environment holds the captured values
+            cir::ExpressionData::Closure { captures, .. } if !captures.is_empty() => current,
+            cir::ExpressionData::Cast { expr, .. }
+            | cir::ExpressionData::UnaryOperation { operand: expr, .. }
+            | cir::ExpressionData::FieldAccess { origin: expr, .. }
+            | cir::ExpressionData::TupleAccess { origin: expr, .. }
+            | cir::ExpressionData::ArraySubscript { origin: expr, .. }
+            | cir::ExpressionData::SliceSubscript { origin: expr, .. }
+            | cir::ExpressionData::PointerSubscript { origin: expr, .. } => self.lifetime(expr, current),
+            cir::ExpressionData::BinaryOperation { left, right, .. } => all(&mut [left, right].into_iter().map(|e| &**e)),
+            cir::ExpressionData::ArrayLiteral { exprs, .. } | cir::ExpressionData::Tuple { exprs, .. } => all(&mut exprs.iter()),
+            cir::ExpressionData::EnumInit { fields, .. } => all(&mut fields.iter().map(|(_, e)| e)),
+            cir::ExpressionData::StructInit { fields, base, .. } => all(&mut fields.iter().map(|(_, e)| e).chain(base.as_deref())),
+            cir::ExpressionData::If { r#true, r#false, .. } => all(&mut [r#true, r#false].into_iter().map(|e| &**e)),
+            cir::ExpressionData::Match { arms, .. } => all(&mut arms.iter().map(|(_, branch)| branch)),
+            _ => None,
+        }
+    }
+
+    // Lifetime of a value of the current scope, which only matters for the types that may hold temporaries
+    fn value_lifetime(&self, expr: &cir::Expression, typ: &cir::Typing) -> Option<usize> {
+        if !typ.holds_temporaries() {
+            return None;
+        }
+        self.lifetime(expr, self.namespace.locals.len().checked_sub(1))
+    }
+
+    // Local variable that owns the place assigned by `target`, and the depth of its scope.
+    // None when it is a global or the place is reached through a pointer or a slice.
+    fn owner<'a>(&self, target: &'a cir::Expression) -> Option<(usize, &'a String)> {
+        match &target.data {
+            cir::ExpressionData::Identifier { name } => {
+                self.namespace.locals.iter().rposition(|scope| scope.contains_key(name)).map(|depth| (depth, name))
+            }
+            cir::ExpressionData::FieldAccess { origin, .. }
+            | cir::ExpressionData::TupleAccess { origin, .. }
+            | cir::ExpressionData::ArraySubscript { origin, .. } => self.owner(origin),
+            _ => None,
+        }
+    }
+
     fn auto_dereference(&self, expression: cir::TypedExpression) -> cir::TypedExpression {
         let (mut expr, mut typ) = expression;
         let span = expr.span;
@@ -381,8 +1077,12 @@ impl SemanticChecker {
 
     // Make the changes in the expression so the inference is possible
     fn make_inference(&self, expression: cir::Expression, from: &cir::Typing, into: &cir::Typing) -> Option<cir::Expression> {
-        let inf = self.typecheck_inference(from, into);
+        let mut inf = self.typecheck_inference(from, into);
         let id = expression.identity;
+        // C strings may be null, like the ones returned by getenv
+        if let (cir::ExpressionData::Null, cir::Typing::Primitive(cir::Primitive::CStr)) = (&expression.data, into) {
+            inf = Inference::Cast;
+        }
 
         match inf {
             Inference::Invalid => None,
@@ -404,8 +1104,9 @@ impl SemanticChecker {
             return Inference::Equal;
         }
 
-        let x = from.get_size();
-        let y = into.get_size();
+        // The largest `from` must fit in the smallest `into`
+        let (_, x) = from.get_size_range();
+        let (y, _) = into.get_size_range();
 
         let mut cast = false;
         if from.is_unsigned() {
@@ -432,6 +1133,16 @@ impl SemanticChecker {
         ) = (&from, &into) {
             cast = true;
         }
+        // rawptr is compatible with every pointer, like void* in C
+        if let (
+            &cir::Typing::Primitive(cir::Primitive::RawPtr),
+            &cir::Typing::Pointer { .. },
+        ) | (
+            &cir::Typing::Pointer { .. },
+            &cir::Typing::Primitive(cir::Primitive::RawPtr),
+        ) = (&from, &into) {
+            cast = true;
+        }
 
         if cast {
             return Inference::Cast;
@@ -441,7 +1152,8 @@ impl SemanticChecker {
     }
 
     // Explicit type cast rules
-    fn typecheck_cast(&mut self, origin: &cir::Typing, into: &cir::Typing, span: Span) -> Result<(), SemanticError> {
+    // `alias` is the alias the type to cast into is written through, if any
+    fn typecheck_cast(&mut self, origin: &cir::Typing, into: &cir::Typing, alias: Option<&String>, span: Span) -> Result<(), SemanticError> {
         let mut ok = false;
 
         if let Inference::Cast | Inference::Equal = self.typecheck_inference(&origin, &into) {
@@ -449,16 +1161,38 @@ impl SemanticChecker {
         }
 
         if origin.is_integer() {
-            ok = into.is_bool() || into.is_integer() || into.is_decimal();
+            ok = into.is_bool() || into.is_integer() || into.is_decimal() || matches!(into, cir::Typing::Enum(e) if !e.is_tagged());
+            if let cir::Typing::Primitive(cir::Primitive::USize | cir::Primitive::ISize) = origin {
+                ok = ok || into.is_pointer();
+            }
         } else if origin.is_decimal() {
             ok = into.is_bool() || into.is_integer() || into.is_decimal();
         } else if origin.is_bool() {
             ok = into.is_integer();
+        } else if let cir::Typing::Enum(e) = origin {
+            ok = into.is_integer() && !e.is_tagged();
+        } else if origin.is_pointer() {
+            ok = into.is_pointer() || matches!(into, cir::Typing::Primitive(cir::Primitive::USize | cir::Primitive::ISize));
+            // rawptr is also how C code passes strings around, like void* in C
+            if let cir::Typing::Primitive(cir::Primitive::RawPtr) = origin {
+                ok = ok || matches!(into, cir::Typing::Primitive(cir::Primitive::CStr));
+            }
         } else if let (
             &cir::Typing::Primitive(cir::Primitive::Char),
             &cir::Typing::Primitive(cir::Primitive::U8)
         ) = (&origin, &into) {
             ok = true;
+        } else if let (
+            &cir::Typing::Primitive(cir::Primitive::Str),
+            &cir::Typing::Primitive(cir::Primitive::CStr)
+        ) | (
+            &cir::Typing::Primitive(cir::Primitive::CStr),
+            &cir::Typing::Primitive(cir::Primitive::Str)
+        ) | (
+            &cir::Typing::Primitive(cir::Primitive::CStr),
+            &cir::Typing::Primitive(cir::Primitive::RawPtr)
+        ) = (&origin, &into) {
+            ok = true;
         }
 
         if !ok {
@@ -466,7 +1200,7 @@ impl SemanticChecker {
                 span: span,
                 case: SemanticErrorCase::InvalidCast {
                     from: format!("{}", origin),
-                    into: format!("{}", into),
+                    into: self.written_type_name(&into, alias),
                 }
             });
         }
@@ -476,10 +1210,49 @@ impl SemanticChecker {
     fn typecheck_expr(&mut self, expr: &ast::Expression, function_call: bool) -> Result<cir::TypedExpression, SemanticError> {
         match &expr.data {
             ast::ExpressionData::Cast { expr: inner, typ } => {
+                let alias = self.written_alias(typ);
                 let typ = self.check_type(typ)?;
                 let (inner, origin) = self.typecheck_expr(inner, function_call)?;
                 let id = inner.identity;
-                self.typecheck_cast(&origin, &typ, expr.span)?;
+                // An exported function converted into rawptr is the address of its C entry point,
+                // which can be passed to C as a callback
+                if let (cir::ExpressionData::Identifier { name }, ExpressionIdentity::Function(false), cir::Typing::Primitive(Primitive::RawPtr)) = (&inner.data, id, &typ) {
+                    if let Some((_, symbol)) = self.namespace.exports.get(name) {
+                        return Ok((
+                            cir::Expression {
+                                span: expr.span,
+                                data: cir::ExpressionData::ExportedFunction { name: name.clone(), symbol: symbol.clone() },
+                                identity: ExpressionIdentity::Immediate,
+                            },
+                            typ,
+                        ));
+                    }
+                }
+                self.typecheck_cast(&origin, &typ, alias.as_ref(), expr.span)?;
+                // Converting between str and cstr copies the characters
+                let conversion = match (&origin, &typ) {
+                    (cir::Typing::Primitive(Primitive::Str), cir::Typing::Primitive(Primitive::CStr)) => {
+                        Some(cir::ExpressionData::StrToCStr { expr: Box::new(inner.clone()) })
+                    }
+                    (cir::Typing::Primitive(Primitive::CStr), cir::Typing::Primitive(Primitive::Str)) => {
+                        Some(cir::ExpressionData::CStrToStr { expr: Box::new(inner.clone()) })
+                    }
+                    // Only the values of the variants are valid, which is checked when converting
+                    (origin, cir::Typing::Enum(e)) if origin.is_integer() => {
+                        Some(cir::ExpressionData::EnumFromInt { expr: Box::new(inner.clone()), origin: e.clone(), signed: origin.is_signed() })
+                    }
+                    _ => None,
+                };
+                if let Some(data) = conversion {
+                    return Ok((
+                        cir::Expression {
+                            span: expr.span,
+                            data,
+                            identity: ExpressionIdentity::Immediate,
+                        },
+                        typ,
+                    ));
+                }
                 return Ok((
                     cir::Expression {
                         span: expr.span,
@@ -729,8 +1502,39 @@ impl SemanticChecker {
             }
             ast::ExpressionData::Subscript { origin, inner } => {
                 let origin = self.typecheck_expr(origin, function_call)?;
+                let pointer = origin.clone();
                 let (origin, origin_type) = self.auto_dereference(origin);
                 let origin_id = origin.identity;
+                // Pointers to values that can't be indexed are indexed like C arrays
+                if let cir::Typing::Pointer { typ, mutable } = pointer.1
+                    && !matches!(origin_type, cir::Typing::Array { .. } | cir::Typing::Slice { .. } | cir::Typing::Primitive(Primitive::Str))
+                {
+                    let (index, index_type) = self.typecheck_expr(inner, function_call)?;
+                    let index_span = index.span;
+                    let usize = cir::Typing::Primitive(Primitive::USize);
+                    let index = if let Some(i) = self.make_inference(index, &index_type, &usize) {
+                        i
+                    } else {
+                        return Err(SemanticError {
+                            span: index_span,
+                            case: SemanticErrorCase::TypeMismatch {
+                                got: format!("{}", index_type),
+                                expected: format!("{}", usize),
+                            }
+                        });
+                    };
+                    return Ok((
+                        cir::Expression {
+                            span: expr.span,
+                            data: cir::ExpressionData::PointerSubscript {
+                                origin: Box::new(pointer.0),
+                                index: Box::new(index),
+                            },
+                            identity: ExpressionIdentity::Locatable(mutable),
+                        },
+                        *typ,
+                    ));
+                }
                 let (inner, inner_type) = self.typecheck_expr(inner, function_call)?;
                 let inner_span = inner.span;
 
@@ -791,7 +1595,7 @@ impl SemanticChecker {
                         span: origin.span,
                         case: SemanticErrorCase::IndexNonIndexable {
                             thing: format!("{origin}"),
-                            got: format!("{origin_type}"),
+                            got: format!("{}", origin_type),
                         }
                     })
                 }
@@ -799,13 +1603,18 @@ impl SemanticChecker {
             ast::ExpressionData::FieldAccess { origin, field } => {
                 if let ast::ExpressionData::Identifier { name } = &origin.data {
                     if let Some((_, e)) = self.namespace.enums.get(name) {
-                        let mut found = false;
-                        for i in e.variants.iter() {
-                            if i == field {
-                                found = true;
-                            }
+                        // Enum.count is the number of variants, unless a variant has that name
+                        if field == "count" && e.value_of(field).is_none() {
+                            return Ok((
+                                cir::Expression {
+                                    span: expr.span,
+                                    data: cir::ExpressionData::Integer { value: e.variants.len() as i128 },
+                                    identity: ExpressionIdentity::Immediate,
+                                },
+                                cir::Typing::Primitive(Primitive::UInt),
+                            ));
                         }
-                        if !found {
+                        if e.value_of(field).is_none() {
                             return Err(SemanticError {
                                 span: origin.span,
                                 case: SemanticErrorCase::UnknownEnumVariant {
@@ -814,11 +1623,22 @@ impl SemanticChecker {
                                 }
                             })
                         }
+                        if let Some(payload) = e.payload_of(field) {
+                            return Err(SemanticError {
+                                span: expr.span,
+                                case: SemanticErrorCase::UnmatchedArguments {
+                                    function: format!("{}.{}", e.name, field),
+                                    got: 0,
+                                    expected: payload.fields().len(),
+                                    too_much: false,
+                                },
+                            });
+                        }
                         return Ok((
                             cir::Expression {
                                 span: expr.span,
                                 data: cir::ExpressionData::EnumVariant {
-                                    origin: e.name.clone(),
+                                    origin: e.clone(),
                                     variant: field.clone()
                                 },
                                 identity: ExpressionIdentity::Immediate,
@@ -862,6 +1682,29 @@ impl SemanticChecker {
                             typ.unwrap(),
                         ));
                     }
+                    cir::Typing::Slice { .. } => {
+                        // The only field of a slice is its length, which can't be assigned
+                        if field != "len" {
+                            return Err(SemanticError {
+                                span: expr.span,
+                                case: SemanticErrorCase::UnresolvedField {
+                                    name: format!("{field}"),
+                                    from: format!("{}", typing),
+                                },
+                            });
+                        }
+                        return Ok((
+                            cir::Expression {
+                                span: expr.span,
+                                data: cir::ExpressionData::FieldAccess {
+                                    origin: Box::new(expression),
+                                    field: field.clone(),
+                                },
+                                identity: ExpressionIdentity::Immediate,
+                            },
+                            cir::Typing::Primitive(cir::Primitive::UInt),
+                        ));
+                    }
                     _ => {
                         return Err(SemanticError {
                             span: origin.span,
@@ -876,30 +1719,124 @@ impl SemanticChecker {
             ast::ExpressionData::FunctionCall {
                 function,
                 arguments: caller_arguments,
+                spread,
+                named,
             } => {
+                if let ast::ExpressionData::FieldAccess { origin, field } = &function.data
+                    && let ast::ExpressionData::Identifier { name } = &origin.data
+                    && let Some((_, e)) = self.namespace.enums.get(name)
+                {
+                    let e = e.clone();
+                    if e.value_of(field).is_some() {
+                        return self.typecheck_variant_init(&e, field, caller_arguments, spread, named, expr.span);
+                    }
+                    if field == "variants" && caller_arguments.is_empty() && named.is_empty() && spread.is_none() {
+                        return self.typecheck_enum_variants(&e, expr.span);
+                    }
+                }
+                // value.name() is the name of the variant of an enum value
+                if let ast::ExpressionData::FieldAccess { origin, field } = &function.data
+                    && field == "name"
+                    && caller_arguments.is_empty() && named.is_empty() && spread.is_none()
+                {
+                    let (checked, typing) = self.typecheck_expr(origin, false)?;
+                    if let cir::Typing::Enum(e) = typing {
+                        return Ok((
+                            cir::Expression {
+                                span: expr.span,
+                                data: cir::ExpressionData::EnumName { expr: Box::new(checked), origin: e },
+                                identity: ExpressionIdentity::Immediate,
+                            },
+                            cir::Typing::Primitive(Primitive::Str),
+                        ));
+                    }
+                }
                 let (function, function_type) = self.typecheck_expr(function, true)?;
                 let span = function.span;
-                if let cir::Typing::Function { ret, arguments, variadic, extrn: _ } = function_type {
+                if let cir::Typing::Function { ret, arguments, variadic, extrn } = function_type {
                     return self.typecheck_function_call(
                         function,
                         *ret,
                         &arguments,
                         variadic,
+                        extrn,
                         caller_arguments,
+                        spread,
+                        named,
                         span,
                     );
                 } else if let cir::Typing::Intrinsic(intrinsic) = function_type {
+                    if let Some(spread) = spread {
+                        return Err(SemanticError {
+                            span: spread.span,
+                            case: SemanticErrorCase::InvalidSpread { function: format!("{intrinsic}") },
+                        });
+                    }
+                    if let Some(ast::Field { name, value }) = named.first() {
+                        return Err(SemanticError {
+                            span: value.span,
+                            case: SemanticErrorCase::UnknownArgument {
+                                function: format!("{intrinsic}"),
+                                name: name.clone(),
+                            },
+                        });
+                    }
                     return self.typecheck_intrinsic_call(intrinsic, caller_arguments, span);
                 } else {
                     return Err(SemanticError {
                         span: expr.span,
                         case: SemanticErrorCase::CallNonFunction {
-                            typ: format!("{function_type}"),
+                            typ: format!("{}", function_type),
                         },
                     });
                 }
             }
-            ast::ExpressionData::StructInit { name, fields } => {
+            ast::ExpressionData::Closure { arguments, ret, block } => {
+                let mut validated_args = Vec::new();
+                let mut scope = HashMap::new();
+                for a in arguments.iter() {
+                    let typing = self.check_type(&a.typing)?;
+                    let mutable = matches!(typing, cir::Typing::Pointer { mutable: true, .. });
+                    // The values passed by the caller live until the call returns
+                    let lifetime = typing.holds_temporaries().then_some(self.namespace.locals.len());
+                    scope.insert(a.name.clone(), Variable { mutable, typing: typing.clone(), lifetime });
+                    validated_args.push((a.name.clone(), typing));
+                }
+                let validated_ret_type = match ret {
+                    Some(ret_type) => self.check_type(ret_type)?,
+                    None => cir::Typing::Void,
+                };
+
+                let name = String::from("anonymous function");
+                let function = std::mem::take(&mut self.current_function);
+                self.captures.push((self.namespace.locals.len(), Vec::new()));
+                let validated_block = self.typecheck_function_block(block.content.clone(), &validated_ret_type, &name, scope);
+                let (_, captures) = self.captures.pop().unwrap();
+                self.current_function = function;
+                let validated_block = validated_block?;
+                self.controlcheck_function_block(expr.span, &validated_block, &name, &validated_ret_type)?;
+
+                let typing = cir::Typing::Function {
+                    ret: Box::new(validated_ret_type.clone()),
+                    arguments: validated_args.iter().map(|(_, typ)| typ.clone()).collect(),
+                    variadic: false,
+                    extrn: false,
+                };
+                return Ok((
+                    cir::Expression {
+                        span: expr.span,
+                        data: cir::ExpressionData::Closure {
+                            arguments: validated_args,
+                            ret: validated_ret_type,
+                            captures,
+                            block: validated_block,
+                        },
+                        identity: ExpressionIdentity::Immediate,
+                    },
+                    typing,
+                ));
+            }
+            ast::ExpressionData::StructInit { name, fields, base } => {
                 let span = expr.span;
                 let (_, strukt) = self
                     .namespace
@@ -912,8 +1849,17 @@ impl SemanticChecker {
                         },
                     })?
                     .clone();
-                let mut checked_fields = Vec::new();
+                let mut checked_fields: Vec<cir::Field> = Vec::new();
                 for field in fields {
+                    if checked_fields.iter().any(|(name, _)| name == &field.name) {
+                        return Err(SemanticError {
+                            span: field.value.span,
+                            case: SemanticErrorCase::DuplicateField {
+                                name: strukt.name.clone(),
+                                field: field.name.clone(),
+                            },
+                        });
+                    }
                     let expected_typing =
                         struct_field_linear_search(&strukt.fields, &field.name).ok_or_else(|| SemanticError {
                             span: span,
@@ -927,18 +1873,75 @@ impl SemanticChecker {
                     if let Some(expr) = self.make_inference(expr, &typing, &expected_typing) {
                         checked_fields.push((field.name.clone(), expr));
                     } else {
+                        let alias = self.namespace.written.get(&Declaration::Field(strukt.name.clone(), field.name.clone()));
                         return Err(SemanticError {
                             span: field_value_span,
+                            case: SemanticErrorCase::TypeMismatch {
+                                got: format!("{}", typing),
+                                expected: self.written_type_name(&expected_typing, alias),
+                            },
+                        });
+                    }
+                }
+                let mut checked_base = None;
+                if let Some(base) = base {
+                    let (expr, typing) = self.typecheck_expr(base, function_call)?;
+                    let expected_typing = cir::Typing::Struct(strukt.clone());
+                    if typing != expected_typing {
+                        return Err(SemanticError {
+                            span: base.span,
                             case: SemanticErrorCase::TypeMismatch {
                                 got: format!("{}", typing),
                                 expected: format!("{}", expected_typing),
                             },
                         });
                     }
+                    checked_base = Some(Box::new(expr));
+                }
+                if strukt.layout.union {
+                    // Only one field of a union is initialized, by its default if none is given
+                    if base.is_none() && checked_fields.is_empty() {
+                        if let Some(defaults) = self.namespace.field_defaults.get(&strukt.name) {
+                            checked_fields.extend(defaults.iter().cloned());
+                        }
+                    }
+                    let valid = if base.is_some() { checked_fields.len() <= 1 } else { checked_fields.len() == 1 };
+                    if !valid {
+                        return Err(SemanticError {
+                            span,
+                            case: SemanticErrorCase::UnionFieldCount { name: strukt.name.clone(), got: checked_fields.len() },
+                        });
+                    }
+                } else if base.is_none() {
+                    // Without a base, the fields that were not given take their default values
+                    let mut missing = Vec::new();
+                    for (field, _) in &strukt.fields {
+                        if checked_fields.iter().any(|(f, _)| f == field) {
+                            continue;
+                        }
+                        let default = self.namespace.field_defaults
+                            .get(&strukt.name)
+                            .and_then(|defaults| defaults.iter().find(|(f, _)| f == field));
+                        if let Some(default) = default {
+                            checked_fields.push(default.clone());
+                        } else {
+                            missing.push(field.clone());
+                        }
+                    }
+                    if !missing.is_empty() {
+                        return Err(SemanticError {
+                            span,
+                            case: SemanticErrorCase::MissingFields {
+                                name: strukt.name.clone(),
+                                fields: missing,
+                            },
+                        });
+                    }
                 }
                 let thing = cir::ExpressionData::StructInit {
                     origin: strukt.clone(),
                     fields: checked_fields,
+                    base: checked_base,
                 };
                 Ok((
                     cir::Expression {
@@ -971,6 +1974,67 @@ impl SemanticChecker {
                     cir::Typing::Primitive(cir::Primitive::Float),
                 ))
             }
+            ast::ExpressionData::If { condition, block_true, block_false } => {
+                let (condition, typing) = self.typecheck_expr(condition, false)?;
+                if typing != cir::Typing::Primitive(cir::Primitive::Bool) {
+                    return Err(SemanticError {
+                        span: condition.span,
+                        case: SemanticErrorCase::TypeMismatch {
+                            got: format!("{}", typing),
+                            expected: format!("{}", cir::Typing::Primitive(cir::Primitive::Bool)),
+                        },
+                    });
+                }
+                let r#true = self.typecheck_value_block(block_true, expr.span)?;
+                let r#false = self.typecheck_value_block(block_false, expr.span)?;
+                let (typ, mut branches) = self.unify_branches(vec![r#true, r#false])?;
+                let r#false = branches.pop().unwrap();
+                let r#true = branches.pop().unwrap();
+                return Ok((
+                    cir::Expression {
+                        span: expr.span,
+                        data: cir::ExpressionData::If {
+                            condition: Box::new(condition),
+                            r#true: Box::new(r#true),
+                            r#false: Box::new(r#false),
+                            typ: typ.clone(),
+                        },
+                        identity: ExpressionIdentity::Immediate,
+                    },
+                    typ,
+                ));
+            }
+            ast::ExpressionData::Match { value, arms } => {
+                return self.typecheck_match(value, arms, expr.span, true);
+            }
+            ast::ExpressionData::TypeArgument { .. } => {
+                return Err(SemanticError {
+                    span: expr.span,
+                    case: SemanticErrorCase::TypeMismatch { got: "type".to_string(), expected: "value".to_string() },
+                });
+            }
+            ast::ExpressionData::Comptime { expr: inner } => {
+                let (checked, typ) = self.typecheck_expr(inner, false)?;
+                if typ == cir::Typing::Void || !consteval::is_evaluable(&typ) {
+                    return Err(SemanticError {
+                        span: inner.span,
+                        case: SemanticErrorCase::NotConstant { what: format!("{checked}") },
+                    });
+                }
+                let mut evaluator = Evaluator::new(&self.namespace.constant_values, &self.namespace.function_bodies, self.target);
+                let (value, _) = evaluator.evaluate(&checked)?;
+                let mut literal = value.to_expression(&checked, &typ);
+                literal.span = expr.span;
+                return Ok((literal, typ));
+            }
+            ast::ExpressionData::NullLiteral => Ok((
+                cir::Expression {
+                    span: expr.span,
+                    data: cir::ExpressionData::Null,
+                    identity: ExpressionIdentity::Immediate,
+                },
+                cir::Typing::Primitive(cir::Primitive::RawPtr),
+            )),
             ast::ExpressionData::BooleanLiteral { value } => Ok((
                 cir::Expression {
                     span: expr.span,
@@ -1024,18 +2088,49 @@ impl SemanticChecker {
                     // to find the variable.
                     // This is because the most recent scope should take precedence.
                     // If the variable is not found, return an error.
-                    for i in self.namespace.locals.iter().rev() {
+                    for (depth, i) in self.namespace.locals.iter().enumerate().rev() {
                         if let Some(var) = i.get(name) {
+                            // Variables from outside of a closure are captured by copy, so they can't be assigned
+                            let mut mutable = var.mutable;
+                            for (outer_scopes, captures) in self.captures.iter_mut() {
+                                if depth < *outer_scopes {
+                                    mutable = false;
+                                    if !captures.iter().any(|(n, _)| n == name) {
+                                        captures.push((name.clone(), var.typing.clone()));
+                                    }
+                                }
+                            }
                             return Ok((
                                 cir::Expression {
                                     span: expr.span,
                                     data: cir::ExpressionData::Identifier { name: name.clone() },
-                                    identity: ExpressionIdentity::Locatable(var.mutable),
+                                    identity: ExpressionIdentity::Locatable(mutable),
                                 },
                                 var.typing.clone()
                             ));
                         }
                     }
+                    // Globals are shared by everyone, so closures use them directly instead of capturing them
+                    if let Some((_, var)) = self.namespace.globals.get(name) {
+                        return Ok((
+                            cir::Expression {
+                                span: expr.span,
+                                data: cir::ExpressionData::Identifier { name: name.clone() },
+                                identity: ExpressionIdentity::Locatable(var.mutable),
+                            },
+                            var.typing.clone()
+                        ));
+                    }
+                    if let Some((_, var)) = self.namespace.externs.get(name) {
+                        return Ok((
+                            cir::Expression {
+                                span: expr.span,
+                                data: cir::ExpressionData::ExternVariable { name: name.clone() },
+                                identity: ExpressionIdentity::Locatable(var.mutable),
+                            },
+                            var.typing.clone()
+                        ));
+                    }
                     return Err(SemanticError {
                         span: expr.span,
                         case: SemanticErrorCase::UnresolvedName { name: name.clone() },
@@ -1066,6 +2161,225 @@ impl SemanticChecker {
         Ok(blk)
     }
 
+    // Check a branch of an if or match expression, whose value is its last expression
+    fn typecheck_value_block(&mut self, block: &ast::Block, span: Span) -> Result<cir::TypedExpression, SemanticError> {
+        let mut content = block.content.clone();
+        let span = content.last().map_or(span, |node| node.span);
+        let value = content.pop().and_then(block_value).ok_or(SemanticError {
+            span,
+            case: SemanticErrorCase::MissingBlockValue,
+        })?;
+        if content.is_empty() {
+            return self.typecheck_expr(&value, false);
+        }
+
+        let return_type = self.return_type.clone();
+        self.namespace.locals.push(HashMap::new());
+        let mut block = Vec::new();
+        for node in content {
+            match self.typecheck_node(node, return_type.as_ref()) {
+                Ok(x) => block.push(x),
+                Err(e) => {
+                    self.namespace.locals.pop();
+                    return Err(e);
+                }
+            }
+        }
+        let value = self.typecheck_expr(&value, false);
+        // The temporaries of the value are created in the enclosing scope, but those of the statements
+        // are released when the block is exited
+        let outer = self.namespace.locals.len().checked_sub(2);
+        let short_lived = value.as_ref().is_ok_and(|(value, typ)| typ.holds_temporaries() && self.lifetime(value, outer) > outer);
+        self.namespace.locals.pop();
+        let (value, typ) = value?;
+        if short_lived {
+            return Err(SemanticError {
+                span: value.span,
+                case: SemanticErrorCase::ShortLivedValue { what: String::from("value of the block") },
+            });
+        }
+        Ok((
+            cir::Expression {
+                span: value.span,
+                data: cir::ExpressionData::Block { block, value: Some(Box::new(value)) },
+                identity: ExpressionIdentity::Immediate,
+            },
+            typ,
+        ))
+    }
+
+    // Find the type all the branches of an if or match expression can be converted to,
+    // and convert them to it
+    fn unify_branches(&self, branches: Vec<cir::TypedExpression>) -> Result<(cir::Typing, Vec<cir::Expression>), SemanticError> {
+        let mut typ = branches[0].1.clone();
+        for (branch, typing) in &branches[1..] {
+            if !matches!(self.typecheck_inference(typing, &typ), Inference::Invalid) {
+                continue;
+            }
+            if matches!(self.typecheck_inference(&typ, typing), Inference::Invalid) {
+                return Err(SemanticError {
+                    span: branch.span,
+                    case: SemanticErrorCase::TypeMismatch {
+                        got: format!("{}", typing),
+                        expected: format!("{}", typ),
+                    },
+                });
+            }
+            typ = typing.clone();
+        }
+        let mut result = Vec::new();
+        for (mut branch, typing) in branches {
+            // The conversion applies to the value of the block, since the block itself is not a C expression
+            if let cir::ExpressionData::Block { value: Some(value), .. } = &mut branch.data {
+                **value = self.make_inference((**value).clone(), &typing, &typ).unwrap();
+            } else {
+                branch = self.make_inference(branch, &typing, &typ).unwrap();
+            }
+            result.push(branch);
+        }
+        Ok((typ, result))
+    }
+
+    fn typecheck_match(
+        &mut self,
+        value: &ast::Expression,
+        arms: &Vec<ast::MatchArm>,
+        span: Span,
+        produces_value: bool,
+    ) -> Result<cir::TypedExpression, SemanticError> {
+        let (value, typing) = self.typecheck_expr(value, false)?;
+        let matched = self.value_lifetime(&value, &typing);
+        if !(typing.is_integer() || typing.is_bool()
+            || matches!(typing, cir::Typing::Enum(_) | cir::Typing::Primitive(cir::Primitive::Char)))
+        {
+            return Err(SemanticError {
+                span: value.span,
+                case: SemanticErrorCase::UnmatchableType { typ: format!("{}", typing) },
+            });
+        }
+
+        // The patterns of an enum with payloads are its variants, which can bind the payload
+        let tagged = match &typing {
+            cir::Typing::Enum(e) if e.is_tagged() => Some(e.clone()),
+            _ => None,
+        };
+
+        let mut patterns = Vec::new();
+        let mut branches = Vec::new();
+        for (i, arm) in arms.iter().enumerate() {
+            let mut bindings = Vec::new();
+            let pattern = match &arm.pattern {
+                Some(pattern) if let Some(e) = &tagged => {
+                    let (pattern, variables) = self.typecheck_variant_pattern(e, pattern)?;
+                    bindings = variables;
+                    Some(pattern)
+                }
+                Some(pattern) => {
+                    let (pattern, pattern_typing) = self.typecheck_expr(pattern, false)?;
+                    let pattern_span = pattern.span;
+                    let pattern = self.make_inference(pattern, &pattern_typing, &typing).ok_or(SemanticError {
+                        span: pattern_span,
+                        case: SemanticErrorCase::TypeMismatch {
+                            got: format!("{}", pattern_typing),
+                            expected: format!("{}", typing),
+                        },
+                    })?;
+                    Some(pattern)
+                }
+                None if i + 1 < arms.len() => {
+                    let span = arm.block.content.first().map_or(span, |node| node.span);
+                    return Err(SemanticError { span, case: SemanticErrorCase::MisplacedElseArm });
+                }
+                None => None,
+            };
+            patterns.push(pattern);
+
+            // The bound fields are variables of the arm, declared before its statements
+            let mut scope = HashMap::new();
+            let mut declarations = Vec::new();
+            for (binding, (assignment, typing)) in bindings {
+                // The payloads are copied from the matched value, so they refer to the same temporaries
+                scope.insert(binding.clone(), Variable { mutable: false, typing: typing.clone(), lifetime: matched });
+                declarations.push(cir::Statement {
+                    span: assignment.span,
+                    kind: cir::StatementKind::Variable { assignment, binding, typing },
+                });
+            }
+            self.namespace.locals.push(scope);
+            let branch = if produces_value {
+                self.typecheck_value_block(&arm.block, span)
+            } else {
+                self.typecheck_block(arm.block.content.clone(), self.return_type.clone().as_ref()).map(|block| {
+                    let branch = cir::Expression {
+                        span,
+                        data: cir::ExpressionData::Block { block, value: None },
+                        identity: ExpressionIdentity::Immediate,
+                    };
+                    (branch, cir::Typing::Void)
+                })
+            };
+            self.namespace.locals.pop();
+            let (mut branch, typing) = branch?;
+            if !declarations.is_empty() {
+                branch = match branch.data {
+                    cir::ExpressionData::Block { mut block, value } => {
+                        declarations.append(&mut block);
+                        cir::Expression { data: cir::ExpressionData::Block { block: declarations, value }, ..branch }
+                    }
+                    _ => cir::Expression {
+                        span: branch.span,
+                        data: cir::ExpressionData::Block { block: declarations, value: Some(Box::new(branch)) },
+                        identity: ExpressionIdentity::Immediate,
+                    },
+                };
+            }
+            branches.push((branch, typing));
+        }
+
+        // Without an else arm, the patterns must cover every possible value
+        if patterns.last().is_none_or(|p| p.is_some()) {
+            let missing = match &typing {
+                cir::Typing::Enum(e) => {
+                    let missing = e.variants.iter().filter(|(variant, _)| !patterns.iter().any(|p| matches!(
+                        p,
+                        Some(cir::Expression { data: cir::ExpressionData::EnumVariant { variant: v, .. }, .. }) if v == variant
+                    ))).map(|(variant, _)| format!("{}.{}", e.name, variant)).collect::<Vec<String>>();
+                    missing.join(", ")
+                }
+                _ if typing.is_bool() => {
+                    let missing = [true, false].iter().filter(|b| !patterns.iter().any(|p| matches!(
+                        p,
+                        Some(cir::Expression { data: cir::ExpressionData::Bool { value }, .. }) if value == *b
+                    ))).map(|b| b.to_string()).collect::<Vec<String>>();
+                    missing.join(", ")
+                }
+                _ => format!("every {} value", typing),
+            };
+            if !missing.is_empty() {
+                return Err(SemanticError { span, case: SemanticErrorCase::NonExhaustiveMatch { missing } });
+            }
+        }
+
+        let (typ, branches) = if produces_value {
+            self.unify_branches(branches)?
+        } else {
+            (cir::Typing::Void, branches.into_iter().map(|(branch, _)| branch).collect())
+        };
+        Ok((
+            cir::Expression {
+                span,
+                data: cir::ExpressionData::Match {
+                    value: Box::new(value),
+                    value_typ: typing,
+                    arms: patterns.into_iter().zip(branches).collect(),
+                    typ: typ.clone(),
+                },
+                identity: ExpressionIdentity::Immediate,
+            },
+            typ,
+        ))
+    }
+
     fn typecheck_function_block(
         &mut self,
         block: Vec<ast::Node>,
@@ -1074,6 +2388,7 @@ impl SemanticChecker {
         function_arguments: HashMap<String, Variable>,
     ) -> Result<Vec<cir::Statement>, SemanticError> {
         self.current_function = function_name.to_string();
+        let outer_return_type = self.return_type.replace(return_type.clone());
         let mut blk = Vec::new();
 
         // Create a new scope for the function
@@ -1086,12 +2401,14 @@ impl SemanticChecker {
                 Ok(x) => blk.push(x),
                 Err(e) => {
                     self.namespace.locals.pop();
+                    self.return_type = outer_return_type;
                     return Err(e);
                 }
             }
         }
 
         self.namespace.locals.pop();
+        self.return_type = outer_return_type;
         Ok(blk)
     }
 
@@ -1136,6 +2453,70 @@ impl SemanticChecker {
         Ok(())
     }
 
+    fn typecheck_global(
+        &mut self,
+        span: Span,
+        name: &str,
+        assignment: &ast::Expression,
+        mutable: bool,
+    ) -> Result<cir::Statement, SemanticError> {
+        if let Some(s) = self.check_name_availability(name) {
+            return Err(SemanticError { span, case: SemanticErrorCase::NameRedefinition {
+                name: name.to_string(),
+                defined: s
+            }})
+        }
+        let (expr, typ) = self.typecheck_expr(assignment, false)?;
+        // The temporaries created outside of functions are never released
+        self.namespace.globals.insert(name.to_string(), (span, Variable { mutable, typing: typ.clone(), lifetime: None }));
+        Ok(cir::Statement {
+            span,
+            kind: cir::StatementKind::Global {
+                binding: name.to_string(),
+                assignment: expr,
+                typing: typ,
+            }
+        })
+    }
+
+    // If there is a C symbol declared or exported by the program, return the span of the definition
+    fn check_symbol_availability(&self, symbol: &str) -> Option<Span> {
+        if let Some((span, _)) = self.namespace.exports.values().find(|(_, s)| s == symbol) {
+            return Some(*span)
+        } else if let Some((span, head)) = self.namespace.functions.get(symbol) && head.extrn {
+            return Some(*span)
+        } else if let Some((span, _)) = self.namespace.externs.get(symbol) {
+            return Some(*span)
+        }
+        None
+    }
+
+    fn check_export(&self, span: Span, head: &cir::FunctionHead, symbol: &str) -> Result<(), SemanticError> {
+        let identifier = symbol.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && symbol.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !identifier || symbol == "main" || symbol.starts_with("__elo_") {
+            return Err(SemanticError { span, case: SemanticErrorCase::InvalidSymbol { symbol: symbol.to_string() } });
+        }
+        if let Some(defined) = self.check_symbol_availability(symbol) {
+            return Err(SemanticError { span, case: SemanticErrorCase::NameRedefinition {
+                name: symbol.to_string(),
+                defined
+            }})
+        }
+        // C can't pass arrays by value, only inside of structs
+        let types = head.arguments.iter().map(|(_, t)| t).chain([&head.ret]);
+        for typ in types {
+            let passable = *typ == cir::Typing::Void || (typ.is_c_compatible() && !matches!(typ, cir::Typing::Array { .. }));
+            if !passable {
+                return Err(SemanticError { span, case: SemanticErrorCase::NonCType {
+                    function: head.name.clone(),
+                    typ: format!("{}", typ),
+                }})
+            }
+        }
+        Ok(())
+    }
+
     // If there is a name, return the span of the definition
     fn check_name_availability(&self, name: &str) -> Option<Span> {
         if let Some((span, _)) = self.namespace.functions.get(name) {
@@ -1146,20 +2527,45 @@ impl SemanticChecker {
             return Some(*span)
         } else if let Some((span, _)) = self.namespace.constants.get(name) {
             return Some(*span)
+        } else if let Some((span, _)) = self.namespace.aliases.get(name) {
+            return Some(*span)
+        } else if let Some((span, _)) = self.namespace.globals.get(name) {
+            return Some(*span)
+        } else if let Some((span, _)) = self.namespace.externs.get(name) {
+            return Some(*span)
         }
         None
     }
 
+    // Name of a type for the diagnostics, through the alias it was written with, if any.
+    // Aliases are the names the user knows the types by, but only where they are written.
+    fn written_type_name(&self, typ: &cir::Typing, alias: Option<&String>) -> String {
+        match alias {
+            Some(alias) => format!("{alias} (aka {typ})"),
+            None => format!("{}", typ),
+        }
+    }
+
+    // The alias a type annotation is written through, like Id in `x: Id`
+    fn written_alias(&self, typ: &ast::Type) -> Option<String> {
+        match &typ.typing {
+            ast::Typing::Named { name, .. } if self.namespace.aliases.contains_key(name) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    // Keep the alias the type of a declaration is written through (see Namespace::written)
+    fn remember_alias(&mut self, declaration: Declaration, typ: &ast::Type) {
+        if let Some(alias) = self.written_alias(typ) {
+            self.namespace.written.insert(declaration, alias);
+        }
+    }
+
     fn typecheck_node(&mut self, node: ast::Node, expects_return: Option<&cir::Typing>) -> Result<cir::Statement, SemanticError> {
         match node.stmt {
             ast::Statement::LetStatement(stmt) => {
                 if expects_return.is_none() {
-                    return Err(SemanticError {
-                        span: node.span,
-                        case: SemanticErrorCase::TopLevelImperativeStatement {
-                            statement: String::from("let statement")
-                        },
-                    });
+                    return self.typecheck_global(node.span, &stmt.binding, &stmt.assignment, false);
                 }
 
                 let assignment = &stmt.assignment;
@@ -1175,6 +2581,7 @@ impl SemanticChecker {
                 }
 
                 let (expr, typ) = self.typecheck_expr(assignment, false)?;
+                let lifetime = self.value_lifetime(&expr, &typ);
 
                 // Add the variable to the current scope
                 self.namespace.locals.last_mut().unwrap().insert(
@@ -1182,6 +2589,7 @@ impl SemanticChecker {
                     Variable {
                         mutable: false,
                         typing: typ.clone(),
+                        lifetime,
                     },
                 );
                 Ok(
@@ -1197,12 +2605,7 @@ impl SemanticChecker {
             }
             ast::Statement::VarStatement(stmt) => {
                 if expects_return.is_none() {
-                    return Err(SemanticError {
-                        span: node.span,
-                        case: SemanticErrorCase::TopLevelImperativeStatement {
-                            statement: String::from("var statement")
-                        },
-                    });
+                    return self.typecheck_global(node.span, &stmt.binding, &stmt.assignment, true);
                 }
 
                 let assignment = &stmt.assignment;
@@ -1219,11 +2622,13 @@ impl SemanticChecker {
                 }
 
                 // Add the variable to the current scope
+                let lifetime = self.value_lifetime(&expr, &typ);
                 self.namespace.locals.last_mut().unwrap().insert(
                     name.clone(),
                     Variable {
                         mutable: true,
                         typing: typ.clone(),
+                        lifetime,
                     },
                 );
                 Ok(cir::Statement {
@@ -1240,25 +2645,80 @@ impl SemanticChecker {
                 let name = &stmt.binding;
                 let (expr, typ) = self.typecheck_expr(assignment, false)?;
                 let annotated = self.check_type(&stmt.typing)?;
-                self.namespace.constants.insert(name.clone(), (node.span, typ.clone()));
-                if let Some(expr) = self.make_inference(expr, &typ, &annotated) {
-                    return Ok(cir::Statement {
-                        span: node.span,
-                        kind: cir::StatementKind::Constant {
-                            value: expr,
-                            binding: name.clone(),
-                            typing: typ,
-                        }
-                    })
-                } else {
+                let Some(expr) = self.make_inference(expr, &typ, &annotated) else {
                     return Err(SemanticError {
                         span: stmt.typing.span,
                         case: SemanticErrorCase::TypeMismatch {
                             got: format!("{}", typ),
-                            expected: format!("{}", annotated),
+                            expected: self.written_type_name(&annotated, self.written_alias(&stmt.typing).as_ref()),
+                        },
+                    });
+                };
+
+                // The value is evaluated into a literal, calling the functions it uses at compile time.
+                // Values that can't be literals (like pointers) are left for C to initialize.
+                let mut evaluator = Evaluator::new(&self.namespace.constant_values, &self.namespace.function_bodies, self.target);
+                let value = match evaluator.evaluate(&expr) {
+                    Ok((value, _)) => {
+                        let literal = value.to_expression(&expr, &annotated);
+                        self.namespace.constant_values.insert(name.clone(), (value, annotated.clone()));
+                        literal
+                    }
+                    Err(e) if consteval::is_evaluable(&annotated) => return Err(e),
+                    Err(_) => expr,
+                };
+                self.namespace.constants.insert(name.clone(), (node.span, annotated.clone()));
+                return Ok(cir::Statement {
+                    span: node.span,
+                    kind: cir::StatementKind::Constant {
+                        value,
+                        binding: name.clone(),
+                        typing: annotated,
+                    }
+                })
+            }
+            ast::Statement::StaticAssertStatement(stmt) => {
+                let (condition, typ) = self.typecheck_expr(&stmt.condition, false)?;
+                if !typ.is_bool() {
+                    return Err(SemanticError {
+                        span: stmt.condition.span,
+                        case: SemanticErrorCase::TypeMismatch {
+                            got: format!("{}", typ),
+                            expected: format!("{}", cir::Typing::Primitive(Primitive::Bool)),
+                        },
+                    });
+                }
+                let mut evaluator = Evaluator::new(&self.namespace.constant_values, &self.namespace.function_bodies, self.target);
+                if let (Value::Bool(false), _) = evaluator.evaluate(&condition)? {
+                    return Err(SemanticError {
+                        span: node.span,
+                        case: SemanticErrorCase::StaticAssertFailed { message: stmt.message.clone() },
+                    });
+                }
+                return Ok(cir::Statement {
+                    span: node.span,
+                    kind: cir::StatementKind::StaticAssert { condition, message: stmt.message },
+                });
+            }
+            ast::Statement::DeferStatement(stmt) => {
+                if expects_return.is_none() {
+                    return Err(SemanticError {
+                        span: node.span,
+                        case: SemanticErrorCase::TopLevelImperativeStatement {
+                            statement: String::from("defer statement")
                         },
                     });
                 }
+
+                let block = self.typecheck_block(stmt.block.content, expects_return)?;
+                // The deferred code runs while the function is already returning
+                if let Some(span) = find_return(&block) {
+                    return Err(SemanticError { span, case: SemanticErrorCase::ReturnInDefer });
+                }
+                return Ok(cir::Statement {
+                    span: node.span,
+                    kind: cir::StatementKind::DeferStatement { block },
+                });
             }
             ast::Statement::ReturnStatement(stmt) => {
                 if expects_return.is_none() {
@@ -1273,10 +2733,10 @@ impl SemanticChecker {
                 if expects_return.is_none() && stmt.expr.is_some() {
                     return Err(SemanticError { span: node.span, case: SemanticErrorCase::MisplacedReturn })
                 }
-                if expects_return.is_some() && stmt.expr.is_none() {
+                if let Some(typ) = expects_return && *typ != cir::Typing::Void && stmt.expr.is_none() {
                     return Err(SemanticError { span: node.span, case: SemanticErrorCase::MissingReturnValue {
                         fn_name: self.current_function.clone(),
-                        typ: format!("{}", expects_return.unwrap()),
+                        typ: format!("{}", typ),
                     }})
                 }
                 if let Some(expr) = &stmt.expr {
@@ -1300,7 +2760,7 @@ impl SemanticChecker {
                         return Err(SemanticError { span: node.span, case: SemanticErrorCase::MismatchedReturnType {
                             function: self.current_function.clone(),
                             got: format!("{}", typ),
-                            expected: format!("{}", expected_return),
+                            expected: self.written_type_name(&expected_return, self.namespace.written.get(&Declaration::Return(self.current_function.clone()))),
                         }});
                     }
                 }
@@ -1321,12 +2781,27 @@ impl SemanticChecker {
                 }
 
                 let mut validated_args = Vec::new();
+                let mut defaults = Vec::new();
                 for a in stmt.arguments.iter() {
-                    validated_args.push((a.name.clone(), self.check_type(&a.typing)?));
+                    let declaration = Declaration::Argument(stmt.name.clone(), a.name.clone());
+                    let mut checked_type = self.check_type(&a.typing)?;
+                    if stmt.variadic && std::ptr::eq(a, stmt.arguments.last().unwrap()) {
+                        // The variadic tail is seen as a slice inside the function
+                        checked_type = cir::Typing::Slice { typ: Box::new(checked_type) };
+                    } else {
+                        self.remember_alias(declaration.clone(), &a.typing);
+                    }
+                    if let Some(default) = &a.default {
+                        defaults.push((a.name.clone(), self.check_default(default, &checked_type, &declaration)?));
+                    }
+                    validated_args.push((a.name.clone(), checked_type));
                 }
 
                 let validated_ret_type = match &stmt.ret {
-                    Some(ret_type) => self.check_type(ret_type)?,
+                    Some(ret_type) => {
+                        self.remember_alias(Declaration::Return(stmt.name.clone()), ret_type);
+                        self.check_type(ret_type)?
+                    }
                     None => cir::Typing::Void,
                 };
 
@@ -1343,6 +2818,8 @@ impl SemanticChecker {
                         Variable {
                             mutable,
                             typing: typing.clone(),
+                            // The values passed by the caller live until the call returns
+                            lifetime: typing.holds_temporaries().then_some(self.namespace.locals.len()),
                         },
                     );
                 }
@@ -1351,13 +2828,19 @@ impl SemanticChecker {
                     name: stmt.name.clone(),
                     ret: validated_ret_type.clone(),
                     arguments: validated_args,
-                    variadic: false, // In this case, variadic is ALWAYS false
-                                     // Because Elo is not meant to support variadic functions at all.
-                    extrn: false     // The same for extrn which is meant to flag if this function should be mangled
+                    variadic: stmt.variadic, // Elo variadic functions take the extra arguments in a slice,
+                                             // which is their last argument, instead of C variadic arguments.
+                    extrn: false     // extrn is meant to flag if this function should be mangled
                 };
 
+                if let Some(symbol) = &stmt.export {
+                    self.check_export(node.span, &head, symbol)?;
+                    self.namespace.exports.insert(stmt.name.clone(), (node.span, symbol.clone()));
+                }
+
                 // Insert the function into the namespace
                 self.namespace.functions.insert(stmt.name.clone(), (node.span, head.clone()));
+                self.namespace.argument_defaults.insert(stmt.name.clone(), defaults);
 
                 let validated_block = self.typecheck_function_block(stmt.block.content, &validated_ret_type, &stmt.name, arguments)?;
                 self.controlcheck_function_block(node.span, &validated_block, &stmt.name, &validated_ret_type)?;
@@ -1365,7 +2848,9 @@ impl SemanticChecker {
                 let validated = cir::Function {
                     head: head.clone(),
                     block: validated_block,
+                    export: stmt.export.clone(),
                 };
+                self.namespace.function_bodies.insert(stmt.name.clone(), validated.clone());
 
                 return Ok(cir::Statement {
                     span: node.span,
@@ -1373,7 +2858,7 @@ impl SemanticChecker {
                 });
             }
             ast::Statement::ExternFnStatement(stmt) => {
-                if let Some(s) = self.check_name_availability(&stmt.name) {
+                if let Some(s) = self.check_name_availability(&stmt.name).or_else(|| self.check_symbol_availability(&stmt.name)) {
                     return Err(SemanticError { span: node.span, case: SemanticErrorCase::NameRedefinition {
                         name: stmt.name.clone(),
                         defined: s
@@ -1403,6 +2888,25 @@ impl SemanticChecker {
                     }
                 );
             }
+            ast::Statement::ExternVarStatement(stmt) => {
+                if let Some(s) = self.check_name_availability(&stmt.name).or_else(|| self.check_symbol_availability(&stmt.name)) {
+                    return Err(SemanticError { span: node.span, case: SemanticErrorCase::NameRedefinition {
+                        name: stmt.name.clone(),
+                        defined: s
+                    }})
+                }
+                let typing = self.check_type(&stmt.typing)?;
+                let variable = Variable { mutable: stmt.mutable, typing: typing.clone(), lifetime: None };
+                self.namespace.externs.insert(stmt.name.clone(), (node.span, variable));
+                return Ok(cir::Statement {
+                    span: node.span,
+                    kind: cir::StatementKind::ExternVarStatement {
+                        binding: stmt.name.clone(),
+                        typing,
+                        mutable: stmt.mutable,
+                    }
+                });
+            }
             ast::Statement::StructStatement(stmt) => {
                 if let Some(s) = self.check_name_availability(&stmt.name) {
                     return Err(SemanticError { span: node.span, case: SemanticErrorCase::NameRedefinition {
@@ -1412,15 +2916,41 @@ impl SemanticChecker {
                 }
 
                 let mut fields = Vec::new();
-                for TypedField { name, typing } in &stmt.fields {
+                let mut defaults = Vec::new();
+                let mut field_aligns = Vec::new();
+                let mut field_packed = Vec::new();
+                for TypedField { name, typing, default, packed, align, .. } in &stmt.fields {
+                    let declaration = Declaration::Field(stmt.name.clone(), name.clone());
                     let checked_type = self.check_type(typing)?;
+                    self.remember_alias(declaration.clone(), typing);
+                    if let Some(default) = default {
+                        defaults.push((name.clone(), self.check_default(default, &checked_type, &declaration)?));
+                    }
                     fields.push((name.clone(), checked_type));
+                    field_aligns.push(align.as_ref().map(|align| self.check_align(align)).transpose()?);
+                    field_packed.push(*packed);
+                }
+                // A union with defaults is initialized by them, so only one field can have a default
+                if stmt.union && defaults.len() > 1 {
+                    return Err(SemanticError {
+                        span: node.span,
+                        case: SemanticErrorCase::UnionFieldCount { name: stmt.name.clone(), got: defaults.len() },
+                    });
                 }
+                let layout = cir::Layout {
+                    union: stmt.union,
+                    packed: stmt.packed,
+                    align: stmt.align.as_ref().map(|align| self.check_align(align)).transpose()?,
+                    field_aligns,
+                    field_packed,
+                };
                 let e = cir::Struct {
                     name: stmt.name,
                     fields,
+                    layout,
                 };
                 self.namespace.structs.insert(e.name.clone(), (node.span, e.clone()));
+                self.namespace.field_defaults.insert(e.name.clone(), defaults);
                 return Ok(cir::Statement {
                     span: node.span,
                     kind: cir::StatementKind::StructStatement(e)
@@ -1434,16 +2964,91 @@ impl SemanticChecker {
                     }})
                 }
 
-                let e = cir::Enum {
+                let backing = match &stmt.backing {
+                    Some(typ) => match self.check_type(typ)? {
+                        cir::Typing::Primitive(p) if cir::Typing::Primitive(p).is_integer() => Some(p),
+                        other => {
+                            return Err(SemanticError {
+                                span: typ.span,
+                                case: SemanticErrorCase::TypeMismatch {
+                                    got: format!("{}", other),
+                                    expected: "integer".to_string(),
+                                },
+                            });
+                        }
+                    },
+                    None => None,
+                };
+                let mut e = cir::Enum {
                     name: stmt.name,
-                    variants: stmt.variants,
+                    variants: Vec::new(),
+                    backing,
+                    payloads: Vec::new(),
                 };
+                // Like in C, the variants without a value take the one after the previous variant
+                let mut next = 0;
+                for variant in &stmt.variants {
+                    let (value, span) = match &variant.value {
+                        Some(value) => (self.check_discriminant(value)?, value.span),
+                        None => (next, node.span),
+                    };
+                    let (min, max) = consteval::integer_range(&e.backing_type(), &self.target).unwrap();
+                    if value < min || value > max {
+                        return Err(SemanticError {
+                            span,
+                            case: SemanticErrorCase::ConstantOverflow { typ: format!("{}", e.backing_type()) },
+                        });
+                    }
+                    if let Some((other, _)) = e.variants.iter().find(|(_, v)| *v == value) {
+                        return Err(SemanticError {
+                            span,
+                            case: SemanticErrorCase::DuplicateDiscriminant {
+                                enumeration: e.name.clone(),
+                                variant: variant.name.clone(),
+                                other: other.clone(),
+                                value: value.to_string(),
+                            },
+                        });
+                    }
+                    e.variants.push((variant.name.clone(), value));
+                    e.payloads.push(match &variant.payload {
+                        Some(ast::Payload::Tuple(types)) if !types.is_empty() => {
+                            let types = types.iter().map(|typ| self.check_type(typ)).collect::<Result<_, _>>()?;
+                            Some(cir::Payload::Tuple(types))
+                        }
+                        Some(ast::Payload::Struct(fields)) if !fields.is_empty() => {
+                            let mut checked = Vec::new();
+                            for TypedField { name, typing, .. } in fields {
+                                checked.push((name.clone(), self.check_type(typing)?));
+                            }
+                            Some(cir::Payload::Struct(checked))
+                        }
+                        // `Empty()` and `Empty {}` carry nothing
+                        _ => None,
+                    });
+                    next = value + 1;
+                }
                 self.namespace.enums.insert(e.name.clone(), (node.span, e.clone()));
                 return Ok(cir::Statement {
                     span: node.span,
                     kind: cir::StatementKind::EnumStatement(e)
                 });
             }
+            ast::Statement::TypeStatement(stmt) => {
+                if let Some(s) = self.check_name_availability(&stmt.name) {
+                    return Err(SemanticError { span: node.span, case: SemanticErrorCase::NameRedefinition {
+                        name: stmt.name.clone(),
+                        defined: s
+                    }})
+                }
+
+                let typing = self.check_type(&stmt.typing)?;
+                self.namespace.aliases.insert(stmt.name.clone(), (node.span, typing.clone()));
+                return Ok(cir::Statement {
+                    span: node.span,
+                    kind: cir::StatementKind::TypeAlias { name: stmt.name, typing }
+                });
+            }
             ast::Statement::IfStatement(stmt) => {
                 if expects_return.is_none() {
                     return Err(SemanticError {
@@ -1517,6 +3122,49 @@ impl SemanticChecker {
                     }
                 );
             }
+            ast::Statement::ForStatement(stmt) => {
+                if expects_return.is_none() {
+                    return Err(SemanticError {
+                        span: node.span,
+                        case: SemanticErrorCase::TopLevelImperativeStatement {
+                            statement: String::from("for statement")
+                        },
+                    });
+                }
+
+                for i in self.namespace.locals.iter().rev() {
+                    if i.get(&stmt.binding).is_some() {
+                        return Err(SemanticError {
+                            span: node.span,
+                            case: SemanticErrorCase::VariableRedefinition { name: stmt.binding.clone() }
+                        });
+                    }
+                }
+
+                let (iterable, typing) = self.typecheck_expr(&stmt.iterable, false)?;
+                let cir::Typing::Array { typ, amount } = typing else {
+                    return Err(SemanticError {
+                        span: stmt.iterable.span,
+                        case: SemanticErrorCase::TypeMismatch {
+                            got: format!("{}", typing),
+                            expected: String::from("array"),
+                        },
+                    });
+                };
+                // The items are copied from the array, which is kept by the current scope
+                let lifetime = self.value_lifetime(&iterable, &typ);
+                let mut scope = HashMap::new();
+                scope.insert(stmt.binding.clone(), Variable { mutable: false, typing: (*typ).clone(), lifetime });
+                self.namespace.locals.push(scope);
+                let block = self.typecheck_block(stmt.block.content, expects_return)?;
+                self.namespace.locals.pop();
+                return Ok(
+                    cir::Statement {
+                        span: node.span,
+                        kind: cir::StatementKind::ForStatement { binding: stmt.binding, typing: *typ, amount, iterable, block }
+                    }
+                );
+            }
             ast::Statement::ExpressionStatement(stmt) => {
                 if expects_return.is_none() {
                     return Err(SemanticError {
@@ -1527,10 +3175,15 @@ impl SemanticChecker {
                     });
                 }
 
+                // The arms of a match used as a statement don't need to have a value
+                let (expr, _) = match &stmt.data {
+                    ast::ExpressionData::Match { value, arms } => self.typecheck_match(value, arms, stmt.span, false)?,
+                    _ => self.typecheck_expr(&stmt, false)?,
+                };
                 return Ok(
                     cir::Statement {
                         span: node.span,
-                        kind: cir::StatementKind::ExpressionStatement(self.typecheck_expr(&stmt, false)?.0)
+                        kind: cir::StatementKind::ExpressionStatement(expr)
                     }
                 );
             }
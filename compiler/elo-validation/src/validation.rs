@@ -18,6 +18,12 @@ impl Validator {
         }
     }
 
+    pub fn with_target(target: cir::Target) -> Validator {
+        Validator {
+            semchecker: SemanticChecker::with_target(target),
+        }
+    }
+
     pub fn go(mut self, filename: String, input: Vec<ast::Node>) -> Result<cir::Program, Vec<ValidationError>> {
         let tc = self.semchecker.go(filename, input);
         let mut errors = Vec::new();
@@ -0,0 +1,49 @@
+#ifndef LIMITS_H
+#define LIMITS_H
+
+#define SMALLEST (-2147483647 - 1)
+#define SMALLEST_LONG (-9223372036854775807LL - 1)
+#define LARGEST 2147483647
+#define SCALE 1e+3
+#define HUGE_SCALE 1e999
+
+enum {
+    LOWEST = -2147483647 - 1,
+    HIGHEST = 2147483647
+};
+
+typedef enum level {
+    LEVEL_LOW = -1,
+    LEVEL_HIGH = 1
+} level;
+
+typedef enum mode {
+    MODE_READ = 1,
+    MODE_WRITE = 2,
+    MODE_DEFAULT = MODE_READ
+} mode;
+
+struct buffer {
+    char data[
+16];
+    int length;
+};
+
+int buffer_fill(struct buffer* buffer, level level);
+
+typedef struct type {
+    int match;
+} type;
+
+typedef enum defer {
+    DEFER_NOW,
+    DEFER_LATER
+} defer;
+
+typedef int ret;
+
+int match(int a, int b);
+extern int export;
+ret type_size(type* t, defer when);
+
+#endif
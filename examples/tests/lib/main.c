@@ -0,0 +1,24 @@
+#include <stdio.h>
+#include <string.h>
+#include <mem.h>
+#include "counter.h"
+
+int main(void) {
+    add(5);
+    printf("%d\n", add(1));
+    Point p = step((Point){ .x = 1, .y = 2 }, Direction_Down);
+    printf("%d %d\n", p.x, p.y);
+    counter_greet("C");
+    // The temporaries of the returned cstr are only kept until the next call
+    label();
+    size_t kept = __elo_context_get()->temporaries.count;
+    for (int i = 0; i < 1000; i++) {
+        if (strcmp(label(), "label") != 0) {
+            return 1;
+        }
+    }
+    printf("%s %zu\n", label(), __elo_context_get()->temporaries.count - kept);
+    Note* n = note();
+    note_print(n);
+    return 0;
+}
@@ -3,6 +3,7 @@
 
 #include <stddef.h>
 #include <slice.h>
+#include <panic.h>
 
 #ifndef MEM_H
 #define MEM_H
@@ -30,6 +31,8 @@ typedef struct {
 typedef struct {
 	HandleTable handles;
 	SlotTable   dead_slots;
+	// Handles dropped when the scope that created them is exited
+	SlotTable   temporaries;
 	_ELO_SLICE_T args;
 } GlobalContext;
 
@@ -39,4 +42,24 @@ void __elo_handle_resize(GlobalContext* ctx, Slot slot, size_t size);
 void __elo_handle_drop(GlobalContext* ctx, Slot slot);
 void* __elo_handle_get(GlobalContext* ctx, Slot slot);
 
+// Copy `len` items of `size` bytes into a new slice, which is a temporary
+_ELO_SLICE_T __elo_slice_pack(GlobalContext* ctx, const void* items, size_t len, size_t size);
+// Address of the item at `index`, panicking if it is out of bounds
+void* __elo_slice_get(GlobalContext* ctx, Pos pos, _ELO_SLICE_T slice, size_t index, size_t size);
+
+// Copy the environment of a closure into a new temporary
+void* __elo_env_new(GlobalContext* ctx, const void* env, size_t size);
+
+// New handle that is dropped by the next release of a mark taken before it
+Slot __elo_temporary_new(GlobalContext* ctx, size_t size);
+size_t __elo_temporary_mark(GlobalContext* ctx);
+// Drop the temporaries created since `mark` was taken
+void __elo_temporary_release(GlobalContext* ctx, size_t mark);
+// Move the temporaries created since `mark` into `kept`, dropping the ones kept before
+void __elo_temporary_keep(GlobalContext* ctx, size_t mark, SlotTable* kept);
+
+// Context of the program, used by exported functions since C callers don't pass one.
+// It is defined by the entry point: src/main.c for programs and src/lib.c for libraries.
+GlobalContext* __elo_context_get(void);
+
 #endif
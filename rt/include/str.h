@@ -12,5 +12,9 @@ typedef struct {
 Str __elo_str_new(GlobalContext* ctx, const char* cstr);
 Str __elo_str_slice(GlobalContext *ctx, Pos pos, Str str, size_t start, size_t end);
 uint32_t __elo_str_get(GlobalContext* ctx, Pos pos, Str str, size_t index);
+// NUL-terminated copy of `str`, dropped when the current scope is exited
+char* __elo_str_to_cstr(GlobalContext* ctx, Str str);
+// Copy of `cstr` in a new handle
+Str __elo_str_from_cstr(GlobalContext* ctx, Pos pos, const char* cstr);
 
 #endif
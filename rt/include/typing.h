@@ -1,6 +1,7 @@
 // Elo's core typing definition
 
 #include <stdint.h>
+#include <stddef.h>
 #include <stdbool.h>
 #include <mem.h>
 #include <panic.h>
@@ -22,10 +23,30 @@ typedef float                             _ELO_F32_T;
 typedef double                            _ELO_F64_T;
 typedef int32_t                           _ELO_INT_T;
 typedef uint32_t                          _ELO_UINT_T;
+typedef ptrdiff_t                         _ELO_ISIZE_T;
+typedef size_t                            _ELO_USIZE_T;
 typedef float                             _ELO_FLOAT_T;
 typedef Str                               _ELO_STR_T;
 typedef uint32_t                          _ELO_CHAR_T;
 
+// Function values: a function and the environment of its captured variables.
+// Without an environment (env is NULL), fn doesn't take it as argument.
+typedef struct { void* fn; void* env; }   _ELO_FN_T;
+
+// Layout attributes of packed and aligned structs.
+// MSVC packs with a pragma around the struct, the other compilers with an attribute.
+#ifdef _MSC_VER
+#define __elo_packed_begin __pragma(pack(push, 1))
+#define __elo_packed_end __pragma(pack(pop))
+#define __elo_packed
+#define __elo_align(n) __declspec(align(n))
+#else
+#define __elo_packed_begin
+#define __elo_packed_end
+#define __elo_packed __attribute__((packed))
+#define __elo_align(n) __attribute__((aligned(n)))
+#endif
+
 // Generic type generation
 #define __elo_struct(Name, ...) \
 	typedef struct { \
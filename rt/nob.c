@@ -9,14 +9,15 @@
 
 static Cmd cmd = {0};
 
-bool build_native(const char *source) {
-    cmd_append(&cmd, "cc", "-c", "-o", BUILD_DIR RT_NATIVE, source);
+bool build_native(const char *source, const char *output) {
+    // Position independent, since it can end up in a shared library
+    cmd_append(&cmd, "cc", "-c", "-fPIC", "-o", temp_sprintf(BUILD_DIR "%s", output), source);
     cmd_append(&cmd, "-I", "include", CFLAGS);
     return cmd_run(&cmd);
 }
 
-bool build_mingw(const char *source) {
-    cmd_append(&cmd, "x86_64-w64-mingw32-gcc", "-c", "-o", BUILD_DIR RT_WIN, source);
+bool build_mingw(const char *source, const char *output) {
+    cmd_append(&cmd, "x86_64-w64-mingw32-gcc", "-c", "-o", temp_sprintf(BUILD_DIR "%s", output), source);
     cmd_append(&cmd, "-I", "include", CFLAGS);
     return cmd_run(&cmd);
 }
@@ -25,8 +26,8 @@ int main(int argc, char **argv) {
     NOB_GO_REBUILD_URSELF(argc, argv);
 
     if (!mkdir_if_not_exists(BUILD_DIR)) return 1;
-    if (!build_native(ENTRY)) return 1;
-    if (!build_mingw(ENTRY)) return 1;
+    if (!build_native(ENTRY, RT_NATIVE)) return 1;
+    if (!build_mingw(ENTRY, RT_WIN)) return 1;
 
     return 0;
 }
@@ -0,0 +1,29 @@
+#include <prelude.h>
+
+#include "panic.c"
+#include "mem.c"
+#include "list.c"
+#include "str.c"
+#include "builtins.c"
+
+// Entry point of Elo code built as a library, where C owns main.
+// The context is created by the first exported function that is called, which also
+// initializes the globals, so it is not safe to call exported functions from many threads at first.
+
+void __elo_init(GlobalContext*);
+
+static GlobalContext ctx = {
+    .handles = {0},
+    .dead_slots = {0},
+    .temporaries = {0},
+    .args = {0},
+};
+static bool initialized = false;
+
+GlobalContext* __elo_context_get(void) {
+    if (!initialized) {
+        initialized = true;
+        __elo_init(&ctx);
+    }
+    return &ctx;
+}
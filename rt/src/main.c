@@ -6,21 +6,31 @@
 #include "str.c"
 #include "builtins.c"
 
+void __elo_init(GlobalContext*);
 void __elo_main(GlobalContext*);
 
+// Static, so that exported functions called after main returns (like atexit callbacks) can still use it
+static GlobalContext ctx = {
+    .handles = {0},
+    .dead_slots = {0},
+    .temporaries = {0},
+    .args = {0},
+};
+
+GlobalContext* __elo_context_get(void) {
+    return &ctx;
+}
+
 int main(int argc, char** argv) {
-    GlobalContext ctx = (GlobalContext){
-        .handles = {0},
-        .dead_slots = {0},
-        .args = {0},
-    };
     size_t size = sizeof(_ELO_STR_T)*argc;
     _ELO_SLICE_T args = { .data = __elo_handle_add(&ctx, malloc(size)), .len = argc };
     _ELO_STR_T* p = __elo_handle_get(&ctx, args.data);
     for (size_t i = 0; i < (size_t)argc; i++, p++) {
         *p = __elo_str_new(&ctx, argv[i]);
     }
+    ctx.args = args;
 
+	__elo_init(&ctx);
 	__elo_main(&ctx);
 	return 0;
 }
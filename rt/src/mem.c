@@ -3,6 +3,7 @@
 
 #include <stdio.h>
 #include <stdlib.h>
+#include <string.h>
 #include <mem.h>
 
 #define da_append(xs, x)                                                             \
@@ -51,3 +52,51 @@ void __elo_handle_drop(GlobalContext* ctx, Slot slot) {
 inline void* __elo_handle_get(GlobalContext* ctx, Slot slot) {
     return ctx->handles.items[slot];
 }
+
+_ELO_SLICE_T __elo_slice_pack(GlobalContext* ctx, const void* items, size_t len, size_t size) {
+	// Never allocate 0 bytes, since malloc may return NULL for it
+	Slot slot = __elo_temporary_new(ctx, len > 0 ? len*size : 1);
+	if (len > 0) {
+		memcpy(__elo_handle_get(ctx, slot), items, len*size);
+	}
+	return (_ELO_SLICE_T){ .data = slot, .len = len };
+}
+
+void* __elo_slice_get(GlobalContext* ctx, Pos pos, _ELO_SLICE_T slice, size_t index, size_t size) {
+	if (index >= slice.len) {
+		__elo_panic(pos, "index %zu is out of bounds for slice of length %zu", index, slice.len);
+	}
+	return (char*)__elo_handle_get(ctx, slice.data) + index*size;
+}
+
+void* __elo_env_new(GlobalContext* ctx, const void* env, size_t size) {
+	void* ptr = __elo_handle_get(ctx, __elo_temporary_new(ctx, size));
+	memcpy(ptr, env, size);
+	return ptr;
+}
+
+Slot __elo_temporary_new(GlobalContext* ctx, size_t size) {
+	Slot slot = __elo_handle_new(ctx, size);
+	da_append(&ctx->temporaries, slot);
+	return slot;
+}
+
+size_t __elo_temporary_mark(GlobalContext* ctx) {
+	return ctx->temporaries.count;
+}
+
+void __elo_temporary_release(GlobalContext* ctx, size_t mark) {
+	while (ctx->temporaries.count > mark) {
+		__elo_handle_drop(ctx, ctx->temporaries.items[--(ctx->temporaries.count)]);
+	}
+}
+
+void __elo_temporary_keep(GlobalContext* ctx, size_t mark, SlotTable* kept) {
+	while (kept->count > 0) {
+		__elo_handle_drop(ctx, kept->items[--(kept->count)]);
+	}
+	for (size_t i = mark; i < ctx->temporaries.count; i++) {
+		da_append(kept, ctx->temporaries.items[i]);
+	}
+	ctx->temporaries.count = mark;
+}
@@ -37,3 +37,23 @@ Str __elo_str_slice(GlobalContext *ctx, Pos pos, Str str, size_t start, size_t e
         .size = size
     };
 }
+
+char* __elo_str_to_cstr(GlobalContext* ctx, Str str) {
+    char* cstr = __elo_handle_get(ctx, __elo_temporary_new(ctx, str.size + 1));
+    memcpy(cstr, (char*)__elo_handle_get(ctx, str.slot) + str.offset, str.size);
+    cstr[str.size] = '\0';
+    return cstr;
+}
+
+Str __elo_str_from_cstr(GlobalContext* ctx, Pos pos, const char* cstr) {
+    if (cstr == NULL)
+        __elo_panic(pos, "null cstr can not be converted into str");
+    size_t size = strlen(cstr);
+    Slot slot = __elo_handle_new(ctx, size + 1);
+    memcpy(__elo_handle_get(ctx, slot), cstr, size + 1);
+    return (Str) {
+        .slot = slot,
+        .offset = 0,
+        .size = size
+    };
+}
@@ -0,0 +1,1043 @@
+// Elo declarations from C headers (`elo bindgen`)
+use std::collections::{HashMap, HashSet};
+
+use elo_lexer::keyword::Keyword;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Str(String),
+    Char(String),
+    Punct(String),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Token::Ident(s) | Token::Number(s) | Token::Punct(s) => write!(f, "{s}"),
+            Token::Str(s) => write!(f, "\"{s}\""),
+            Token::Char(s) => write!(f, "'{s}'"),
+        }
+    }
+}
+
+fn is_punct(token: &Token, punct: &str) -> bool {
+    matches!(token, Token::Punct(p) if p == punct)
+}
+
+fn is_ident(token: &Token, ident: &str) -> bool {
+    matches!(token, Token::Ident(i) if i == ident)
+}
+
+// Remove the comments and join the lines continued with a backslash.
+// Every logical line is returned with the number of the line it starts at.
+fn logical_lines(source: &str) -> Vec<(usize, String)> {
+    let mut text = String::new();
+    let mut chars = source.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', Some(_)) => {
+                text.push(c);
+                if let Some(e) = chars.next() {
+                    text.push(e);
+                }
+            }
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                text.push(c);
+            }
+            (c, Some(q)) if c == q => {
+                quote = None;
+                text.push(c);
+            }
+            ('/', None) if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', None) if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                while let Some(c) = chars.next() {
+                    // Keep the newlines, so the line numbers stay right
+                    if c == '\n' {
+                        text.push('\n');
+                    }
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+                text.push(' ');
+            }
+            // An unterminated quote ends with its line
+            ('\n', Some(_)) => {
+                quote = None;
+                text.push(c);
+            }
+            _ => text.push(c),
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (number, line) in text.lines().enumerate() {
+        let (start, mut joined) = current.take().unwrap_or((number + 1, String::new()));
+        if let Some(line) = line.strip_suffix('\\') {
+            joined.push_str(line);
+            joined.push(' ');
+            current = Some((start, joined));
+        } else {
+            joined.push_str(line);
+            lines.push((start, joined));
+        }
+    }
+    if let Some(line) = current {
+        lines.push(line);
+    }
+    lines
+}
+
+fn tokenize(line: &str) -> Vec<Token> {
+    let chars = line.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())) {
+            while i < chars.len() {
+                let c = chars[i];
+                let exponent = i > start && matches!(chars[i - 1], 'e' | 'E' | 'p' | 'P') && (c == '+' || c == '-');
+                if !(c.is_ascii_alphanumeric() || c == '.' || c == '_' || exponent) {
+                    break;
+                }
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c == '"' || c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            let content = chars[start + 1..i.min(chars.len())].iter().collect();
+            tokens.push(if c == '"' { Token::Str(content) } else { Token::Char(content) });
+            i += 1;
+        } else {
+            let rest = chars[i..].iter().take(3).collect::<String>();
+            let punct = ["...", "<<", ">>", "->", "##"]
+                .into_iter()
+                .find(|p| rest.starts_with(p))
+                .map(|p| p.to_string())
+                .unwrap_or(c.to_string());
+            i += punct.len();
+            tokens.push(Token::Punct(punct));
+        }
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Value {
+    Int { value: i128, unsigned: bool },
+    Float { value: f64, single: bool },
+}
+
+fn parse_number(text: &str) -> Option<Value> {
+    let text = text.to_ascii_lowercase();
+    let hex = text.starts_with("0x");
+    let float = !hex && (text.contains('.') || text.contains('e'));
+    if float {
+        let single = text.ends_with('f');
+        let value = text.trim_end_matches(['f', 'l']).parse::<f64>().ok()?;
+        return Some(Value::Float { value, single });
+    }
+    let unsigned = text.trim_start_matches("0x").trim_end_matches('l').ends_with('u')
+        || text.trim_end_matches('u').ends_with("lu")
+        || text.trim_end_matches('u').ends_with("llu");
+    let digits = text.trim_end_matches(['u', 'l']).replace('\'', "");
+    let (digits, radix) = if let Some(d) = digits.strip_prefix("0x") {
+        (d.to_string(), 16)
+    } else if let Some(d) = digits.strip_prefix("0b") {
+        (d.to_string(), 2)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (digits[1..].to_string(), 8)
+    } else {
+        (digits, 10)
+    };
+    let value = i128::from_str_radix(&digits, radix).ok()?;
+    Some(Value::Int { value, unsigned })
+}
+
+// Value of a constant expression made of literals, known constants and integer operators
+struct Evaluator<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    constants: &'a HashMap<String, Value>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn evaluate(tokens: &'a [Token], constants: &'a HashMap<String, Value>) -> Option<Value> {
+        let mut evaluator = Evaluator { tokens, pos: 0, constants };
+        let value = evaluator.binary(0)?;
+        if evaluator.pos != tokens.len() {
+            return None;
+        }
+        Some(value)
+    }
+
+    fn precedence(op: &str) -> Option<u8> {
+        match op {
+            "|" => Some(1),
+            "^" => Some(2),
+            "&" => Some(3),
+            "<<" | ">>" => Some(4),
+            "+" | "-" => Some(5),
+            "*" | "/" | "%" => Some(6),
+            _ => None,
+        }
+    }
+
+    fn binary(&mut self, min: u8) -> Option<Value> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Punct(op)) = self.tokens.get(self.pos) {
+            let Some(precedence) = Self::precedence(op) else { break };
+            if precedence < min {
+                break;
+            }
+            let op = op.clone();
+            self.pos += 1;
+            let rhs = self.binary(precedence + 1)?;
+            let (Value::Int { value: a, unsigned: ua }, Value::Int { value: b, unsigned: ub }) = (lhs, rhs) else {
+                return None;
+            };
+            let value = match op.as_str() {
+                "|" => a | b,
+                "^" => a ^ b,
+                "&" => a & b,
+                "<<" => a.checked_shl(u32::try_from(b).ok()?)?,
+                ">>" => a.checked_shr(u32::try_from(b).ok()?)?,
+                "+" => a.checked_add(b)?,
+                "-" => a.checked_sub(b)?,
+                "*" => a.checked_mul(b)?,
+                "/" => a.checked_div(b)?,
+                "%" => a.checked_rem(b)?,
+                _ => unreachable!(),
+            };
+            lhs = Value::Int { value, unsigned: ua || ub };
+        }
+        Some(lhs)
+    }
+
+    fn unary(&mut self) -> Option<Value> {
+        let token = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        match token {
+            Token::Number(n) => parse_number(&n),
+            Token::Ident(name) => self.constants.get(&name).copied(),
+            Token::Punct(p) if p == "(" => {
+                let value = self.binary(0)?;
+                if !is_punct(self.tokens.get(self.pos)?, ")") {
+                    return None;
+                }
+                self.pos += 1;
+                Some(value)
+            }
+            Token::Punct(p) if p == "-" => match self.unary()? {
+                Value::Int { value, unsigned } => Some(Value::Int { value: -value, unsigned }),
+                Value::Float { value, single } => Some(Value::Float { value: -value, single }),
+            },
+            Token::Punct(p) if p == "+" => self.unary(),
+            Token::Punct(p) if p == "~" => match self.unary()? {
+                Value::Int { value, unsigned } => Some(Value::Int { value: !value, unsigned }),
+                Value::Float { .. } => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+// Elo type and value of a constant, which is followed by a cast to the type
+fn constant(value: Value) -> Result<(&'static str, String), &'static str> {
+    let typ = match value {
+        Value::Int { value, unsigned: false } if i32::try_from(value).is_ok() => "c_int",
+        Value::Int { value, unsigned: false } if i64::try_from(value).is_ok() => "c_longlong",
+        Value::Int { value, unsigned: true } if u32::try_from(value).is_ok() => "c_uint",
+        Value::Int { value, unsigned: true } if u64::try_from(value).is_ok() => "c_ulonglong",
+        Value::Int { .. } => return Err("its value does not fit in a C integer type"),
+        Value::Float { value, .. } if !value.is_finite() => return Err("its value is not a finite number"),
+        Value::Float { single: true, .. } => "c_float",
+        Value::Float { single: false, .. } => "c_double",
+    };
+    match value {
+        // The minimum is the negation of a literal that is too big for the type, like INT_MIN
+        Value::Int { value, .. } if value == i32::MIN as i128 || value == i64::MIN as i128 => {
+            Ok((typ, format!("{} as {typ} - 1", value + 1)))
+        }
+        Value::Int { value, .. } => Ok((typ, value.to_string())),
+        Value::Float { value, .. } => {
+            // Float literals in Elo always have a fractional part
+            let mut text = value.to_string();
+            if !text.contains('.') {
+                text.push_str(".0");
+            }
+            Ok((typ, text))
+        }
+    }
+}
+
+// Names that are keywords in Elo get an underscore, except the ones of symbols (see `symbol`)
+fn identifier(name: &str) -> String {
+    if Keyword::from_str(name).is_some() {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+// Functions and variables are linked by their name, so they can't be renamed like the other names
+fn symbol(name: &str) -> Result<(), &'static str> {
+    if Keyword::from_str(name).is_some() {
+        Err("its name is a keyword in Elo, and its symbol can't be renamed")
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Base {
+    Void,
+    // Plain char, since a pointer to it is a cstr
+    Char,
+    Elo(String),
+    // Struct that is declared but not defined, so only pointers to it can be used
+    Opaque(String),
+}
+
+#[derive(Debug, Clone)]
+enum Body {
+    // Fields of a struct, or of a union if the flag is set
+    Struct(Result<Vec<(String, String)>, String>, bool),
+    Enum(Vec<(String, i128)>),
+}
+
+#[derive(Debug, Clone)]
+struct Specifiers {
+    base: Base,
+    constant: bool,
+    typedef: bool,
+    // static or inline, which have no symbol to link with
+    local: bool,
+    tag: Option<String>,
+    body: Option<Body>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Declarator {
+    name: Option<String>,
+    pointers: usize,
+    arrays: Vec<Option<usize>>,
+    function: Option<(Vec<(String, String)>, bool)>,
+    function_pointer: bool,
+    bitfield: bool,
+}
+
+pub struct BindingGenerator {
+    pub output: String,
+    pub warnings: Vec<String>,
+    // Macros that expand to nothing or to attributes, like API export markers (#define RLAPI)
+    attributes: HashSet<String>,
+    constants: HashMap<String, Value>,
+    // Elo types of the C struct tags, enum tags and typedef names
+    types: HashMap<String, Base>,
+    // Names defined by the output so far
+    defined: HashSet<String>,
+    // Line of the declaration being translated, for the warnings
+    line: usize,
+}
+
+impl BindingGenerator {
+    pub fn new() -> Self {
+        Self {
+            output: String::new(),
+            warnings: Vec::new(),
+            attributes: HashSet::new(),
+            constants: HashMap::new(),
+            types: HashMap::new(),
+            defined: HashSet::new(),
+            line: 0,
+        }
+    }
+
+    fn warn(&mut self, what: &str, reason: &str) {
+        self.warnings.push(format!("line {}: skipped {what}: {reason}", self.line));
+    }
+
+    // Whether the name can be defined, which is not the case for names defined twice (like in both branches of an #if)
+    fn define(&mut self, name: &str) -> bool {
+        self.defined.insert(name.to_string())
+    }
+
+    pub fn go(&mut self, header: &str, filename: &str) {
+        self.output.push_str(&format!("// Generated by elo bindgen from {filename}\n\n"));
+        let mut code = Vec::new();
+        for (line, text) in logical_lines(header) {
+            self.line = line;
+            let trimmed = text.trim_start();
+            if let Some(directive) = trimmed.strip_prefix('#') {
+                self.directive(directive.trim_start());
+            } else {
+                code.extend(tokenize(&text).into_iter().map(|t| (line, t)));
+            }
+        }
+        if !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+        self.declarations(code);
+    }
+
+    // Object-like macros with constant values become constants, and the other directives are ignored
+    fn directive(&mut self, directive: &str) {
+        let Some(rest) = directive.strip_prefix("define") else { return };
+        if !rest.starts_with(char::is_whitespace) {
+            return;
+        }
+        let rest = rest.trim_start();
+        let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        let (name, value) = rest.split_at(end);
+        // Function-like macros have the parameters right after the name
+        if name.is_empty() || value.starts_with('(') {
+            return;
+        }
+        let tokens = tokenize(value);
+        match tokens.first() {
+            None => {
+                self.attributes.insert(name.to_string());
+                return;
+            }
+            Some(Token::Ident(i)) if i.starts_with("__declspec") || i.starts_with("__attribute") || i == "extern" => {
+                self.attributes.insert(name.to_string());
+                return;
+            }
+            _ => {}
+        }
+        if let [Token::Str(text)] = tokens.as_slice() {
+            // Only the escape sequences that Elo and C share can be kept
+            let escapes = text.split('\\').skip(1).all(|s| s.starts_with(['n', 'r', 't', 'v', 'f', '\\', '\'', '"']));
+            if !escapes {
+                self.warn(&format!("macro {name}"), "the string has escape sequences that Elo doesn't support");
+            } else if self.define(name) {
+                let text = text.replace("\\\"", "\"").replace('\'', "\\'").replace("\\\\'", "\\'");
+                self.output.push_str(&format!("const {}: cstr = c'{text}'\n", identifier(name)));
+            }
+            return;
+        }
+        let value = Evaluator::evaluate(&tokens, &self.constants);
+        match value.map(constant) {
+            Some(Ok((typ, literal))) => {
+                self.constants.insert(name.to_string(), value.unwrap());
+                if self.define(name) {
+                    self.output.push_str(&format!("const {}: {typ} = {literal} as {typ}\n", identifier(name)));
+                }
+            }
+            Some(Err(reason)) => self.warn(&format!("macro {name}"), reason),
+            None => self.warn(&format!("macro {name}"), "its value is not a constant number or string"),
+        }
+    }
+
+    // Split the code into declarations, skipping the bodies of functions
+    fn declarations(&mut self, code: Vec<(usize, Token)>) {
+        let mut current: Vec<Token> = Vec::new();
+        let mut depth = 0;
+        let mut i = 0;
+        while i < code.len() {
+            let (line, token) = &code[i];
+            if current.is_empty() {
+                self.line = *line;
+            }
+            // extern "C" { ... } around the declarations
+            if depth == 0 && current.is_empty() && is_ident(token, "extern") {
+                if let (Some((_, Token::Str(c))), Some((_, brace))) = (code.get(i + 1), code.get(i + 2)) && c == "C" && is_punct(brace, "{") {
+                    i += 3;
+                    continue;
+                }
+            }
+            match token {
+                Token::Punct(p) if p == "{" && depth == 0 && current.last().is_some_and(|t| is_punct(t, ")")) => {
+                    // The function name is right before its parameters
+                    let parameters = current.iter().position(|t| is_punct(t, "(")).unwrap_or(0);
+                    let name = current[..parameters].iter().rev().find_map(|t| if let Token::Ident(i) = t { Some(i.clone()) } else { None });
+                    let mut braces = 0;
+                    while i < code.len() {
+                        if is_punct(&code[i].1, "{") {
+                            braces += 1;
+                        } else if is_punct(&code[i].1, "}") {
+                            braces -= 1;
+                            if braces == 0 {
+                                break;
+                            }
+                        }
+                        i += 1;
+                    }
+                    self.warn(&format!("function {}", name.unwrap_or_default()), "inline functions have no symbol to link with");
+                    current.clear();
+                }
+                Token::Punct(p) if p == "{" => {
+                    depth += 1;
+                    current.push(token.clone());
+                }
+                // The end of extern "C" { ... }
+                Token::Punct(p) if p == "}" && depth == 0 => {}
+                Token::Punct(p) if p == "}" => {
+                    depth -= 1;
+                    current.push(token.clone());
+                }
+                Token::Punct(p) if p == ";" && depth == 0 => {
+                    let declaration = std::mem::take(&mut current);
+                    self.declaration(self.strip_attributes(declaration));
+                }
+                _ => current.push(token.clone()),
+            }
+            i += 1;
+        }
+    }
+
+    fn strip_attributes(&self, tokens: Vec<Token>) -> Vec<Token> {
+        const IGNORED: [&str; 11] = [
+            "volatile", "register", "restrict", "__restrict", "__restrict__", "__extension__",
+            "_Noreturn", "__cdecl", "__stdcall", "__inline__", "__forceinline",
+        ];
+        let mut result = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                Token::Ident(name) if name == "__attribute__" || name == "__declspec" || name == "__asm__" || name == "__asm" => {
+                    // Skip the parentheses of the attribute
+                    let mut parens = 0;
+                    i += 1;
+                    while i < tokens.len() {
+                        if is_punct(&tokens[i], "(") {
+                            parens += 1;
+                        } else if is_punct(&tokens[i], ")") {
+                            parens -= 1;
+                            if parens == 0 {
+                                break;
+                            }
+                        }
+                        i += 1;
+                    }
+                }
+                Token::Ident(name) if self.attributes.contains(name) || IGNORED.contains(&name.as_str()) => {}
+                token => result.push(token.clone()),
+            }
+            i += 1;
+        }
+        result
+    }
+
+    fn builtin_type(name: &str) -> Option<&'static str> {
+        match name {
+            "int8_t" => Some("i8"),
+            "int16_t" => Some("i16"),
+            "int32_t" => Some("i32"),
+            "int64_t" => Some("i64"),
+            "uint8_t" => Some("u8"),
+            "uint16_t" => Some("u16"),
+            "uint32_t" => Some("u32"),
+            "uint64_t" => Some("u64"),
+            "intptr_t" => Some("isize"),
+            "uintptr_t" => Some("usize"),
+            "size_t" => Some("c_size_t"),
+            "ssize_t" => Some("c_ssize_t"),
+            "ptrdiff_t" => Some("c_ptrdiff_t"),
+            "bool" | "_Bool" => Some("bool"),
+            _ => None,
+        }
+    }
+
+    fn specifiers(&mut self, tokens: &[Token], pos: &mut usize) -> Result<Specifiers, String> {
+        let mut spec = Specifiers { base: Base::Void, constant: false, typedef: false, local: false, tag: None, body: None };
+        let mut base = None;
+        let (mut signed, mut unsigned, mut short, mut long, mut int) = (false, false, false, 0, false);
+        let mut primitive = None;
+        while let Some(token) = tokens.get(*pos) {
+            let Token::Ident(word) = token else { break };
+            match word.as_str() {
+                "const" => spec.constant = true,
+                "typedef" => spec.typedef = true,
+                "extern" => {}
+                "static" | "inline" | "__inline" => spec.local = true,
+                "signed" => signed = true,
+                "unsigned" => unsigned = true,
+                "short" => short = true,
+                "long" => long += 1,
+                "int" => int = true,
+                "char" | "float" | "double" | "void" => primitive = Some(word.clone()),
+                "struct" | "union" | "enum" => {
+                    let kind = word.clone();
+                    *pos += 1;
+                    if let Some(Token::Ident(tag)) = tokens.get(*pos) {
+                        spec.tag = Some(tag.clone());
+                        *pos += 1;
+                    }
+                    if tokens.get(*pos).is_some_and(|t| is_punct(t, "{")) {
+                        let end = Self::matching(tokens, *pos).ok_or("unterminated body")?;
+                        let body = &tokens[*pos + 1..end];
+                        spec.body = Some(if kind == "enum" { Body::Enum(self.enumerators(body)?) } else { Body::Struct(self.fields(body), kind == "union") });
+                        *pos = end;
+                        base = Some(Base::Void); // Decided by the caller, which knows the name
+                    } else if let Some(tag) = &spec.tag {
+                        base = Some(match self.types.get(tag) {
+                            Some(t) => t.clone(),
+                            None if kind == "enum" => Base::Elo("c_int".to_string()),
+                            None => Base::Opaque(tag.clone()),
+                        });
+                        // Already past the tag
+                        continue;
+                    } else {
+                        return Err(format!("{kind} without a name or a body"));
+                    }
+                }
+                name if base.is_none() && primitive.is_none() && !(signed || unsigned || short || long > 0 || int) => {
+                    if let Some(t) = self.types.get(name) {
+                        base = Some(t.clone());
+                    } else if let Some(t) = Self::builtin_type(name) {
+                        base = Some(Base::Elo(t.to_string()));
+                    } else if tokens.get(*pos + 1).is_some_and(|t| matches!(t, Token::Ident(_)) || is_punct(t, "*")) {
+                        return Err(format!("unknown type {name}"));
+                    } else {
+                        break;
+                    }
+                }
+                _ => break,
+            }
+            *pos += 1;
+        }
+
+        spec.base = if let Some(base) = base {
+            base
+        } else {
+            let name = match (primitive.as_deref(), unsigned, short, long) {
+                (Some("void"), ..) => return Ok(Specifiers { base: Base::Void, ..spec }),
+                (Some("char"), false, ..) if !signed => return Ok(Specifiers { base: Base::Char, ..spec }),
+                (Some("char"), false, ..) => "c_schar",
+                (Some("char"), true, ..) => "c_uchar",
+                (Some("float"), ..) => "c_float",
+                (Some("double"), _, _, 0) => "c_double",
+                (Some("double"), ..) => return Err("long double is not supported".to_string()),
+                (_, false, true, _) => "c_short",
+                (_, true, true, _) => "c_ushort",
+                (_, false, _, 1) => "c_long",
+                (_, true, _, 1) => "c_ulong",
+                (_, false, _, 2) => "c_longlong",
+                (_, true, _, 2) => "c_ulonglong",
+                (_, false, _, _) if signed || int => "c_int",
+                (_, true, _, _) => "c_uint",
+                _ => return Err("missing type".to_string()),
+            };
+            Base::Elo(name.to_string())
+        };
+        Ok(spec)
+    }
+
+    // Position of the bracket that closes the one at `start`
+    fn matching(tokens: &[Token], start: usize) -> Option<usize> {
+        let (open, close) = match &tokens[start] {
+            Token::Punct(p) if p == "{" => ("{", "}"),
+            Token::Punct(p) if p == "(" => ("(", ")"),
+            Token::Punct(p) if p == "[" => ("[", "]"),
+            _ => return None,
+        };
+        let mut depth = 0;
+        for (i, token) in tokens.iter().enumerate().skip(start) {
+            if is_punct(token, open) {
+                depth += 1;
+            } else if is_punct(token, close) {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+        }
+        None
+    }
+
+    fn declarator(&mut self, tokens: &[Token], pos: &mut usize) -> Result<Declarator, String> {
+        let mut decl = Declarator::default();
+        while let Some(token) = tokens.get(*pos) {
+            if is_punct(token, "*") {
+                decl.pointers += 1;
+            } else if !is_ident(token, "const") {
+                break;
+            }
+            *pos += 1;
+        }
+        // Function pointer, like (*callback)(int)
+        if tokens.get(*pos).is_some_and(|t| is_punct(t, "(")) && tokens.get(*pos + 1).is_some_and(|t| is_punct(t, "*")) {
+            let end = Self::matching(tokens, *pos).ok_or("unterminated parentheses")?;
+            decl.name = tokens[*pos + 1..end].iter().find_map(|t| if let Token::Ident(i) = t { Some(i.clone()) } else { None });
+            decl.function_pointer = true;
+            *pos = end + 1;
+            if tokens.get(*pos).is_some_and(|t| is_punct(t, "(")) {
+                *pos = Self::matching(tokens, *pos).ok_or("unterminated parentheses")? + 1;
+            }
+            return Ok(decl);
+        }
+        if let Some(Token::Ident(name)) = tokens.get(*pos) {
+            decl.name = Some(name.clone());
+            *pos += 1;
+        }
+        while let Some(token) = tokens.get(*pos) {
+            if is_punct(token, "[") {
+                let end = Self::matching(tokens, *pos).ok_or("unterminated brackets")?;
+                let size = &tokens[*pos + 1..end];
+                if size.is_empty() {
+                    decl.arrays.push(None);
+                } else {
+                    match Evaluator::evaluate(size, &self.constants) {
+                        Some(Value::Int { value, .. }) if value >= 0 => decl.arrays.push(Some(value as usize)),
+                        _ => return Err("the array size is not a constant".to_string()),
+                    }
+                }
+                *pos = end + 1;
+            } else if is_punct(token, "(") {
+                let end = Self::matching(tokens, *pos).ok_or("unterminated parentheses")?;
+                decl.function = Some(self.parameters(&tokens[*pos + 1..end])?);
+                *pos = end + 1;
+            } else if is_punct(token, ":") {
+                decl.bitfield = true;
+                *pos = tokens.len();
+            } else {
+                break;
+            }
+        }
+        Ok(decl)
+    }
+
+    fn parameters(&mut self, tokens: &[Token]) -> Result<(Vec<(String, String)>, bool), String> {
+        let mut parameters = Vec::new();
+        let mut variadic = false;
+        if let [Token::Ident(v)] = tokens && v == "void" {
+            return Ok((parameters, variadic));
+        }
+        for (i, parameter) in Self::split(tokens, ",").into_iter().enumerate() {
+            if let [Token::Punct(p)] = parameter && p == "..." {
+                variadic = true;
+                continue;
+            }
+            let mut pos = 0;
+            let spec = self.specifiers(parameter, &mut pos)?;
+            let mut decl = self.declarator(parameter, &mut pos)?;
+            // Array parameters are pointers to the first item
+            if !decl.arrays.is_empty() {
+                decl.arrays.remove(0);
+                decl.pointers += 1;
+            }
+            let typ = self.translate(&spec, &decl)?.ok_or("void parameter")?;
+            let name = decl.name.unwrap_or(format!("arg{i}"));
+            parameters.push((identifier(&name), typ));
+        }
+        Ok((parameters, variadic))
+    }
+
+    // Split the tokens by the separator, outside of brackets
+    fn split<'t>(tokens: &'t [Token], separator: &str) -> Vec<&'t [Token]> {
+        let mut parts = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                Token::Punct(p) if p == "(" || p == "[" || p == "{" => depth += 1,
+                Token::Punct(p) if p == ")" || p == "]" || p == "}" => depth -= 1,
+                Token::Punct(p) if p == separator && depth == 0 => {
+                    parts.push(&tokens[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if start < tokens.len() {
+            parts.push(&tokens[start..]);
+        }
+        parts
+    }
+
+    fn fields(&mut self, body: &[Token]) -> Result<Vec<(String, String)>, String> {
+        let mut fields = Vec::new();
+        for declaration in Self::split(body, ";") {
+            let mut pos = 0;
+            let spec = self.specifiers(declaration, &mut pos)?;
+            if spec.body.is_some() {
+                return Err("nested structs and enums are not supported".to_string());
+            }
+            for declarator in Self::split(&declaration[pos..], ",") {
+                let decl = self.declarator(declarator, &mut 0)?;
+                if decl.bitfield {
+                    return Err("bit fields are not supported".to_string());
+                }
+                let name = decl.name.clone().ok_or("field without a name")?;
+                let typ = self.translate(&spec, &decl)?.ok_or("void field")?;
+                fields.push((identifier(&name), typ));
+            }
+        }
+        Ok(fields)
+    }
+
+    fn enumerators(&mut self, body: &[Token]) -> Result<Vec<(String, i128)>, String> {
+        let mut enumerators = Vec::new();
+        let mut next = 0;
+        for enumerator in Self::split(body, ",") {
+            let Some(Token::Ident(name)) = enumerator.first() else {
+                return Err("invalid enumerator".to_string());
+            };
+            if enumerator.len() > 1 {
+                if !is_punct(&enumerator[1], "=") {
+                    return Err(format!("invalid enumerator {name}"));
+                }
+                match Evaluator::evaluate(&enumerator[2..], &self.constants) {
+                    Some(Value::Int { value, .. }) => next = value,
+                    _ => return Err(format!("the value of {name} is not a constant")),
+                }
+            }
+            self.constants.insert(name.clone(), Value::Int { value: next, unsigned: false });
+            enumerators.push((name.clone(), next));
+            next += 1;
+        }
+        Ok(enumerators)
+    }
+
+    // Elo type of a C declaration, or None if it is void
+    fn translate(&self, spec: &Specifiers, decl: &Declarator) -> Result<Option<String>, String> {
+        let mut pointers = decl.pointers;
+        let mut typ = if decl.function_pointer {
+            "rawptr".to_string()
+        } else {
+            match &spec.base {
+                Base::Void if pointers == 0 => return Ok(None),
+                Base::Void => {
+                    pointers -= 1;
+                    "rawptr".to_string()
+                }
+                Base::Char if pointers > 0 => {
+                    pointers -= 1;
+                    "cstr".to_string()
+                }
+                Base::Char => "c_char".to_string(),
+                Base::Opaque(_) if pointers > 0 => {
+                    pointers -= 1;
+                    "rawptr".to_string()
+                }
+                Base::Opaque(name) => return Err(format!("struct {name} is not defined")),
+                Base::Elo(t) => {
+                    if pointers > 0 {
+                        pointers -= 1;
+                        if spec.constant { format!("*{t}") } else { format!("*mut {t}") }
+                    } else {
+                        t.clone()
+                    }
+                }
+            }
+        };
+        for _ in 0..pointers {
+            typ = format!("*mut {typ}");
+        }
+        for size in decl.arrays.iter().rev() {
+            let size = size.ok_or("arrays without size are not supported")?;
+            typ = format!("{{{typ}; {size}}}");
+        }
+        Ok(Some(typ))
+    }
+
+    fn declaration(&mut self, tokens: Vec<Token>) {
+        if tokens.is_empty() {
+            return;
+        }
+        let mut pos = 0;
+        let spec = match self.specifiers(&tokens, &mut pos) {
+            Ok(spec) => spec,
+            Err(reason) => {
+                let name = tokens.iter().rev().find_map(|t| if let Token::Ident(i) = t { Some(i.clone()) } else { None });
+                return self.warn(&format!("declaration {}", name.unwrap_or_default()), &reason);
+            }
+        };
+        let declarators = Self::split(&tokens[pos..], ",");
+
+        // The name of a struct or enum defined with a typedef is the typedef name
+        let mut name = spec.tag.clone();
+        if spec.typedef && let Some(first) = declarators.first() && let [Token::Ident(n)] = first {
+            name = Some(n.clone());
+        }
+        match &spec.body {
+            Some(Body::Struct(fields, union)) => {
+                let kind = if *union { "union" } else { "struct" };
+                let Some(name) = name.clone() else {
+                    return self.warn(kind, &format!("anonymous {kind}s are not supported"));
+                };
+                match fields {
+                    Ok(fields) if !fields.is_empty() => {
+                        if self.define(&name) {
+                            self.output.push_str(&format!("{kind} {} {{\n", identifier(&name)));
+                            for (field, typ) in fields {
+                                self.output.push_str(&format!("    {field}: {typ},\n"));
+                            }
+                            self.output.push_str("}\n\n");
+                        }
+                        self.types.insert(name.clone(), Base::Elo(identifier(&name)));
+                    }
+                    Ok(_) => {
+                        self.warn(&format!("{kind} {name}"), "it has no fields");
+                        self.types.insert(name.clone(), Base::Opaque(name.clone()));
+                    }
+                    Err(reason) => {
+                        self.warn(&format!("{kind} {name}"), reason);
+                        self.types.insert(name.clone(), Base::Opaque(name.clone()));
+                    }
+                }
+                if let Some(tag) = &spec.tag {
+                    let typ = self.types[&name].clone();
+                    self.types.insert(tag.clone(), typ);
+                }
+            }
+            Some(Body::Enum(enumerators)) => {
+                // Named enums are Elo enums, the anonymous ones are integer constants, like the ones
+                // with variants of the same value, which Elo enums can't have
+                let unique = enumerators.iter().enumerate().all(|(i, (_, value))| enumerators[..i].iter().all(|(_, other)| other != value));
+                if let Some(name) = &name && !enumerators.is_empty() && unique {
+                    if self.define(name) {
+                        self.output.push_str(&format!("enum {} {{\n", identifier(name)));
+                        let mut next = 0;
+                        for (variant, value) in enumerators {
+                            if *value == next {
+                                self.output.push_str(&format!("    {},\n", identifier(variant)));
+                            } else {
+                                self.output.push_str(&format!("    {} = {value},\n", identifier(variant)));
+                            }
+                            next = value + 1;
+                        }
+                        self.output.push_str("}\n\n");
+                    }
+                    self.types.insert(name.clone(), Base::Elo(identifier(name)));
+                } else {
+                    if let Some(name) = &name {
+                        if self.define(name) {
+                            self.output.push_str(&format!("type {} = c_int\n", identifier(name)));
+                        }
+                        self.types.insert(name.clone(), Base::Elo("c_int".to_string()));
+                    }
+                    for (variant, value) in enumerators {
+                        match constant(Value::Int { value: *value, unsigned: false }) {
+                            Ok((typ, literal)) => {
+                                if self.define(variant) {
+                                    self.output.push_str(&format!("const {}: {typ} = {literal} as {typ}\n", identifier(variant)));
+                                }
+                            }
+                            Err(reason) => self.warn(&format!("enumerator {variant}"), reason),
+                        }
+                    }
+                    self.output.push('\n');
+                }
+                if let (Some(tag), Some(name)) = (&spec.tag, &name) {
+                    let typ = self.types[name].clone();
+                    self.types.insert(tag.clone(), typ);
+                }
+            }
+            None => {}
+        }
+
+        for declarator in declarators {
+            let decl = match self.declarator(declarator, &mut 0) {
+                Ok(decl) => decl,
+                Err(reason) => return self.warn("declaration", &reason),
+            };
+            let Some(decl_name) = decl.name.clone() else { continue };
+            if spec.typedef {
+                self.typedef(&spec, &decl, &decl_name);
+            } else if let Some((parameters, variadic)) = &decl.function {
+                self.function(&spec, &decl, &decl_name, parameters, *variadic);
+            } else {
+                self.variable(&spec, &decl, &decl_name);
+            }
+        }
+    }
+
+    fn typedef(&mut self, spec: &Specifiers, decl: &Declarator, name: &str) {
+        // typedef struct { ... } Name, which was already translated
+        if spec.body.is_some() && decl.pointers == 0 && decl.arrays.is_empty() && !decl.function_pointer {
+            return;
+        }
+        if decl.function.is_some() && !decl.function_pointer {
+            return self.warn(&format!("type {name}"), "function types are not supported, only pointers to functions");
+        }
+        // typedef struct Name Name, or a typedef of a struct that is only declared
+        if let Base::Opaque(_) = spec.base && decl.pointers == 0 && decl.arrays.is_empty() {
+            self.types.insert(name.to_string(), Base::Opaque(name.to_string()));
+            return;
+        }
+        match self.translate(spec, decl) {
+            Ok(Some(typ)) => {
+                let alias = identifier(name);
+                if typ != alias && self.define(name) {
+                    self.output.push_str(&format!("type {alias} = {typ}\n\n"));
+                }
+                self.types.insert(name.to_string(), Base::Elo(alias));
+            }
+            Ok(None) => self.warn(&format!("type {name}"), "void types are not supported"),
+            Err(reason) => self.warn(&format!("type {name}"), &reason),
+        }
+    }
+
+    fn function(&mut self, spec: &Specifiers, decl: &Declarator, name: &str, parameters: &[(String, String)], variadic: bool) {
+        if spec.local {
+            return self.warn(&format!("function {name}"), "static and inline functions have no symbol to link with");
+        }
+        if let Err(reason) = symbol(name) {
+            return self.warn(&format!("function {name}"), reason);
+        }
+        let ret = Declarator { function: None, ..decl.clone() };
+        let ret = match self.translate(spec, &ret) {
+            Ok(ret) => ret,
+            Err(reason) => return self.warn(&format!("function {name}"), &reason),
+        };
+        if !self.define(name) {
+            return;
+        }
+        let mut arguments = parameters.iter().map(|(n, t)| format!("{n}: {t}")).collect::<Vec<String>>();
+        if variadic {
+            arguments.push("...".to_string());
+        }
+        let ret = ret.map(|r| format!(": {r}")).unwrap_or_default();
+        self.output.push_str(&format!("extern fn {name}({}){ret}\n", arguments.join(", ")));
+    }
+
+    fn variable(&mut self, spec: &Specifiers, decl: &Declarator, name: &str) {
+        if spec.local {
+            return self.warn(&format!("variable {name}"), "static variables have no symbol to link with");
+        }
+        if let Err(reason) = symbol(name) {
+            return self.warn(&format!("variable {name}"), reason);
+        }
+        match self.translate(spec, decl) {
+            Ok(Some(typ)) => {
+                if self.define(name) {
+                    // A const variable is immutable, but a pointer to const data is not
+                    let keyword = if spec.constant && decl.pointers == 0 { "let" } else { "var" };
+                    self.output.push_str(&format!("extern {keyword} {name}: {typ}\n"));
+                }
+            }
+            Ok(None) => self.warn(&format!("variable {name}"), "void variables are not supported"),
+            Err(reason) => self.warn(&format!("variable {name}"), &reason),
+        }
+    }
+}
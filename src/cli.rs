@@ -1,3 +1,6 @@
+use crate::doc::DocFormat;
+use elo_ir::cir::Target;
+
 const RED: &str = "\x1b[1;31m";
 const CYAN: &str = "\x1b[1;36m";
 const YELLOW: &str = "\x1b[1;33m";
@@ -25,8 +28,10 @@ pub fn usage(program: &str, command: Option<&CLICommand>) {
         match cmd {
             CLICommand::Run { .. } => eprintln!("usage: {program} run <input> [...<args>]"),
             CLICommand::Build { .. } => {
-                eprintln!("usage: {program} build <input> [-o <output>] [-c] [-l <library>] [-L <path>]")
+                eprintln!("usage: {program} build <input> [-o <output>] [-c] [-l <library>] [-L <path>] [-t <target>] [--lib <kind>]")
             }
+            CLICommand::Doc { .. } => eprintln!("usage: {program} doc <input> [-o <output>] [-f <format>]"),
+            CLICommand::Bindgen { .. } => eprintln!("usage: {program} bindgen <header> [-o <output>]"),
             CLICommand::Help { .. } => eprintln!("usage: {program} help [<command>]"),
         }
     } else {
@@ -41,6 +46,8 @@ pub fn help(program: &str, command: Option<&CLICommand>) {
             eprintln!("commands:");
             eprintln!("    run   | r        Run with the given input file");
             eprintln!("    build | b        Build from given source code");
+            eprintln!("    doc   | d        Generate API documentation from given source code");
+            eprintln!("    bindgen          Generate Elo declarations from a C header");
             eprintln!("    help  | h        Show help message for a specific command or general help");
         }
         Some(CLICommand::Run { .. }) => {
@@ -58,6 +65,55 @@ pub fn help(program: &str, command: Option<&CLICommand>) {
             eprintln!("    -c               Output C source-code file from the compiled program");
             eprintln!("    -l <library>     Schedules linking with <library> in the final executable. Can be passed multiple times.");
             eprintln!("    -L <path>        Add a library linking search path <path>. Can be passed multiple times.");
+            eprintln!("    -t <target>      Target platform of the C source-code file written by `-c`: `x86_64-linux`, `x86_64-macos`, `x86_64-windows`, `aarch64-linux` or `i686-linux`. Defaults to the host.");
+            eprintln!("    --lib <kind>     Build a `static` or `shared` library, or an `object` file, instead of an executable. A C header declaring the exported functions is written next to the output, with the .h extension.");
+        }
+        Some(CLICommand::Bindgen { .. }) => {
+            eprintln!("\nGenerate `extern fn`, `struct`, `enum` and `const` declarations from the given C header. Unsupported declarations are skipped with a warning\n");
+            eprintln!("positional arguments:");
+            eprintln!("    <header>         C header input file");
+            eprintln!("flags:");
+            eprintln!("    -o <output>      Specify output file. Defaults to the header name with the .elo extension");
+        }
+        Some(CLICommand::Doc { .. }) => {
+            eprintln!("\nGenerate API documentation of the functions, structs, enums and constants of the given source code, from its doc comments\n");
+            eprintln!("positional arguments:");
+            eprintln!("    <input>          Source-code input file");
+            eprintln!("flags:");
+            eprintln!("    -o <output>      Specify output file");
+            eprintln!("    -f <format>      Output format: `md` (Markdown, default) or `html`");
+        }
+    }
+}
+
+// What `build --lib` produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibraryKind {
+    Static,
+    Shared,
+    Object,
+}
+
+impl LibraryKind {
+    pub fn from_str(s: &str) -> Option<LibraryKind> {
+        match s {
+            "static" => Some(LibraryKind::Static),
+            "shared" => Some(LibraryKind::Shared),
+            "object" => Some(LibraryKind::Object),
+            _ => None,
+        }
+    }
+
+    // Extension of the output file on the host platform
+    pub fn extension(&self) -> &'static str {
+        match self {
+            LibraryKind::Static if cfg!(windows) => "lib",
+            LibraryKind::Static => "a",
+            LibraryKind::Shared if cfg!(windows) => "dll",
+            LibraryKind::Shared if cfg!(target_os = "macos") => "dylib",
+            LibraryKind::Shared => "so",
+            LibraryKind::Object if cfg!(windows) => "obj",
+            LibraryKind::Object => "o",
         }
     }
 }
@@ -69,11 +125,22 @@ pub enum CLICommand {
         lib_search_paths: Vec<String>,
         libs: Vec<String>,
         c: bool,
+        target: Target,
+        lib: Option<LibraryKind>,
     },
     Run {
         input: String,
         args: Vec<String>,
     },
+    Doc {
+        input: String,
+        output: Option<String>,
+        format: DocFormat,
+    },
+    Bindgen {
+        input: String,
+        output: Option<String>,
+    },
     Help {
         command: Option<String>,
     },
@@ -87,12 +154,23 @@ impl CLICommand {
                 output: None,
                 lib_search_paths: Vec::new(),
                 libs: Vec::new(),
-                c: false
+                c: false,
+                target: Target::host(),
+                lib: None,
             }),
             "run" | "r" => Some(CLICommand::Run {
                 input: String::new(),
                 args: Vec::new(),
             }),
+            "doc" | "d" => Some(CLICommand::Doc {
+                input: String::new(),
+                output: None,
+                format: DocFormat::Markdown,
+            }),
+            "bindgen" => Some(CLICommand::Bindgen {
+                input: String::new(),
+                output: None,
+            }),
             "help" => Some(CLICommand::Help { command: None }),
             _ => None,
         }
@@ -139,6 +217,8 @@ fn parse_build(program: &str, args: &[String]) -> Result<CLICommand, ()> {
     let mut lib_search_paths = Vec::new();
     let mut libs = Vec::new();
     let mut c = false;
+    let mut target = None;
+    let mut lib = None;
 
     let mut i = 2; // Start after the command and program name
     while i < args.len() {
@@ -147,6 +227,22 @@ fn parse_build(program: &str, args: &[String]) -> Result<CLICommand, ()> {
             "-c" => {
                 c = true;
             }
+            "--lib" => {
+                let Some(kind) = args.get(i + 1) else {
+                    usage(program, CLICommand::from_str("build").as_ref());
+                    fatal("expected library kind after `--lib` flag");
+                    return Err(());
+                };
+                i += 1; // skip the next argument
+                if let Some(k) = LibraryKind::from_str(kind) {
+                    lib = Some(k);
+                } else {
+                    usage(program, CLICommand::from_str("build").as_ref());
+                    fatal(&format!("unknown library kind `{kind}`"));
+                    information("available kinds are `static`, `shared` and `object`");
+                    return Err(());
+                }
+            }
             _ if arg.starts_with("-o") => {
                 let rest = arg[2..].to_string();
                 if rest.is_empty() {
@@ -195,6 +291,28 @@ fn parse_build(program: &str, args: &[String]) -> Result<CLICommand, ()> {
                     lib_search_paths.push(rest);
                 }
             }
+            _ if arg.starts_with("-t") => {
+                let mut rest = arg[2..].to_string();
+                if rest.is_empty() {
+                    // get the next argument instead
+                    if let Some(next_arg) = args.get(i + 1) {
+                        rest = next_arg.to_string();
+                        i += 1; // skip the next argument
+                    } else {
+                        usage(program, CLICommand::from_str("build").as_ref());
+                        fatal("expected target after `-t` flag");
+                        return Err(());
+                    }
+                }
+                if let Some(t) = Target::from_str(&rest) {
+                    target = Some(t);
+                } else {
+                    usage(program, CLICommand::from_str("build").as_ref());
+                    fatal(&format!("unknown target `{rest}`"));
+                    information("available targets are `x86_64-linux`, `x86_64-macos`, `x86_64-windows`, `aarch64-linux` and `i686-linux`");
+                    return Err(());
+                }
+            }
             _ if input.is_none() => {
                 input = Some(arg.to_string());
             }
@@ -213,12 +331,150 @@ fn parse_build(program: &str, args: &[String]) -> Result<CLICommand, ()> {
         fatal("expected positional argument: <input>");
         return Err(());
     }
+    // The backend compilers build for the host, so only the C source can be made for another target
+    if target.is_some() && !c {
+        usage(program, CLICommand::from_str("build").as_ref());
+        fatal("`-t` flag can only be used with `-c`");
+        information("compile the C source-code file with a cross compiler for the target");
+        return Err(());
+    }
+    let target = target.unwrap_or_else(Target::host);
     Ok(CLICommand::Build {
         input: input.unwrap(),
         libs,
         lib_search_paths,
         output,
-        c
+        c,
+        target,
+        lib,
+    })
+}
+
+fn parse_doc(program: &str, args: &[String]) -> Result<CLICommand, ()> {
+    if args.len() < 2 {
+        return Err(());
+    }
+
+    let mut input = None;
+    let mut output = None;
+    let mut format = DocFormat::Markdown;
+
+    let mut i = 2; // Start after the command and program name
+    while i < args.len() {
+        let arg = &args[i];
+        match arg.as_str() {
+            _ if arg.starts_with("-o") => {
+                let rest = arg[2..].to_string();
+                if rest.is_empty() {
+                    // get the next argument instead
+                    if let Some(next_arg) = args.get(i + 1) {
+                        output = Some(next_arg.to_string());
+                        i += 1; // skip the next argument
+                    } else {
+                        usage(program, CLICommand::from_str("doc").as_ref());
+                        fatal("expected output file after `-o` flag");
+                        return Err(());
+                    }
+                } else {
+                    output = Some(rest);
+                }
+            }
+            _ if arg.starts_with("-f") => {
+                let mut rest = arg[2..].to_string();
+                if rest.is_empty() {
+                    // get the next argument instead
+                    if let Some(next_arg) = args.get(i + 1) {
+                        rest = next_arg.to_string();
+                        i += 1; // skip the next argument
+                    } else {
+                        usage(program, CLICommand::from_str("doc").as_ref());
+                        fatal("expected format after `-f` flag");
+                        return Err(());
+                    }
+                }
+                if let Some(f) = DocFormat::from_str(&rest) {
+                    format = f;
+                } else {
+                    usage(program, CLICommand::from_str("doc").as_ref());
+                    fatal(&format!("unknown documentation format `{rest}`"));
+                    information("available formats are `md` and `html`");
+                    return Err(());
+                }
+            }
+            _ if input.is_none() => {
+                input = Some(arg.to_string());
+            }
+            x if input.is_some() => {
+                usage(program, CLICommand::from_str("doc").as_ref());
+                fatal(&format!("unexpected positional argument `{x}`"));
+                return Err(());
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if input.is_none() {
+        usage(program, CLICommand::from_str("doc").as_ref());
+        fatal("expected positional argument: <input>");
+        return Err(());
+    }
+    Ok(CLICommand::Doc {
+        input: input.unwrap(),
+        output,
+        format,
+    })
+}
+
+fn parse_bindgen(program: &str, args: &[String]) -> Result<CLICommand, ()> {
+    if args.len() < 2 {
+        return Err(());
+    }
+
+    let mut input = None;
+    let mut output = None;
+
+    let mut i = 2; // Start after the command and program name
+    while i < args.len() {
+        let arg = &args[i];
+        match arg.as_str() {
+            _ if arg.starts_with("-o") => {
+                let rest = arg[2..].to_string();
+                if rest.is_empty() {
+                    // get the next argument instead
+                    if let Some(next_arg) = args.get(i + 1) {
+                        output = Some(next_arg.to_string());
+                        i += 1; // skip the next argument
+                    } else {
+                        usage(program, CLICommand::from_str("bindgen").as_ref());
+                        fatal("expected output file after `-o` flag");
+                        return Err(());
+                    }
+                } else {
+                    output = Some(rest);
+                }
+            }
+            _ if input.is_none() => {
+                input = Some(arg.to_string());
+            }
+            x if input.is_some() => {
+                usage(program, CLICommand::from_str("bindgen").as_ref());
+                fatal(&format!("unexpected positional argument `{x}`"));
+                return Err(());
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if input.is_none() {
+        usage(program, CLICommand::from_str("bindgen").as_ref());
+        fatal("expected positional argument: <header>");
+        return Err(());
+    }
+    Ok(CLICommand::Bindgen {
+        input: input.unwrap(),
+        output,
     })
 }
 
@@ -233,6 +489,8 @@ fn parse_command(program: &str, args: &[String]) -> Result<CLICommand, ()> {
     match command.as_str() {
         "r" | "run" => parse_run(program, args),
         "b" | "build" => parse_build(program, args),
+        "d" | "doc" => parse_doc(program, args),
+        "bindgen" => parse_bindgen(program, args),
         "h" | "help" => parse_help(args),
         _ => {
             usage(program, None);
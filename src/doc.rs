@@ -0,0 +1,444 @@
+// API documentation generation from doc comments (`elo doc`)
+use std::collections::HashMap;
+
+use elo_ir::{ast, cir};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    pub fn from_str(s: &str) -> Option<DocFormat> {
+        match s {
+            "md" | "markdown" => Some(DocFormat::Markdown),
+            "html" => Some(DocFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        }
+    }
+}
+
+// Doc comments and types of the documented items, indexed by their anchor (see `anchor`).
+// They are collected from the AST because the CIR does not keep them.
+#[derive(Default)]
+pub struct Docs {
+    comments: HashMap<String, String>,
+    // Types as written, in the order they are declared: the arguments and the return type of a
+    // function, the fields of a struct, the payload of an enum variant, or the type of an alias or a constant
+    types: HashMap<String, Vec<ast::Type>>,
+}
+
+// Unique identifier of an item in the generated page, like struct.Point or struct.Point.x
+fn anchor(kind: &str, name: &str) -> String {
+    format!("{kind}.{name}")
+}
+
+fn function_types(arguments: &[ast::TypedField], ret: &Option<ast::Type>) -> Vec<ast::Type> {
+    arguments.iter().map(|a| a.typing.clone()).chain(ret.clone()).collect()
+}
+
+pub fn collect_docs(program: &ast::Program) -> Docs {
+    let mut docs = Docs::default();
+    for node in &program.nodes {
+        match &node.stmt {
+            ast::Statement::FnStatement(stmt) => {
+                if let Some(doc) = &stmt.doc {
+                    docs.comments.insert(anchor("fn", &stmt.name), doc.clone());
+                }
+                docs.types.insert(anchor("fn", &stmt.name), function_types(&stmt.arguments, &stmt.ret));
+            }
+            ast::Statement::ExternFnStatement(stmt) => {
+                docs.types.insert(anchor("fn", &stmt.name), function_types(&stmt.arguments, &stmt.ret));
+            }
+            ast::Statement::StructStatement(stmt) => {
+                if let Some(doc) = &stmt.doc {
+                    docs.comments.insert(anchor("struct", &stmt.name), doc.clone());
+                }
+                for field in &stmt.fields {
+                    if let Some(doc) = &field.doc {
+                        docs.comments.insert(anchor("struct", &format!("{}.{}", stmt.name, field.name)), doc.clone());
+                    }
+                }
+                docs.types.insert(anchor("struct", &stmt.name), stmt.fields.iter().map(|f| f.typing.clone()).collect());
+            }
+            ast::Statement::EnumStatement(stmt) => {
+                if let Some(doc) = &stmt.doc {
+                    docs.comments.insert(anchor("enum", &stmt.name), doc.clone());
+                }
+                for variant in &stmt.variants {
+                    let variant_anchor = anchor("enum", &format!("{}.{}", stmt.name, variant.name));
+                    if let Some(doc) = &variant.doc {
+                        docs.comments.insert(variant_anchor.clone(), doc.clone());
+                    }
+                    let types = match &variant.payload {
+                        Some(ast::Payload::Tuple(types)) => types.clone(),
+                        Some(ast::Payload::Struct(fields)) => fields.iter().map(|f| f.typing.clone()).collect(),
+                        None => Vec::new(),
+                    };
+                    docs.types.insert(variant_anchor, types);
+                }
+            }
+            ast::Statement::ConstStatement(stmt) => {
+                if let Some(doc) = &stmt.doc {
+                    docs.comments.insert(anchor("const", &stmt.binding), doc.clone());
+                }
+                docs.types.insert(anchor("const", &stmt.binding), vec![stmt.typing.clone()]);
+            }
+            ast::Statement::TypeStatement(stmt) => {
+                if let Some(doc) = &stmt.doc {
+                    docs.comments.insert(anchor("type", &stmt.name), doc.clone());
+                }
+                docs.types.insert(anchor("type", &stmt.name), vec![stmt.typing.clone()]);
+            }
+            _ => {}
+        }
+    }
+    return docs;
+}
+
+// A type as it is written, like Grid for the {{bool; 8}; 8} it resolves to. The amounts of
+// arrays that are not numbers or names of constants are shown evaluated.
+fn written(typ: &ast::Type, resolved: &cir::Typing) -> String {
+    match (&typ.typing, resolved) {
+        (ast::Typing::Named { name, generic: None }, _) => name.clone(),
+        (ast::Typing::Array { typ, amount }, cir::Typing::Array { typ: item, amount: evaluated }) => {
+            let amount = match &amount.data {
+                ast::ExpressionData::IntegerLiteral { value } => value.to_string(),
+                ast::ExpressionData::Identifier { name } => name.clone(),
+                _ => evaluated.to_string(),
+            };
+            format!("{{{}; {amount}}}", written(typ, item))
+        }
+        (ast::Typing::Slice { typ }, cir::Typing::Slice { typ: item }) => format!("{{{}}}", written(typ, item)),
+        (ast::Typing::Tuple { types }, cir::Typing::Tuple { types: items }) => {
+            let types = types.iter().zip(items).map(|(t, item)| written(t, item)).collect::<Vec<String>>();
+            format!("({})", types.join(", "))
+        }
+        (ast::Typing::Pointer { typ, mutable }, cir::Typing::Pointer { typ: item, .. }) => {
+            format!("*{}{}", if *mutable { "mut " } else { "" }, written(typ, item))
+        }
+        (ast::Typing::Function { args, ret }, cir::Typing::Function { arguments, ret: returned, .. }) => {
+            let arguments = args.iter().zip(arguments).map(|(t, item)| written(t, item)).collect::<Vec<String>>();
+            match ret {
+                Some(ret) => format!("fn ({}): {}", arguments.join(", "), written(ret, returned)),
+                None => format!("fn ({})", arguments.join(", ")),
+            }
+        }
+        _ => resolved.to_string(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            c => result.push(c),
+        }
+    }
+    return result;
+}
+
+pub struct DocGenerator<'a> {
+    format: DocFormat,
+    docs: &'a Docs,
+    // Anchors of the documented types, by their name. Used to cross-link them.
+    types: HashMap<String, String>,
+    pub output: String,
+}
+
+impl<'a> DocGenerator<'a> {
+    pub fn new(format: DocFormat, docs: &'a Docs) -> DocGenerator<'a> {
+        DocGenerator {
+            format,
+            docs,
+            types: HashMap::new(),
+            output: String::new(),
+        }
+    }
+
+    // Render a piece of Elo code (like a function signature), linking every type name
+    // to the place where the type is documented.
+    fn code(&self, code: &str) -> String {
+        let mut result = String::new();
+        let chars: Vec<char> = code.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i].is_alphabetic() || chars[i] == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                // Names followed by : are argument or field names, not types, like the alias a type statement declares
+                let is_binding = chars.get(i) == Some(&':') || result.ends_with("type ");
+                match self.types.get(&word) {
+                    Some(anchor) if !is_binding => result.push_str(&format!("<a href=\"#{anchor}\">{word}</a>")),
+                    _ => result.push_str(&word),
+                }
+                continue;
+            }
+            result.push_str(&escape_html(&chars[i].to_string()));
+            i += 1;
+        }
+        return result;
+    }
+
+    fn doc(&mut self, anchor: &str) {
+        if let Some(doc) = self.docs.comments.get(anchor) {
+            match self.format {
+                // Doc comments are written in Markdown already
+                DocFormat::Markdown => self.output.push_str(&format!("{doc}\n\n")),
+                DocFormat::Html => {
+                    for paragraph in doc.split("\n\n") {
+                        self.output.push_str(&format!("<p>{}</p>\n", escape_html(paragraph)));
+                    }
+                }
+            }
+        }
+    }
+
+    fn section(&mut self, title: &str) {
+        match self.format {
+            DocFormat::Markdown => self.output.push_str(&format!("## {title}\n\n")),
+            DocFormat::Html => self.output.push_str(&format!("<h2>{title}</h2>\n")),
+        }
+    }
+
+    // Heading of a documented item, followed by its code (if any) and its doc comment
+    fn item(&mut self, anchor: &str, title: &str, code: Option<&str>) {
+        match self.format {
+            DocFormat::Markdown => self.output.push_str(&format!("<a id=\"{anchor}\"></a>\n\n### {title}\n\n")),
+            DocFormat::Html => self.output.push_str(&format!("<h3 id=\"{anchor}\">{}</h3>\n", escape_html(title))),
+        }
+        if let Some(code) = code {
+            let code = self.code(code);
+            self.output.push_str(&format!("<pre><code>{code}</code></pre>\n"));
+            if let DocFormat::Markdown = self.format {
+                self.output.push('\n');
+            }
+        }
+        self.doc(anchor);
+    }
+
+    // Types of the item as written, or as resolved if they are not known
+    fn types(&self, anchor: &str, resolved: &[&cir::Typing]) -> Vec<String> {
+        match self.docs.types.get(anchor) {
+            Some(types) if types.len() == resolved.len() => types.iter().zip(resolved).map(|(t, r)| written(t, r)).collect(),
+            _ => resolved.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    // Signature of a function, with the types as written
+    fn signature(&self, head: &cir::FunctionHead) -> String {
+        let mut resolved = head.arguments.iter().map(|(_, typ)| typ).collect::<Vec<&cir::Typing>>();
+        // The variadic tail of an Elo function is written as the type of its items
+        let tail = match head.arguments.last() {
+            Some((_, cir::Typing::Slice { typ })) if head.variadic && !head.extrn => {
+                resolved.pop();
+                resolved.push(typ);
+                true
+            }
+            _ => false,
+        };
+        if head.ret != cir::Typing::Void {
+            resolved.push(&head.ret);
+        }
+        let mut types = self.types(&anchor("fn", &head.name), &resolved);
+        let ret = if head.ret != cir::Typing::Void { types.pop() } else { None };
+        let mut arguments = head.arguments
+            .iter()
+            .zip(&types)
+            .enumerate()
+            .map(|(i, ((name, _), typ))| if tail && i + 1 == head.arguments.len() { format!("{name}: ...{typ}") } else { format!("{name}: {typ}") })
+            .collect::<Vec<String>>();
+        if head.variadic && head.extrn {
+            arguments.push("...".to_string());
+        }
+        let mut signature = format!("fn {}({})", head.name, arguments.join(", "));
+        if let Some(ret) = ret {
+            signature.push_str(&format!(": {ret}"));
+        }
+        if head.extrn { format!("extern {signature}") } else { signature }
+    }
+
+    // Description of a struct field or enum variant, for its row in the table of members
+    fn member_doc(&self, anchor: &str) -> String {
+        match (self.docs.comments.get(anchor), self.format) {
+            (Some(doc), DocFormat::Markdown) => doc.clone(),
+            (Some(doc), DocFormat::Html) => escape_html(doc),
+            (None, _) => String::new(),
+        }
+    }
+
+    // Table of struct fields or enum variants, with their descriptions
+    fn members(&mut self, header: &[&str], rows: Vec<Vec<String>>) {
+        if rows.is_empty() {
+            return;
+        }
+        match self.format {
+            DocFormat::Markdown => {
+                self.output.push_str(&format!("| {} |\n", header.join(" | ")));
+                self.output.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+                for row in rows {
+                    let row = row.iter().map(|cell| cell.replace('\n', " ").replace('|', "\\|")).collect::<Vec<String>>();
+                    self.output.push_str(&format!("| {} |\n", row.join(" | ")));
+                }
+                self.output.push('\n');
+            }
+            DocFormat::Html => {
+                self.output.push_str("<table>\n<tr>");
+                for h in header {
+                    self.output.push_str(&format!("<th>{h}</th>"));
+                }
+                self.output.push_str("</tr>\n");
+                for row in rows {
+                    self.output.push_str("<tr>");
+                    for cell in row {
+                        self.output.push_str(&format!("<td>{cell}</td>"));
+                    }
+                    self.output.push_str("</tr>\n");
+                }
+                self.output.push_str("</table>\n");
+            }
+        }
+    }
+
+    pub fn go(&mut self, program: &cir::Program) {
+        let mut structs = Vec::new();
+        let mut enums = Vec::new();
+        let mut aliases = Vec::new();
+        let mut constants = Vec::new();
+        let mut functions = Vec::new();
+        for stmt in &program.nodes {
+            match &stmt.kind {
+                cir::StatementKind::StructStatement(s) => {
+                    self.types.insert(s.name.clone(), anchor("struct", &s.name));
+                    structs.push(s);
+                }
+                cir::StatementKind::EnumStatement(e) => {
+                    self.types.insert(e.name.clone(), anchor("enum", &e.name));
+                    enums.push(e);
+                }
+                cir::StatementKind::TypeAlias { name, typing } => {
+                    self.types.insert(name.clone(), anchor("type", name));
+                    aliases.push((name, typing));
+                }
+                cir::StatementKind::Constant { binding, value, typing } => constants.push((binding, value, typing)),
+                cir::StatementKind::FnStatement(f) => functions.push(&f.head),
+                cir::StatementKind::ExternFnStatement(head) => functions.push(head),
+                _ => {}
+            }
+        }
+
+        let title = escape_html(&program.filename);
+        match self.format {
+            DocFormat::Markdown => self.output.push_str(&format!("# {title}\n\n")),
+            DocFormat::Html => {
+                self.output.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+                self.output.push_str(&format!("<title>{title}</title>\n"));
+                self.output.push_str("<style>body { font-family: sans-serif; max-width: 60em; margin: auto; } pre { background: #f4f4f4; padding: 0.5em; } td, th { padding: 0.2em 1em; text-align: left; }</style>\n");
+                self.output.push_str(&format!("</head>\n<body>\n<h1>{title}</h1>\n"));
+            }
+        }
+
+        if !structs.is_empty() {
+            self.section("Structs");
+        }
+        for s in structs {
+            let a = anchor("struct", &s.name);
+            let mut declaration = String::new();
+            if s.layout.packed {
+                declaration.push_str("packed ");
+            }
+            if let Some(align) = s.layout.align {
+                declaration.push_str(&format!("align({align}) "));
+            }
+            declaration.push_str(if s.layout.union { "union " } else { "struct " });
+            self.item(&a, &format!("{declaration}{}", s.name), None);
+            let types = self.types(&a, &s.fields.iter().map(|(_, typ)| typ).collect::<Vec<&cir::Typing>>());
+            let mut rows = Vec::new();
+            for ((name, _), typ) in s.fields.iter().zip(types) {
+                let doc = self.member_doc(&anchor("struct", &format!("{}.{}", s.name, name)));
+                rows.push(vec![format!("<code>{name}</code>"), format!("<code>{}</code>", self.code(&typ)), doc]);
+            }
+            self.members(&["Field", "Type", "Description"], rows);
+        }
+
+        if !enums.is_empty() {
+            self.section("Enums");
+        }
+        for e in enums {
+            let a = anchor("enum", &e.name);
+            let title = match e.backing {
+                Some(backing) => format!("enum {}: {}", e.name, cir::Typing::Primitive(backing)),
+                None => format!("enum {}", e.name),
+            };
+            self.item(&a, &title, None);
+            let rows = e.variants
+                .iter()
+                .zip(&e.payloads)
+                .map(|((v, value), payload)| {
+                    let variant = anchor("enum", &format!("{}.{v}", e.name));
+                    let payload = match payload {
+                        Some(cir::Payload::Tuple(types)) => {
+                            let types = self.types(&variant, &types.iter().collect::<Vec<&cir::Typing>>());
+                            self.code(&format!("({})", types.join(", ")))
+                        }
+                        Some(cir::Payload::Struct(fields)) => {
+                            let types = self.types(&variant, &fields.iter().map(|(_, typ)| typ).collect::<Vec<&cir::Typing>>());
+                            let fields = fields.iter().zip(types).map(|((name, _), typ)| format!("{name}: {typ}")).collect::<Vec<String>>();
+                            self.code(&format!(" {{ {} }}", fields.join(", ")))
+                        }
+                        None => String::new(),
+                    };
+                    let doc = self.member_doc(&variant);
+                    vec![format!("<code>{v}{payload}</code>"), format!("<code>{value}</code>"), doc]
+                })
+                .collect();
+            self.members(&["Variant", "Value", "Description"], rows);
+        }
+
+        if !aliases.is_empty() {
+            self.section("Type aliases");
+        }
+        for (name, typing) in aliases {
+            let a = anchor("type", name);
+            let typing = self.types(&a, &[typing]).remove(0);
+            self.item(&a, &format!("type {name}"), Some(&format!("type {name} = {typing}")));
+        }
+
+        if !constants.is_empty() {
+            self.section("Constants");
+        }
+        for (binding, value, typing) in constants {
+            let a = anchor("const", binding);
+            let typing = self.types(&a, &[typing]).remove(0);
+            self.item(&a, &format!("const {binding}"), Some(&format!("const {binding}: {typing} = {value}")));
+        }
+
+        if !functions.is_empty() {
+            self.section("Functions");
+        }
+        for head in functions {
+            let a = anchor("fn", &head.name);
+            let signature = self.signature(head);
+            self.item(&a, &format!("fn {}", head.name), Some(&signature));
+        }
+
+        if let DocFormat::Html = self.format {
+            self.output.push_str("</body>\n</html>\n");
+        }
+    }
+}
@@ -1,4 +1,6 @@
+mod bindgen;
 mod cli;
+mod doc;
 mod tcc;
 #[cfg(test)]
 mod tests;
@@ -7,6 +9,7 @@ use elo_error::{parseerror, semerror};
 use elo_ir::*;
 
 use elo_codegen::generator::*;
+use elo_codegen::header::HeaderGenerator;
 use elo_lexer::{inputfile::InputFile, lexer::Lexer};
 use elo_parser::parser::*;
 use elo_validation::validation::{self, *};
@@ -14,6 +17,7 @@ use elo_validation::validation::{self, *};
 use crate::{cli::*, tcc::TCCState};
 use std::env::args;
 use std::env;
+use std::path::Path;
 use std::process::{Command, Output};
 
 #[allow(dead_code)]
@@ -44,23 +48,43 @@ fn temporary_file(content: &str, filename: &str, mut with: impl FnMut() -> Resul
     return result;
 }
 
-fn setup_elo_backend(compiler: &mut BackendCompiler, _optimize: bool, _debug: bool) {
+// Runtime linked into executables, which provides main
+const RUNTIME: &str = "elort";
+// Runtime of libraries, where C code provides main. Its source is compiled with the program,
+// so every kind of library can be linked by C code without anything else.
+const LIBRARY_RUNTIME: &str = "#include <lib.c>\n";
+
+// The runtime is linked from rt/bin, or included from rt/src when it is None (see LIBRARY_RUNTIME)
+fn setup_elo_backend(compiler: &mut BackendCompiler, runtime: Option<&str>, _optimize: bool, _debug: bool) {
     match compiler {
         BackendCompiler::CLang { path: _, args } => {
             args.push(String::from("-Irt/include"));
-            args.push(String::from("-Lrt/bin"));
-            args.push(String::from("-lelort"));
+            if let Some(runtime) = runtime {
+                args.push(String::from("-Lrt/bin"));
+                args.push(format!("-l{runtime}"));
+            } else {
+                args.push(String::from("-Irt/src"));
+            }
         }
         BackendCompiler::MSVC { path: _, args } => {
-            args.push(String::from("/LIBPATH:\"rt/bin\""));
-            args.push(String::from("/I\"rt/bin\""));
-            args.push(String::from("elort.lib"));
+            if let Some(runtime) = runtime {
+                args.push(String::from("/LIBPATH:\"rt/bin\""));
+                args.push(String::from("/I\"rt/bin\""));
+                args.push(format!("{runtime}.lib"));
+            } else {
+                args.push(String::from("/I\"rt/include\""));
+                args.push(String::from("/I\"rt/src\""));
+            }
         }
         BackendCompiler::TCC(tcc) => {
-            tcc.add_library_path("rt/bin");
-            tcc.add_library("elort");
+            if let Some(runtime) = runtime {
+                tcc.add_library_path("rt/bin");
+                tcc.add_library(runtime);
+                tcc.set_options("-I rt/include");
+            } else {
+                tcc.set_options("-I rt/include -I rt/src");
+            }
             tcc.add_library("c");
-            tcc.set_options("-I rt/include");
         }
     }
 }
@@ -118,6 +142,91 @@ fn compile_backend_executable(
     }
 }
 
+// Put the objects into a static library
+fn archive(objects: &[&str], output: &str) -> Result<(), ()> {
+    let Some(ar) = find_program("ar").or_else(|| find_program("llvm-ar")) else {
+        cli::fatal("could not find `ar` in your system, which is needed to build static libraries");
+        return Err(());
+    };
+    let mut args = vec!["rcs", output];
+    args.extend(objects);
+    let out = invoke_program(&ar, &args)?;
+    if !out.status.success() {
+        eprintln!("{}", String::from_utf8_lossy(&out.stderr));
+        return Err(());
+    }
+    return Ok(());
+}
+
+fn compile_backend_library(
+    cc: BackendCompiler,
+    input: &str,
+    output: &str,
+    kind: LibraryKind,
+    lib_search_paths: &[&str],
+    libs: &[&str],
+) -> Result<(), ()> {
+    // Static libraries are made from an object file
+    let object = if kind == LibraryKind::Static { "temp.o" } else { output };
+    let input = &format!("{input}{LIBRARY_RUNTIME}");
+    match cc {
+        BackendCompiler::CLang { path: clang_path, mut args } => {
+            let filepath = "temp.c";
+            temporary_file(input, filepath, || {
+                match kind {
+                    LibraryKind::Shared => args.extend(["-shared".to_string(), "-fPIC".to_string()]),
+                    LibraryKind::Static | LibraryKind::Object => args.push("-c".to_string()),
+                }
+                args.extend(vec![
+                    filepath.to_string(),
+                    "-o".to_string(),
+                    object.to_string(),
+                ]);
+                if kind == LibraryKind::Shared {
+                    for i in lib_search_paths {
+                        args.push(format!("-L{i}"));
+                    }
+                    for i in libs {
+                        args.push(format!("-l{i}"));
+                    }
+                }
+                let args = args.iter().map(|x| x.as_str()).collect::<Vec<&str>>();
+                let out = invoke_program(&clang_path, &args)?;
+                if !out.status.success() {
+                    eprintln!("{}", String::from_utf8_lossy(&out.stdout));
+                    eprintln!("{}", String::from_utf8_lossy(&out.stderr));
+                    return Err(());
+                }
+                return Ok(());
+            })?;
+        }
+        BackendCompiler::TCC(mut tcc) => {
+            let output_type = match kind {
+                LibraryKind::Shared => tcc::OutputType::DynamicLibrary,
+                LibraryKind::Static | LibraryKind::Object => tcc::OutputType::Object,
+            };
+            tcc.set_output_type(output_type);
+            if tcc.compile_string(input).is_err() {
+                return Err(());
+            }
+            for search in lib_search_paths {
+                tcc.add_library_path(search);
+            }
+            for lib in libs {
+                tcc.add_library(lib);
+            }
+            tcc.output_file(object);
+        }
+        _ => return Err(()),
+    }
+    if kind == LibraryKind::Static {
+        let result = archive(&[object], output);
+        let _ = std::fs::remove_file(object);
+        return result;
+    }
+    return Ok(());
+}
+
 fn find_program(name: &str) -> Option<String> {
     let path_var = env::var_os("PATH")?;
     for dir in env::split_paths(&path_var) {
@@ -166,8 +275,8 @@ fn parse_program(p: InputFile) -> Result<ast::Program, parseerror::ParseError> {
     parser.parse()
 }
 
-fn validate_program(prog: ast::Program) -> Result<cir::Program, Vec<validation::ValidationError>> {
-    let validator = Validator::new();
+fn validate_program(prog: ast::Program, target: cir::Target) -> Result<cir::Program, Vec<validation::ValidationError>> {
+    let validator = Validator::with_target(target);
     validator.go(prog.filename, prog.nodes)
 }
 
@@ -185,31 +294,9 @@ fn strip_extension(path: &str) -> String {
     }
 }
 
-fn parse_and_validate(filename: &str, source: &str) -> cir::Program {
-    let input_file = InputFile {
-        filename,
-        content: source,
-    };
+fn parse_or_exit(input_file: InputFile) -> ast::Program {
     match parse_program(input_file) {
-        Ok(program) => match validate_program(program) {
-            Ok(validated_program) => {
-                return validated_program;
-            }
-            Err(es) => {
-                let error_amount = es.len() as i32;
-                for e in es {
-                    match e {
-                        ValidationError::SemanticChecker(e) => {
-                            semerror::semantic_error(
-                                e.case,
-                                &e.span.into_filespan(input_file),
-                            );
-                        }
-                    }
-                }
-                std::process::exit(error_amount);
-            },
-        },
+        Ok(program) => return program,
         Err(e) => {
             parseerror::parse_error(e.case, &e.span.into_filespan(input_file));
             std::process::exit(1);
@@ -217,6 +304,37 @@ fn parse_and_validate(filename: &str, source: &str) -> cir::Program {
     }
 }
 
+fn validate_or_exit(input_file: InputFile, program: ast::Program, target: cir::Target) -> cir::Program {
+    match validate_program(program, target) {
+        Ok(validated_program) => {
+            return validated_program;
+        }
+        Err(es) => {
+            let error_amount = es.len() as i32;
+            for e in es {
+                match e {
+                    ValidationError::SemanticChecker(e) => {
+                        semerror::semantic_error(
+                            e.case,
+                            &e.span.into_filespan(input_file),
+                        );
+                    }
+                }
+            }
+            std::process::exit(error_amount);
+        },
+    }
+}
+
+fn parse_and_validate(filename: &str, source: &str, target: cir::Target) -> cir::Program {
+    let input_file = InputFile {
+        filename,
+        content: source,
+    };
+    let program = parse_or_exit(input_file);
+    return validate_or_exit(input_file, program, target);
+}
+
 fn tcc_compile(tcc: &mut tcc::TCCState, source: &str, output: tcc::OutputType) -> Result<(), ()> {
     tcc.set_output_type(output);
     if tcc.compile_string(source).is_err() {
@@ -234,10 +352,24 @@ fn main() {
     let mut compiler = BackendCompiler::TCC(tcc::TCCState::new());
 
     match comm {
-        CLICommand::Build { input, output, libs, lib_search_paths, c } => {
+        CLICommand::Build { input, output, libs, lib_search_paths, c, target, lib } => {
             if let Some(content) = std::fs::read_to_string(&input).ok() {
                 let input_name = strip_extension(&input);
-                let program = parse_and_validate(input.as_str(), content.as_str());
+                let program = parse_and_validate(input.as_str(), content.as_str(), target);
+                if lib.is_some() {
+                    // The header is written next to the library, with the same name
+                    let output_h = match &output {
+                        Some(output) => Path::new(output).with_extension("h").to_string_lossy().to_string(),
+                        None => format!("{}.h", input_name),
+                    };
+                    let name = Path::new(&output_h).file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+                    let mut header = HeaderGenerator::new(&name);
+                    header.go(&program);
+                    if let Err(e) = std::fs::write(&output_h, header.output) {
+                        cli::fatal(&format!("could not write header file {}: {}", output_h, e));
+                        std::process::exit(-1);
+                    }
+                }
                 let backend_code = generate_program(program);
                 if c {
                     let output_c = output.unwrap_or(format!("{}.c", input_name));
@@ -252,11 +384,18 @@ fn main() {
                     warning("the generated executable will likely not be as performant or lightweight");
                     warning("falling back to internal TCC backend compilation");
                 }
-                setup_elo_backend(&mut compiler, false, false);
-                let output = output.clone().unwrap_or(format!("{}.out", input_name));
                 let lib_search_paths = lib_search_paths.iter().map(|x| x.as_str()).collect::<Vec<&str>>();
                 let libs = libs.iter().map(|x| x.as_str()).collect::<Vec<&str>>();
-                if let Err(_) = compile_backend_executable(compiler, &backend_code, &output, &lib_search_paths, &libs) {
+                let result = if let Some(kind) = lib {
+                    setup_elo_backend(&mut compiler, None, false, false);
+                    let output = output.clone().unwrap_or(format!("{}.{}", input_name, kind.extension()));
+                    compile_backend_library(compiler, &backend_code, &output, kind, &lib_search_paths, &libs)
+                } else {
+                    setup_elo_backend(&mut compiler, Some(RUNTIME), false, false);
+                    let output = output.clone().unwrap_or(format!("{}.out", input_name));
+                    compile_backend_executable(compiler, &backend_code, &output, &lib_search_paths, &libs)
+                };
+                if let Err(_) = result {
                     cli::critical("could not compile C backend source-code. This is likely a bug");
                     cli::information("if so, please report the bug at https://github.com/elo-lang/elo/issues");
                     std::process::exit(-1);
@@ -266,6 +405,46 @@ fn main() {
                 std::process::exit(-1);
             }
         }
+        CLICommand::Doc { input, output, format } => {
+            if let Some(content) = std::fs::read_to_string(&input).ok() {
+                let input_file = InputFile {
+                    filename: input.as_str(),
+                    content: content.as_str(),
+                };
+                let program = parse_or_exit(input_file);
+                // Doc comments are not kept after validation, so collect them before
+                let docs = doc::collect_docs(&program);
+                let validated_program = validate_or_exit(input_file, program, cir::Target::host());
+                let mut generator = doc::DocGenerator::new(format, &docs);
+                generator.go(&validated_program);
+                let output = output.unwrap_or(format!("{}.{}", strip_extension(&input), format.extension()));
+                if let Err(e) = std::fs::write(&output, generator.output) {
+                    cli::fatal(&format!("could not write output file {}: {}", output, e));
+                    std::process::exit(-1);
+                }
+            } else {
+                cli::fatal(&format!("could not read input file {}", input));
+                std::process::exit(-1);
+            }
+        }
+        CLICommand::Bindgen { input, output } => {
+            if let Some(content) = std::fs::read_to_string(&input).ok() {
+                let filename = input.rsplit(['/', '\\']).next().unwrap_or(&input);
+                let mut generator = bindgen::BindingGenerator::new();
+                generator.go(&content, filename);
+                for w in &generator.warnings {
+                    warning(&format!("{}: {}", input, w));
+                }
+                let output = output.unwrap_or(format!("{}.elo", strip_extension(&input)));
+                if let Err(e) = std::fs::write(&output, generator.output) {
+                    cli::fatal(&format!("could not write output file {}: {}", output, e));
+                    std::process::exit(-1);
+                }
+            } else {
+                cli::fatal(&format!("could not read input file {}", input));
+                std::process::exit(-1);
+            }
+        }
         CLICommand::Help { command } => {
             if let Some(command) = &command {
                 help(&args[0], CLICommand::from_str(command).as_ref());
@@ -278,9 +457,9 @@ fn main() {
             args: arguments,
         } => {
             if let Some(content) = std::fs::read_to_string(&input).ok() {
-                let validated_program = parse_and_validate(input.as_str(), content.as_str());
+                let validated_program = parse_and_validate(input.as_str(), content.as_str(), cir::Target::host());
                 let g = &generate_program(validated_program);
-                setup_elo_backend(&mut compiler, false, false);
+                setup_elo_backend(&mut compiler, Some(RUNTIME), false, false);
                 if let BackendCompiler::TCC(mut tcc) = compiler {
                     if let Err(_) = tcc_compile(&mut tcc, g, tcc::OutputType::Memory) {
                         cli::critical("could not compile C backend source-code. This is likely a bug");
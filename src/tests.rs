@@ -99,16 +99,20 @@ fn unescape_string(string: &str) -> String {
         }
         if escape && ch == 'n' {
             result.push('\n');
+            escape = false;
             continue;
         }
         if escape && ch == 't' {
             result.push('\t');
+            escape = false;
             continue;
         }
         if escape && ch == 'r' {
             result.push('\r');
+            escape = false;
             continue;
         }
+        escape = false;
         result.push(ch);
     }
     result
@@ -177,3 +181,155 @@ fn main() {
         }
     }
 }
+
+// Run the compiler built by build_compiler with the given arguments
+fn run_compiler(args: &[&str]) -> Output {
+    match std::process::Command::new("./target/release/elo").args(args).output() {
+        Ok(out) => out,
+        Err(e) => {
+            eprintln!("ERROR: Could not run the compiler due to error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+// Each program here is documented in every format, and compared to the file
+// next to it with the same name and the extension of the format
+const DOC_TESTS_DIR: &'static str = "examples/tests/doc/";
+
+#[test]
+fn doc() {
+    build_compiler();
+
+    let mut success = true;
+    for entry in std::fs::read_dir(DOC_TESTS_DIR).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "elo") {
+            continue;
+        }
+        for format in ["md", "html"] {
+            let expected = std::fs::read_to_string(path.with_extension(format)).unwrap();
+            let name = path.file_stem().unwrap().to_string_lossy();
+            let output = std::env::temp_dir().join(format!("elo-doc-{name}.{format}"));
+            let output = output.to_string_lossy();
+            let out = run_compiler(&["doc", &path.to_string_lossy(), "-o", &output, "-f", format]);
+            if !out.status.success() {
+                eprintln!("{}: `elo doc -f {format}` failed:", path.display());
+                eprintln!("{}", String::from_utf8_lossy(&out.stderr));
+                success = false;
+                continue;
+            }
+            let actual = std::fs::read_to_string(output.as_ref()).unwrap();
+            let _ = std::fs::remove_file(output.as_ref());
+            if actual != expected {
+                eprintln!("{}: unexpected {format} documentation:", path.display());
+                eprintln!("   expected: {}", escape_string(&expected));
+                eprintln!("   actual: {}", escape_string(&actual));
+                success = false;
+            }
+        }
+    }
+    assert!(success);
+}
+
+// Each program here is built as every kind of library, and linked by the C program
+// next to it, which includes the generated header. The output of the C program is
+// compared to the header of the Elo program, like in the tests above.
+const LIB_TESTS_DIR: &'static str = "examples/tests/lib/";
+
+#[test]
+fn library() {
+    build_compiler();
+
+    let mut success = true;
+    for entry in std::fs::read_dir(LIB_TESTS_DIR).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "elo") {
+            continue;
+        }
+        let test = read_test_file(&path.to_string_lossy());
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        // The header is written next to the library
+        let directory = std::env::temp_dir().join(format!("elo-lib-{name}"));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        let main = path.with_extension("c");
+        for (kind, extension) in [("static", "a"), ("shared", "so"), ("object", "o")] {
+            let library = directory.join(format!("{name}.{extension}"));
+            let library = library.to_string_lossy();
+            let out = run_compiler(&["build", &path.to_string_lossy(), "--lib", kind, "-o", &library]);
+            if !out.status.success() {
+                eprintln!("{}: `elo build --lib {kind}` failed:", path.display());
+                eprintln!("{}", String::from_utf8_lossy(&out.stderr));
+                success = false;
+                continue;
+            }
+            let program = directory.join(format!("{name}-{kind}"));
+            let out = std::process::Command::new("cc")
+                .arg(&main)
+                .arg(library.as_ref())
+                .arg(format!("-I{}", directory.display()))
+                // For the declaration of the runtime context, whose temporaries main.c checks
+                .arg("-Irt/include")
+                .arg("-o")
+                .arg(&program)
+                .output()
+                .unwrap();
+            if !out.status.success() {
+                eprintln!("{}: could not link the {kind} library:", path.display());
+                eprintln!("{}", String::from_utf8_lossy(&out.stderr));
+                success = false;
+                continue;
+            }
+            let out = std::process::Command::new(&program).output().unwrap();
+            success &= test_file(&format!("{} ({kind})", path.display()), out, test.clone());
+        }
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+    assert!(success);
+}
+
+// Bindings of each header here are compared to the file next to it with the same
+// name, and must be valid Elo code
+const BINDGEN_TESTS_DIR: &'static str = "examples/tests/bindgen/";
+
+#[test]
+fn bindgen() {
+    build_compiler();
+
+    let mut success = true;
+    for entry in std::fs::read_dir(BINDGEN_TESTS_DIR).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "h") {
+            continue;
+        }
+        let expected = std::fs::read_to_string(path.with_extension("elo")).unwrap();
+        let name = path.file_stem().unwrap().to_string_lossy();
+        let output = std::env::temp_dir().join(format!("elo-bindgen-{name}.elo"));
+        let output = output.to_string_lossy();
+        let out = run_compiler(&["bindgen", &path.to_string_lossy(), "-o", &output]);
+        if !out.status.success() {
+            eprintln!("{}: `elo bindgen` failed:", path.display());
+            eprintln!("{}", String::from_utf8_lossy(&out.stderr));
+            success = false;
+            continue;
+        }
+        let actual = std::fs::read_to_string(output.as_ref()).unwrap();
+        if actual != expected {
+            eprintln!("{}: unexpected bindings:", path.display());
+            eprintln!("   expected: {}", escape_string(&expected));
+            eprintln!("   actual: {}", escape_string(&actual));
+            success = false;
+        }
+        let backend = std::env::temp_dir().join(format!("elo-bindgen-{name}.c"));
+        let out = run_compiler(&["build", &output, "-c", "-o", &backend.to_string_lossy()]);
+        if !out.status.success() {
+            eprintln!("{}: the bindings are not valid Elo code:", path.display());
+            eprintln!("{}", String::from_utf8_lossy(&out.stderr));
+            success = false;
+        }
+        let _ = std::fs::remove_file(output.as_ref());
+        let _ = std::fs::remove_file(backend);
+    }
+    assert!(success);
+}